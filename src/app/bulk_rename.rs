@@ -1,11 +1,26 @@
 //! Bulk rename functionality
+//!
+//! The selected names are edited in `$EDITOR`, checked and shown old → new
+//! for confirmation before anything on disk changes.
 
 use std::path::{Path, PathBuf};
 
 use super::App;
+use crate::filesystem::{RenamePlan, RenameStep};
+use crate::jobs::{JobId, JobKind};
+
+/// A checked bulk rename waiting for confirmation
+pub struct RenamePreview {
+    pub plan: RenamePlan,
+    /// First change shown in the panel
+    pub scroll: usize,
+}
 
 impl App {
     pub fn execute_bulk_rename(&mut self) {
+        if self.refuse_rename_here("rename") {
+            return;
+        }
         let paths = self.selected_paths();
         if paths.is_empty() {
             return;
@@ -19,11 +34,67 @@ impl App {
         }
 
         if run_editor(&temp_path).is_ok() {
-            apply_renames(&paths, &temp_path);
+            let edited = std::fs::read_to_string(&temp_path).unwrap_or_default();
+            match RenamePlan::new(&paths, &edited) {
+                Ok(plan) if plan.is_empty() => self.show_message("no names changed"),
+                Ok(plan) => self.rename_preview = Some(RenamePreview { plan, scroll: 0 }),
+                Err(e) => self.show_error(format!("rename: {e}")),
+            }
         }
 
         let _ = std::fs::remove_file(&temp_path);
         self.exit_visual_if_active();
+    }
+
+    /// Answer the rename preview: Enter or y renames, Esc, n or q cancels,
+    /// j and k scroll
+    pub fn handle_rename_preview_key(&mut self, key: &str) -> bool {
+        let Some(preview) = self.rename_preview.as_mut() else {
+            return false;
+        };
+        match key {
+            "j" | "Down" => {
+                let last = preview.plan.changes.len().saturating_sub(1);
+                preview.scroll = (preview.scroll + 1).min(last);
+            }
+            "k" | "Up" => preview.scroll = preview.scroll.saturating_sub(1),
+            "\n" | "y" => {
+                if let Some(preview) = self.rename_preview.take() {
+                    self.apply_rename_plan(preview.plan);
+                }
+            }
+            "\u{1b}" | "n" | "q" => {
+                self.rename_preview = None;
+                self.show_message("rename cancelled");
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Make the planned changes one at a time as jobs of one batch, so they
+    /// are reported and undone together; the first failure stops the rest
+    pub(super) fn apply_rename_plan(&mut self, plan: RenamePlan) {
+        let parent: Option<JobId> = (plan.steps.len() > 1).then(|| {
+            let label = format!("Rename {} items", plan.changes.len());
+            self.job_queue.submit(JobKind::Batch { label })
+        });
+        for step in plan.steps {
+            let kind = match step {
+                RenameStep::CreateDir(path) => JobKind::Mkdir {
+                    path,
+                    parents: true,
+                },
+                RenameStep::Rename(src, dest) => JobKind::Rename { src, dest },
+            };
+            if let (_, Err(e)) = self
+                .journal
+                .run_now(&mut self.job_queue, kind, parent, None)
+            {
+                self.show_error(format!("rename stopped: {e}"));
+                break;
+            }
+        }
         if let Some(browser) = self.browser_mut() {
             browser.refresh();
         }
@@ -42,25 +113,3 @@ fn run_editor(path: &Path) -> std::io::Result<std::process::ExitStatus> {
     let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
     std::process::Command::new(&editor).arg(path).status()
 }
-
-fn apply_renames(paths: &[PathBuf], temp_path: &Path) {
-    if let Ok(content) = std::fs::read_to_string(temp_path) {
-        let new_names: Vec<&str> = content.lines().collect();
-        for (old_path, new_name) in paths.iter().zip(new_names.iter()) {
-            rename_if_valid(old_path, new_name);
-        }
-    }
-}
-
-fn rename_if_valid(old_path: &Path, new_name: &str) {
-    let new_name = new_name.trim();
-    if new_name.is_empty() {
-        return;
-    }
-    if let Some(parent) = old_path.parent() {
-        let new_path = parent.join(new_name);
-        if old_path != new_path {
-            let _ = std::fs::rename(old_path, &new_path);
-        }
-    }
-}
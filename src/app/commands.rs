@@ -1,6 +1,7 @@
 //! Command mode command implementations
 
 use super::{App, CommandResult};
+use crate::filesystem::ConflictPolicy;
 use crate::navigation::Browser;
 
 impl App {
@@ -8,6 +9,9 @@ impl App {
         if let Some(result) = self.try_set_theme(arg) {
             return result;
         }
+        if let Some(result) = self.try_set_conflict(arg) {
+            return result;
+        }
 
         let (negated, option) = parse_set_option(arg);
         self.apply_set_option(option, negated)
@@ -27,6 +31,18 @@ impl App {
         Some(CommandResult::ThemeChange(theme_name))
     }
 
+    fn try_set_conflict(&mut self, arg: &str) -> Option<CommandResult> {
+        let (key, value) = arg.split_once('=')?;
+        if key.trim() != "conflict" {
+            return None;
+        }
+        match ConflictPolicy::parse(value.trim()) {
+            Some(policy) => self.conflict_policy = policy,
+            None => self.show_error(format!("conflict: unknown policy '{}'", value.trim())),
+        }
+        Some(CommandResult::Redraw)
+    }
+
     fn apply_set_option(&mut self, option: &str, negated: bool) -> CommandResult {
         match option {
             "hidden" | "hid" => self.apply_hidden_option(negated),
@@ -35,6 +51,14 @@ impl App {
                 CommandResult::Redraw
             }
             "parent" | "par" => self.apply_parent_option(negated),
+            "persistselect" | "ps" => {
+                self.persist_selection = !negated;
+                CommandResult::Redraw
+            }
+            "preserve" | "pr" => {
+                self.preserve = !negated;
+                CommandResult::Redraw
+            }
             _ => CommandResult::Redraw,
         }
     }
@@ -74,6 +98,7 @@ impl App {
         if self.splits.len() <= 1 {
             return CommandResult::Exit;
         }
+        self.cancel_focused_find();
         self.splits.close_focused();
         CommandResult::Redraw
     }
@@ -118,11 +143,8 @@ impl App {
     }
 
     fn cmd_symlink(&mut self) -> CommandResult {
-        if let Some(browser) = self.browser() {
-            let dest_dir = browser.path.clone();
-            for src in &self.clipboard.paths {
-                let _ = crate::filesystem::create_symlink(src, &dest_dir);
-            }
+        if let Some(dest_dir) = self.browser().map(|b| b.path.clone()) {
+            self.link_paths(self.clipboard.paths.clone(), &dest_dir);
         }
         if let Some(browser) = self.browser_mut() {
             browser.refresh();
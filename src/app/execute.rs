@@ -9,6 +9,7 @@ impl App {
     pub fn execute(&mut self, action: Action) -> bool {
         match action {
             Action::None | Action::Pending => false,
+            Action::Operator(op) => self.execute_operator_line(op, 1),
 
             // Navigation actions
             Action::MoveCursor(delta) => self.execute_move_cursor(delta),
@@ -16,6 +17,8 @@ impl App {
             Action::CursorToBottom => self.execute_cursor_to_bottom(),
             Action::NextDirectory => self.execute_next_directory(),
             Action::PrevDirectory => self.execute_prev_directory(),
+            Action::NextGroup => self.execute_next_group(),
+            Action::PrevGroup => self.execute_prev_group(),
             Action::EnterDirectory => self.execute_enter_directory(),
             Action::ParentDirectory => self.execute_parent_directory(),
 
@@ -27,7 +30,17 @@ impl App {
             Action::Delete => self.execute_delete(),
             Action::Trash => self.execute_trash(),
             Action::CreateSymlink => self.execute_create_symlink(),
+            Action::RenameEntry => self.execute_rename_prompt(false),
+            Action::RenameEntryAppend => self.execute_rename_prompt(true),
+            Action::BulkRename => {
+                self.execute_bulk_rename();
+                true
+            }
             Action::ExtractArchive => self.execute_extract_archive(),
+            Action::Undo => self.execute_undo(),
+            Action::Redo => self.execute_redo(),
+            Action::RevealEntry => self.execute_reveal_entry(),
+            Action::FuzzyFind => self.execute_fuzzy_find(),
 
             // Mode changes
             Action::EnterVisualMode => self.execute_enter_visual_mode(),
@@ -68,6 +81,8 @@ impl App {
             // Bookmark actions
             Action::SetMark(c) => self.execute_set_mark(c),
             Action::JumpToMark(c) => self.execute_jump_to_mark(c),
+            Action::JumpBack => self.execute_jump_back(),
+            Action::JumpForward => self.execute_jump_forward(),
 
             // Sort/filter actions
             Action::CycleSort => self.execute_cycle_sort(),
@@ -89,12 +104,18 @@ impl App {
             Action::PrevError => self.execute_prev_error(),
             Action::ToggleErrorList => self.execute_toggle_error_list(),
             Action::ToggleFeatureList => self.execute_toggle_feature_list(),
+            Action::VisualSwapAnchor => self.execute_visual_swap_anchor(),
+            Action::ReselectVisual => self.execute_reselect_visual(),
+            Action::LineEdit(key) => self.execute_line_edit(key),
+            Action::ToggleSelect => self.execute_toggle_select(),
+            Action::SelectAll => self.execute_select_all(),
+            Action::InvertSelection => self.execute_invert_selection(),
         }
     }
 
     /// Execute a command from command mode
     pub fn execute_command(&mut self) -> CommandResult {
-        let cmd = self.command_buffer.trim().to_string();
+        let cmd = self.command_line.text().trim().to_string();
 
         if let Some(rest) = cmd.strip_prefix("set ").or_else(|| cmd.strip_prefix("se ")) {
             return self.execute_set_command(rest.trim());
@@ -107,6 +128,77 @@ impl App {
             return self.execute_filter_command(pattern.trim());
         }
 
+        if cmd == "!!" {
+            return self.execute_repeat_shell_command();
+        }
+
+        if let Some(command) = cmd.strip_prefix('!') {
+            return self.execute_shell_command(command.trim());
+        }
+
+        if let Some(command) = command_arg(&cmd, "shell").or_else(|| command_arg(&cmd, "sh")) {
+            return match command {
+                "" => self.execute_open_terminal(),
+                _ => self.execute_shell_command(command),
+            };
+        }
+
+        if let Some(path) = command_arg(&cmd, "cd") {
+            return self.execute_cd_command(path);
+        }
+
+        if let Some(path) = command_arg(&cmd, "edit").or_else(|| command_arg(&cmd, "e")) {
+            return self.execute_edit_command(path);
+        }
+
+        if let Some(args) = command_arg(&cmd, "find") {
+            return self.execute_find_command(args);
+        }
+
+        if let Some(args) = command_arg(&cmd, "grep") {
+            return self.execute_grep_command(args);
+        }
+
+        if let Some(args) = command_arg(&cmd, "zimport") {
+            return self.execute_zimport_command(args);
+        }
+
+        if let Some(args) = command_arg(&cmd, "zi") {
+            return self.execute_zi_command(args);
+        }
+
+        if let Some(args) = command_arg(&cmd, "z") {
+            return self.execute_z_command(args);
+        }
+
+        if cmd == "marks" {
+            return self.execute_marks_command();
+        }
+
+        if let Some(marks) = command_arg(&cmd, "delmarks") {
+            return self.execute_delmarks_command(marks);
+        }
+
+        if cmd == "pwd" {
+            return self.execute_pwd_command();
+        }
+
+        if let Some(path) = command_arg(&cmd, "mkdir") {
+            return self.execute_mkdir_command(path);
+        }
+
+        if let Some(path) = command_arg(&cmd, "touch") {
+            return self.execute_touch_command(path);
+        }
+
+        if let Some(name) = cmd.strip_prefix("rename ") {
+            return self.execute_rename_command(name.trim());
+        }
+
+        if let Some(dest) = command_arg(&cmd, "retry") {
+            return self.execute_retry_command(dest);
+        }
+
         if let Some(mode) = cmd.strip_prefix("sort ") {
             return self.execute_sort_command(mode.trim());
         }
@@ -115,6 +207,31 @@ impl App {
             return self.execute_chmod_command(mode.trim());
         }
 
+        if let Some(pattern) = command_arg(&cmd, "unselect") {
+            return self.execute_select_command(pattern, false);
+        }
+
+        if let Some(pattern) = command_arg(&cmd, "select") {
+            return self.execute_select_command(pattern, true);
+        }
+
+        if cmd == "restore" {
+            return self.execute_restore_command();
+        }
+
+        if cmd == "emptytrash" {
+            return self.execute_empty_trash_command();
+        }
+
+        if cmd == "invert" {
+            self.execute_invert_selection();
+            return CommandResult::Redraw;
+        }
+
+        if let Some(result) = self.execute_substitute_command(&cmd) {
+            return result;
+        }
+
         if cmd == "rename" || cmd == "bulkrename" {
             self.execute_bulk_rename();
             return CommandResult::Redraw;
@@ -156,14 +273,26 @@ impl App {
     }
 
     fn execute_chmod_command(&mut self, mode: &str) -> CommandResult {
-        if let Some(browser) = self.browser()
-            && let Some(entry) = browser.current_entry()
-        {
-            let _ = crate::filesystem::chmod(&entry.path, mode);
-        }
+        let jobs: Vec<_> = self
+            .selected_paths()
+            .into_iter()
+            .map(|path| crate::jobs::JobKind::Chmod {
+                path,
+                mode: mode.to_string(),
+            })
+            .collect();
+        self.submit_batch("Chmod", jobs);
+        self.exit_visual_if_active();
         if let Some(browser) = self.browser_mut() {
             browser.refresh();
         }
         CommandResult::Redraw
     }
 }
+
+/// The trimmed argument of `cmd` when it is `name` alone or followed by a space
+fn command_arg<'a>(cmd: &'a str, name: &str) -> Option<&'a str> {
+    cmd.strip_prefix(name)
+        .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+        .map(str::trim)
+}
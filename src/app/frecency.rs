@@ -0,0 +1,100 @@
+//! Jumping to visited directories: `:z`, `:zi` and `:zimport`
+//!
+//! Every directory a browser changes to is recorded in the frecency
+//! database. `:z` goes to the best directory matching its terms and `:zi`
+//! lists the candidates in the fuzzy finder to pick from.
+
+use std::path::PathBuf;
+
+use super::{App, CommandResult};
+use crate::navigation::FrecencySource;
+
+impl App {
+    /// Record the directories the browsers changed to since the last call
+    pub fn record_visits(&mut self) {
+        for id in self.splits.leaf_ids() {
+            let visits = self
+                .splits
+                .get_mut(id)
+                .map(|browser| browser.take_visits())
+                .unwrap_or_default();
+            for path in visits {
+                self.frecency.visit(&path);
+            }
+        }
+    }
+
+    /// `:z TERMS` goes to the best visited directory holding the terms in
+    /// order, the last one in its name; directories that no longer exist
+    /// are forgotten
+    pub fn execute_z_command(&mut self, args: &str) -> CommandResult {
+        let terms: Vec<&str> = args.split_whitespace().collect();
+        if terms.is_empty() {
+            self.show_error("z: expected a directory name");
+            return CommandResult::Redraw;
+        }
+        let current = self.browser().map(|b| b.path.clone());
+        let candidates: Vec<PathBuf> = self
+            .frecency
+            .ranked(&terms)
+            .into_iter()
+            .filter(|path| Some(*path) != current.as_deref())
+            .map(PathBuf::from)
+            .collect();
+        for path in candidates {
+            if path.is_dir() {
+                self.navigate_focused(&path);
+                return CommandResult::Redraw;
+            }
+            self.frecency.remove(&path);
+        }
+        self.show_error(format!("z: no visited directory matches '{args}'"));
+        CommandResult::Redraw
+    }
+
+    /// `:zi [TERMS]` picks from the matching directories, best first
+    pub fn execute_zi_command(&mut self, args: &str) -> CommandResult {
+        let terms: Vec<&str> = args.split_whitespace().collect();
+        self.open_directory_finder(&terms);
+        if self
+            .fuzzy_finder
+            .as_ref()
+            .is_some_and(|finder| finder.candidates.is_empty())
+        {
+            self.fuzzy_finder = None;
+            self.show_error(match args {
+                "" => "zi: no directories visited yet".to_string(),
+                _ => format!("zi: no visited directory matches '{args}'"),
+            });
+        }
+        CommandResult::Redraw
+    }
+
+    /// `:zimport zoxide|autojump [PATH]` merges the database of either
+    /// tool, read from where it keeps it unless PATH is given
+    pub fn execute_zimport_command(&mut self, args: &str) -> CommandResult {
+        let (name, path) = args.split_once(' ').unwrap_or((args, ""));
+        let Some(source) = FrecencySource::parse(name) else {
+            self.show_error("zimport: expected zoxide or autojump");
+            return CommandResult::Redraw;
+        };
+        let path = match path.trim() {
+            "" => source
+                .default_path()
+                .ok_or_else(|| "data directory not found".to_string()),
+            path => self.resolve_path(path),
+        };
+        let result = path.and_then(|path| {
+            let data = std::fs::read(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+            self.frecency.import(source, &data)
+        });
+        match result {
+            Ok(count) => self.show_message(format!(
+                "imported {count} director{} from {name}",
+                if count == 1 { "y" } else { "ies" }
+            )),
+            Err(e) => self.show_error(format!("zimport: {e}")),
+        }
+        CommandResult::Redraw
+    }
+}
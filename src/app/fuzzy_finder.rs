@@ -0,0 +1,247 @@
+//! Fuzzy finder popup for files below the focused directory, and for
+//! visited directories (`:zi`)
+//!
+//! The tree is indexed by a find job whose results are added as they
+//! arrive, the matches being ranked again with each batch and each key
+//! typed. Visited directories keep their frecency order, the query only
+//! narrowing them the way `:z` terms do. Enter shows the choice in the
+//! focused split, Ctrl-v and Ctrl-s in a new vertical or horizontal split.
+
+use std::path::{Path, PathBuf};
+
+use super::App;
+use crate::cmdline::LineEditor;
+use crate::filesystem::FindQuery;
+use crate::input::line_edit_key;
+use crate::jobs::{JobId, JobKind};
+use crate::navigation::{fuzzy_score, path_matches};
+
+/// The index skips what git ignores, as well as `.git` itself
+const INDEX_QUERY: &str = "-type f -gitignore";
+
+/// Files listed for an empty query, in the order they were found
+const UNRANKED_LIMIT: usize = 500;
+
+/// What the fuzzy finder picks from
+pub enum FinderSource {
+    /// Files below `root`, indexed by a find job
+    Files {
+        job: JobId,
+        root: PathBuf,
+        show_hidden: bool,
+    },
+    /// Directories from the frecency database, best first
+    Directories,
+}
+
+/// State of the open fuzzy finder
+pub struct FuzzyFinder {
+    pub source: FinderSource,
+    pub query: LineEditor,
+    /// Files found so far by their path from the root, or directories
+    pub candidates: Vec<String>,
+    /// Indices into `candidates` of those matching the query, best first
+    pub matches: Vec<usize>,
+    pub cursor: usize,
+}
+
+impl FuzzyFinder {
+    fn new(source: FinderSource, candidates: Vec<String>) -> Self {
+        let mut finder = Self {
+            source,
+            query: LineEditor::new(),
+            candidates,
+            matches: Vec::new(),
+            cursor: 0,
+        };
+        finder.rank();
+        finder
+    }
+
+    /// Add indexed files; hidden ones are left out unless shown
+    pub fn add_files(&mut self, paths: &[PathBuf]) {
+        let FinderSource::Files {
+            root, show_hidden, ..
+        } = &self.source
+        else {
+            return;
+        };
+        let files = paths.iter().filter_map(|path| {
+            let relative = path.strip_prefix(root).ok()?;
+            let hidden = relative
+                .iter()
+                .any(|part| part.to_string_lossy().starts_with('.'));
+            (*show_hidden || !hidden).then(|| relative.to_string_lossy().into_owned())
+        });
+        self.candidates.extend(files);
+        self.rank();
+    }
+
+    /// Match the candidates against the query, best first; ties go to
+    /// shorter paths
+    fn rank(&mut self) {
+        let query = self.query.text();
+        if let FinderSource::Directories = self.source {
+            let terms: Vec<&str> = query.split_whitespace().collect();
+            self.matches = (0..self.candidates.len())
+                .filter(|&i| path_matches(&terms, Path::new(&self.candidates[i])))
+                .collect();
+        } else if query.trim().is_empty() {
+            self.matches = (0..self.candidates.len().min(UNRANKED_LIMIT)).collect();
+        } else {
+            let mut scored: Vec<(i32, usize)> = self
+                .candidates
+                .iter()
+                .enumerate()
+                .filter_map(|(i, file)| Some((fuzzy_score(query, file)?, i)))
+                .collect();
+            scored.sort_by(|(a_score, a), (b_score, b)| {
+                b_score
+                    .cmp(a_score)
+                    .then(self.candidates[*a].len().cmp(&self.candidates[*b].len()))
+                    .then(self.candidates[*a].cmp(&self.candidates[*b]))
+            });
+            self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        }
+        self.cursor = self.cursor.min(self.matches.len().saturating_sub(1));
+    }
+
+    fn selected(&self) -> Option<PathBuf> {
+        let candidate = &self.candidates[*self.matches.get(self.cursor)?];
+        match &self.source {
+            FinderSource::Files { root, .. } => Some(root.join(candidate)),
+            FinderSource::Directories => Some(PathBuf::from(candidate)),
+        }
+    }
+
+    /// The find job indexing the tree, if any
+    fn job(&self) -> Option<JobId> {
+        match self.source {
+            FinderSource::Files { job, .. } => Some(job),
+            FinderSource::Directories => None,
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+}
+
+impl App {
+    /// Open the fuzzy finder on the focused directory
+    pub fn execute_fuzzy_find(&mut self) -> bool {
+        let Some(browser) = self.browser() else {
+            return false;
+        };
+        if browser.get_archive_path().is_some() || browser.in_trash() {
+            self.show_error("fuzzy finder: only works in a directory");
+            return true;
+        }
+        let (root, show_hidden) = (browser.path.clone(), browser.show_hidden);
+        let query = FindQuery::parse(INDEX_QUERY).expect("valid index query");
+        let job = self.submit_job_in(
+            JobKind::Find {
+                root: root.clone(),
+                query,
+            },
+            None,
+        );
+        let source = FinderSource::Files {
+            job,
+            root,
+            show_hidden,
+        };
+        self.fuzzy_finder = Some(FuzzyFinder::new(source, Vec::new()));
+        true
+    }
+
+    /// Open the fuzzy finder on the visited directories matching `terms`,
+    /// other than the focused one
+    pub(super) fn open_directory_finder(&mut self, terms: &[&str]) {
+        let current = self.browser().map(|b| b.path.clone());
+        let dirs = self
+            .frecency
+            .ranked(terms)
+            .into_iter()
+            .filter(|path| Some(*path) != current.as_deref() && path.is_dir())
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        self.fuzzy_finder = Some(FuzzyFinder::new(FinderSource::Directories, dirs));
+    }
+
+    /// Keys while the fuzzy finder is open: typing edits the query,
+    /// Up/Down or Ctrl-p/Ctrl-n move, Enter opens the choice in the
+    /// focused split, Ctrl-v/Ctrl-s in a new one and Esc closes
+    pub fn handle_fuzzy_finder_key(&mut self, key: &str) -> bool {
+        let Some(finder) = self.fuzzy_finder.as_mut() else {
+            return false;
+        };
+        match key {
+            "\u{1b}" | "C-c" => self.close_fuzzy_finder(),
+            "Up" | "C-p" | "C-k" => finder.move_cursor(-1),
+            "Down" | "C-n" | "C-j" => finder.move_cursor(1),
+            "PageUp" => finder.move_cursor(-10),
+            "PageDown" => finder.move_cursor(10),
+            "\n" | "C-v" | "C-s" => {
+                let Some(path) = finder.selected() else {
+                    return false;
+                };
+                let directory = matches!(finder.source, FinderSource::Directories);
+                self.close_fuzzy_finder();
+                match key {
+                    "C-v" => self.execute_split_vertical(),
+                    "C-s" => self.execute_split_horizontal(),
+                    _ => true,
+                };
+                if directory {
+                    self.navigate_focused(&path);
+                } else {
+                    self.reveal_path(&path);
+                }
+            }
+            "\u{8}" => {
+                finder.query.backspace();
+                finder.cursor = 0;
+                finder.rank();
+            }
+            _ => {
+                if let Some(edit) = line_edit_key(key) {
+                    finder.query.apply(edit);
+                } else {
+                    let mut chars = key.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) if !c.is_control() => finder.query.insert(c),
+                        _ => return false,
+                    }
+                }
+                finder.cursor = 0;
+                finder.rank();
+            }
+        }
+        true
+    }
+
+    /// Add the files a fuzzy finder's index job found
+    pub(super) fn deliver_indexed_files(&mut self, job: JobId, paths: &[PathBuf]) {
+        if let Some(finder) = self.fuzzy_finder.as_mut()
+            && finder.job() == Some(job)
+        {
+            finder.add_files(paths);
+        }
+    }
+
+    /// Whether the open fuzzy finder is still indexing
+    pub fn fuzzy_finder_indexing(&self) -> bool {
+        self.fuzzy_finder
+            .as_ref()
+            .and_then(|finder| self.job_queue.get(finder.job()?))
+            .is_some_and(|job| job.is_active())
+    }
+
+    fn close_fuzzy_finder(&mut self) {
+        if let Some(job) = self.fuzzy_finder.take().and_then(|finder| finder.job()) {
+            self.job_queue.cancel(job);
+        }
+    }
+}
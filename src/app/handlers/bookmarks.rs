@@ -1,20 +1,23 @@
 //! Bookmark and sort action handlers
 
+use std::path::Path;
+
 use crate::app::App;
 
 impl App {
     pub fn execute_set_mark(&mut self, c: char) -> bool {
         if let Some(browser) = self.browser() {
-            self.bookmarks.insert(c, browser.path.clone());
+            self.bookmarks.set(c, browser.path.clone());
         }
         false
     }
 
     pub fn execute_jump_to_mark(&mut self, c: char) -> bool {
-        if let Some(path) = self.bookmarks.get(&c).cloned()
-            && let Some(browser) = self.browser_mut()
-        {
-            browser.navigate_to(&path);
+        match self.bookmarks.get(c).map(Path::to_path_buf) {
+            Some(path) => {
+                self.navigate_focused(&path);
+            }
+            None => self.show_error(format!("mark '{c}' is not set")),
         }
         true
     }
@@ -47,10 +50,10 @@ impl App {
     }
 
     pub fn apply_search_filter(&mut self) {
-        let pattern = if self.search_buffer.is_empty() {
+        let pattern = if self.search_line.is_empty() {
             None
         } else {
-            Some(self.search_buffer.clone())
+            Some(self.search_line.text().to_string())
         };
 
         if let Some(browser) = self.browser_mut() {
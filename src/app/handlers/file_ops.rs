@@ -3,10 +3,33 @@
 use std::path::{Path, PathBuf};
 
 use crate::app::App;
-use crate::filesystem;
 use crate::input::Mode;
 use crate::jobs;
 
+/// A permanent delete waiting for a yes
+pub struct DeletePrompt {
+    paths: Vec<PathBuf>,
+    /// Whether this empties the trash, which also drops orphaned records
+    empties_trash: bool,
+}
+
+impl DeletePrompt {
+    /// Status bar question for the delete
+    pub fn prompt(&self) -> String {
+        let count = self.paths.len();
+        if self.empties_trash {
+            return format!("empty the trash, deleting {count} entries for good? [y/N]");
+        }
+        match self.paths.as_slice() {
+            [path] => {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                format!("delete {name} for good? [y/N]")
+            }
+            _ => format!("delete {count} entries for good? [y/N]"),
+        }
+    }
+}
+
 impl App {
     pub fn execute_open_file(&mut self) -> bool {
         let paths = self.get_paths_for_open();
@@ -17,10 +40,8 @@ impl App {
     }
 
     fn get_paths_for_open(&self) -> Vec<PathBuf> {
-        if self.mode == Mode::Visual {
-            self.browser()
-                .map(|b| self.selection.to_paths(&b.entries))
-                .unwrap_or_default()
+        if !self.selection.is_empty() {
+            self.selected_paths()
         } else {
             self.browser()
                 .and_then(|b| b.current_entry())
@@ -69,14 +90,14 @@ impl App {
     }
 
     fn get_archive_file_paths(&self, browser: &crate::navigation::Browser) -> Vec<String> {
-        if self.selection.is_empty() {
+        let paths = self.selection.to_paths(&browser.entries, false);
+        if paths.is_empty() && !self.selection.has_range() {
             browser
                 .current_entry()
                 .map(|e| vec![e.path.to_string_lossy().to_string()])
                 .unwrap_or_default()
         } else {
-            self.selection
-                .to_paths(&browser.entries)
+            paths
                 .iter()
                 .map(|p| p.to_string_lossy().to_string())
                 .collect()
@@ -93,7 +114,14 @@ impl App {
         let paste_action = self.determine_paste_action();
         match paste_action {
             PasteAction::Archive { dest_dir } => {
-                let _ = self.clipboard.paste_to(&dest_dir);
+                match self.clipboard.paste_to(&dest_dir, self.conflict_policy) {
+                    Ok(0) => {}
+                    Ok(skipped) => self.show_message(format!("skipped {skipped} existing")),
+                    Err(e) => self.show_error(format!("paste: {e}")),
+                }
+            }
+            PasteAction::Filesystem { .. } if self.in_trash_view() => {
+                self.show_error("cannot paste into the trash");
             }
             PasteAction::Filesystem { dest_dir } => {
                 self.paste_filesystem_files(&dest_dir);
@@ -117,83 +145,151 @@ impl App {
     }
 
     fn paste_filesystem_files(&mut self, dest_dir: &Path) {
-        let jobs: Vec<_> = self
-            .clipboard
-            .paths
-            .iter()
-            .filter_map(|src| {
-                let name = src.file_name()?;
-                Some(make_paste_job_kind(
-                    src,
-                    dest_dir.join(name),
-                    self.clipboard.is_cut,
-                ))
-            })
-            .collect();
+        let is_cut = self.clipboard.is_cut;
+        let paths = if is_cut {
+            self.clipboard.is_cut = false;
+            std::mem::take(&mut self.clipboard.paths)
+        } else {
+            self.clipboard.paths.clone()
+        };
+        self.paste_paths(paths, dest_dir, is_cut);
+    }
 
-        for kind in jobs {
-            self.submit_job(kind);
+    pub fn execute_delete(&mut self) -> bool {
+        let paths = self.selected_paths();
+        if !paths.is_empty() {
+            self.ask_to_delete(paths, false);
         }
+        self.exit_visual_if_active();
+        true
+    }
 
-        if self.clipboard.is_cut {
-            self.clipboard.paths.clear();
-            self.clipboard.is_cut = false;
-        }
+    /// Ask before deleting `paths` for good
+    pub(crate) fn ask_to_delete(&mut self, paths: Vec<PathBuf>, empties_trash: bool) {
+        self.delete_prompt = Some(DeletePrompt {
+            paths,
+            empties_trash,
+        });
     }
 
-    pub fn execute_delete(&mut self) -> bool {
-        if let Some(browser) = self.browser()
-            && let Some(entry) = browser.current_entry()
-        {
-            let _ = filesystem::delete(&entry.path);
+    /// Answer the delete prompt: y deletes, anything else cancels
+    pub fn handle_delete_prompt_key(&mut self, key: &str) -> bool {
+        let Some(prompt) = self.delete_prompt.take() else {
+            return false;
+        };
+        if key != "y" && key != "Y" {
+            self.show_message("delete cancelled");
+            return true;
         }
+        if prompt.empties_trash {
+            self.forget_orphaned_trash();
+        }
+        let jobs: Vec<_> = prompt
+            .paths
+            .into_iter()
+            .map(|path| jobs::JobKind::Delete { path })
+            .collect();
+        self.submit_batch("Delete", jobs);
         self.refresh_browser();
         true
     }
 
     pub fn execute_trash(&mut self) -> bool {
+        // What is already in the trash can only be deleted for good
+        if self.in_trash_view() {
+            return self.execute_delete();
+        }
         let jobs: Vec<_> = self
             .selected_paths()
             .into_iter()
             .map(|path| jobs::JobKind::Trash { path })
             .collect();
-        for kind in jobs {
-            self.submit_job(kind);
-        }
+        self.submit_batch("Trash", jobs);
         self.exit_visual_if_active();
         self.refresh_browser();
         true
     }
 
     pub fn execute_create_symlink(&mut self) -> bool {
-        if let Some(browser) = self.browser() {
-            let dest_dir = browser.path.clone();
-            for src in &self.clipboard.paths {
-                let _ = filesystem::create_symlink(src, &dest_dir);
-            }
+        if self.mode == Mode::Visual {
+            self.link_selection_into_other_split();
+            return true;
+        }
+        if let Some(dest_dir) = self.browser().map(|b| b.path.clone()) {
+            self.link_paths(self.clipboard.paths.clone(), &dest_dir);
         }
         self.refresh_browser();
         true
     }
 
+    /// Create links in `dest_dir` to each of `paths`, under the same names
+    pub(crate) fn link_paths(&mut self, paths: Vec<PathBuf>, dest_dir: &Path) {
+        let jobs: Vec<_> = paths
+            .into_iter()
+            .filter_map(|src| {
+                let dest = dest_dir.join(src.file_name()?);
+                Some(jobs::JobKind::Symlink { src, dest })
+            })
+            .collect();
+        self.submit_batch("Link", jobs);
+    }
+
     pub fn execute_extract_archive(&mut self) -> bool {
-        if let Some(browser) = self.browser()
-            && let Some(entry) = browser.current_entry()
-        {
+        let Some(dest) = self.browser().map(|b| b.path.clone()) else {
+            return false;
+        };
+        for archive in self.selected_paths() {
             let kind = jobs::JobKind::Extract {
-                archive: entry.path.clone(),
-                dest: browser.path.clone(),
+                archive,
+                dest: dest.clone(),
             };
             self.submit_job(kind);
         }
+        self.exit_visual_if_active();
         self.refresh_browser();
         true
     }
 
-    fn submit_job(&mut self, kind: jobs::JobKind) {
-        let job_id = self.job_queue.submit(kind.clone());
-        let tx = self.job_queue.sender();
-        self.runtime.spawn(jobs::execute_job(job_id, kind, tx));
+    pub(crate) fn submit_job(&mut self, kind: jobs::JobKind) {
+        self.submit_job_in(kind, None);
+    }
+
+    /// Queue jobs under one "`verb` N items" batch when there are several,
+    /// returning their ids
+    pub(crate) fn submit_batch(
+        &mut self,
+        verb: &str,
+        kinds: Vec<jobs::JobKind>,
+    ) -> Vec<jobs::JobId> {
+        let parent = (kinds.len() > 1).then(|| {
+            let label = format!("{verb} {} items", kinds.len());
+            self.job_queue.submit(jobs::JobKind::Batch { label })
+        });
+        kinds
+            .into_iter()
+            .map(|kind| self.submit_job_in(kind, parent))
+            .collect()
+    }
+
+    /// Queue a job, as part of the batch `parent` when given, and start
+    /// whatever the scheduler allows
+    pub(crate) fn submit_job_in(
+        &mut self,
+        kind: jobs::JobKind,
+        parent: Option<jobs::JobId>,
+    ) -> jobs::JobId {
+        let id = self.job_queue.submit_in(kind, parent);
+        self.start_ready_jobs();
+        id
+    }
+
+    pub(crate) fn start_ready_jobs(&mut self) {
+        for (job_id, kind, control) in self.job_queue.take_ready() {
+            self.journal.job_started(job_id, &kind);
+            let tx = self.job_queue.sender();
+            self.runtime
+                .spawn(jobs::execute_job(job_id, kind, control, tx));
+        }
     }
 
     fn refresh_browser(&mut self) {
@@ -219,17 +315,3 @@ enum PasteAction {
     Filesystem { dest_dir: PathBuf },
     None,
 }
-
-fn make_paste_job_kind(src: &Path, dest: PathBuf, is_cut: bool) -> jobs::JobKind {
-    if is_cut {
-        jobs::JobKind::Move {
-            src: src.to_path_buf(),
-            dest,
-        }
-    } else {
-        jobs::JobKind::Copy {
-            src: src.to_path_buf(),
-            dest,
-        }
-    }
-}
@@ -0,0 +1,83 @@
+//! `:find`: searching below the focused directory in the background
+
+use crate::app::{App, CommandResult};
+use crate::filesystem::FindQuery;
+use crate::jobs::JobKind;
+
+impl App {
+    /// `:find [options] [pattern]` lists matches below the focused
+    /// directory in its pane, replacing any search still running there
+    pub fn execute_find_command(&mut self, args: &str) -> CommandResult {
+        let Some(browser) = self.browser() else {
+            return CommandResult::None;
+        };
+        if browser.get_archive_path().is_some() || browser.in_trash() {
+            self.show_error("find: only works in a directory");
+            return CommandResult::Redraw;
+        }
+        let query = match FindQuery::parse(args) {
+            Ok(query) => query,
+            Err(e) => {
+                self.show_error(format!("find: {e}"));
+                return CommandResult::Redraw;
+            }
+        };
+        let root = browser.path.clone();
+        if let Some(previous) = browser.find_job() {
+            self.job_queue.cancel(previous);
+        }
+        let text = query.text.clone();
+        let job = self.submit_job_in(JobKind::Find { root, query }, None);
+        if let Some(browser) = self.browser_mut() {
+            browser.enter_find(job, text);
+        }
+        CommandResult::Redraw
+    }
+
+    /// Cancel the searches whose results a pane stopped showing, unless
+    /// another pane shows them too
+    pub fn cancel_left_finds(&mut self) {
+        let leaves = self.splits.leaf_ids();
+        let mut left = Vec::new();
+        for &leaf in &leaves {
+            if let Some(browser) = self.splits.get_mut(leaf) {
+                left.extend(browser.take_left_finds());
+            }
+        }
+        for job in left {
+            let shown = leaves.iter().any(|&leaf| {
+                self.splits
+                    .get(leaf)
+                    .is_some_and(|browser| browser.find_job() == Some(job))
+            });
+            if !shown {
+                self.job_queue.cancel(job);
+            }
+        }
+    }
+
+    /// Stop the search of the focused pane before closing it
+    pub(crate) fn cancel_focused_find(&mut self) {
+        if let Some(job) = self.browser().and_then(|browser| browser.find_job()) {
+            self.job_queue.cancel(job);
+        }
+    }
+
+    /// Hand find results to the panes showing their job, or the fuzzy
+    /// finder indexing with it; returns whether any arrived
+    pub fn deliver_found_entries(&mut self) -> bool {
+        let found = self.job_queue.take_found();
+        let leaves = self.splits.leaf_ids();
+        for (job, paths) in &found {
+            self.deliver_indexed_files(*job, paths);
+            for &leaf in &leaves {
+                if let Some(browser) = self.splits.get_mut(leaf)
+                    && browser.find_job() == Some(*job)
+                {
+                    browser.add_found(paths.clone());
+                }
+            }
+        }
+        !found.is_empty()
+    }
+}
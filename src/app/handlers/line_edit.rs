@@ -0,0 +1,73 @@
+//! Command and search line editing handlers
+
+use crate::app::App;
+use crate::cmdline::{CompletionContext, EditKey, complete};
+use crate::input::Mode;
+
+impl App {
+    pub fn execute_line_edit(&mut self, key: EditKey) -> bool {
+        match self.mode {
+            Mode::Command => self.edit_command_line(key),
+            Mode::Search => self.edit_search_line(key),
+            _ => return false,
+        }
+        true
+    }
+
+    fn edit_command_line(&mut self, key: EditKey) {
+        match key {
+            EditKey::Complete => return self.complete_command_line(true),
+            EditKey::CompletePrev => return self.complete_command_line(false),
+            EditKey::HistoryPrev => {
+                if let Some(line) = self.command_history.prev(self.command_line.text()) {
+                    self.command_line.set(&line);
+                }
+            }
+            EditKey::HistoryNext => {
+                if let Some(line) = self.command_history.next() {
+                    self.command_line.set(&line);
+                }
+            }
+            _ => self.command_line.apply(key),
+        }
+        self.completion = None;
+    }
+
+    fn edit_search_line(&mut self, key: EditKey) {
+        match key {
+            EditKey::HistoryPrev => {
+                if let Some(line) = self.search_history.prev(self.search_line.text()) {
+                    self.search_line.set(&line);
+                }
+            }
+            EditKey::HistoryNext => {
+                if let Some(line) = self.search_history.next() {
+                    self.search_line.set(&line);
+                }
+            }
+            _ => self.search_line.apply(key),
+        }
+        self.update_incremental_search();
+    }
+
+    /// Insert the next completion candidate, starting a new completion when
+    /// none is being cycled
+    fn complete_command_line(&mut self, forward: bool) {
+        if self.completion.is_none() {
+            let cwd = self.browser().map(|b| b.path.clone()).unwrap_or_default();
+            let ctx = CompletionContext::new(cwd);
+            self.completion = complete(self.command_line.before_cursor(), &ctx);
+        }
+        let Some(completion) = &mut self.completion else {
+            return;
+        };
+        let start = completion.start;
+        let unique = completion.candidates.len() == 1;
+        let candidate = completion.cycle(forward).to_string();
+        self.command_line.replace_before_cursor(start, &candidate);
+        // A unique match is final, so the next Tab can descend into it
+        if unique {
+            self.completion = None;
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Marks pane and `:marks` / `:delmarks` commands
+
+use std::path::PathBuf;
+
+use crate::app::{App, CommandResult, FocusArea};
+use crate::navigation::parse_mark_list;
+
+impl App {
+    /// `:marks` lists the marks in a pane
+    pub fn execute_marks_command(&mut self) -> CommandResult {
+        if self.bookmarks.is_empty() {
+            self.show_message("no marks set");
+            return CommandResult::Redraw;
+        }
+        self.marks_pane.show();
+        self.marks_pane.cursor = self.marks_pane.cursor.min(self.bookmarks.len() - 1);
+        self.focus_area = FocusArea::Marks;
+        CommandResult::Redraw
+    }
+
+    /// `:delmarks a b-d`, or `:delmarks!` for every lowercase mark
+    pub fn execute_delmarks_command(&mut self, arg: &str) -> CommandResult {
+        if arg == "!" {
+            self.bookmarks.remove_session_marks();
+            return CommandResult::Redraw;
+        }
+        if arg.is_empty() {
+            self.show_error("delmarks: no marks given");
+            return CommandResult::Redraw;
+        }
+        match parse_mark_list(arg) {
+            Ok(marks) => {
+                for c in marks {
+                    self.bookmarks.remove(c);
+                }
+            }
+            Err(e) => self.show_error(format!("delmarks: {e}")),
+        }
+        CommandResult::Redraw
+    }
+
+    /// Keys while the marks pane has focus: j/k move, Enter jumps, d deletes,
+    /// r followed by a letter renames, Esc or q closes
+    pub fn handle_marks_pane_key(&mut self, key: &str) -> bool {
+        let selected = self.bookmarks.iter().nth(self.marks_pane.cursor);
+        let selected: Option<(char, PathBuf)> = selected.map(|(c, p)| (c, p.to_path_buf()));
+
+        if self.marks_pane.renaming {
+            self.marks_pane.renaming = false;
+            let mut chars = key.chars();
+            if let (Some(to), None, Some((from, _))) = (chars.next(), chars.next(), selected)
+                && to.is_ascii_alphabetic()
+            {
+                self.bookmarks.rename(from, to);
+                self.marks_pane.cursor = self
+                    .bookmarks
+                    .iter()
+                    .position(|(c, _)| c == to)
+                    .unwrap_or(0);
+            }
+            return true;
+        }
+
+        match key {
+            "j" | "Down" | "C-n" => self.marks_pane.move_cursor(1, self.bookmarks.len()),
+            "k" | "Up" | "C-p" => self.marks_pane.move_cursor(-1, self.bookmarks.len()),
+            "\n" | "l" => {
+                self.close_marks_pane();
+                if let Some((_, path)) = selected {
+                    self.navigate_focused(&path);
+                }
+            }
+            "d" | "x" | "Delete" => {
+                if let Some((c, _)) = selected {
+                    self.bookmarks.remove(c);
+                }
+                if self.bookmarks.is_empty() {
+                    self.close_marks_pane();
+                } else {
+                    self.marks_pane.cursor = self.marks_pane.cursor.min(self.bookmarks.len() - 1);
+                }
+            }
+            "r" => self.marks_pane.renaming = selected.is_some(),
+            "\u{1b}" | "q" => self.close_marks_pane(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn close_marks_pane(&mut self) {
+        self.marks_pane.hide();
+        self.focus_area = FocusArea::Splits;
+    }
+}
@@ -4,11 +4,23 @@
 
 mod bookmarks;
 mod file_ops;
+mod find;
 mod fold;
+mod line_edit;
+mod marks;
 mod mode;
 mod navigation;
+mod paste;
 mod search;
 mod search_match;
+mod select;
 mod split;
 mod task;
 mod toggle;
+mod trash;
+mod undo;
+mod visual;
+
+pub use file_ops::DeletePrompt;
+pub use paste::PastePlan;
+pub use visual::LastVisual;
@@ -5,11 +5,12 @@ use crate::input::Mode;
 
 impl App {
     pub fn execute_enter_visual_mode(&mut self) -> bool {
+        let Some(cursor) = self.browser().map(|b| b.cursor) else {
+            return false;
+        };
         self.mode = Mode::Visual;
-        self.selection.clear();
-        if let Some(browser) = self.browser() {
-            self.selection.add(browser.cursor);
-        }
+        self.visual_anchor = Some(cursor);
+        self.sync_visual_selection();
         true
     }
 
@@ -21,36 +22,70 @@ impl App {
                 self.feature_pane.hide();
                 self.focus_area = FocusArea::Splits;
             }
+        } else if self.focus_area == FocusArea::TaskList && self.job_output_shown().is_some() {
+            self.execute_toggle_job_output();
         } else if self.mode == Mode::Visual {
-            self.mode = Mode::Normal;
-            self.selection.clear();
+            self.exit_visual_if_active();
         } else if self.search_active {
             // Cancel active search (ESC after search was executed)
             self.execute_clear_search_highlight();
+        } else {
+            self.selection.clear();
         }
         true
     }
 
     pub fn execute_enter_command_mode(&mut self) -> bool {
         self.mode = Mode::Command;
-        self.command_buffer.clear();
+        self.command_line.clear();
+        self.completion = None;
+        true
+    }
+
+    /// Open `:rename` with the current name filled in, the cursor at the end
+    /// or before the extension
+    pub fn execute_rename_prompt(&mut self, at_end: bool) -> bool {
+        let Some(name) = self
+            .browser()
+            .and_then(|b| b.current_entry())
+            .filter(|e| e.name != "..")
+            .map(|e| e.name.clone())
+        else {
+            return false;
+        };
+        if self.refuse_rename_here("rename") {
+            return true;
+        }
+        let prefix = "rename ";
+        let stem_end = match name.rfind('.') {
+            Some(dot) if dot > 0 && !at_end => dot,
+            _ => name.len(),
+        };
+        self.execute_enter_command_mode();
+        self.command_line
+            .set_with_cursor(&format!("{prefix}{name}"), prefix.len() + stem_end);
         true
     }
 
     pub fn execute_command_append(&mut self, c: char) -> bool {
-        self.command_buffer.push(c);
+        self.command_line.insert(c);
+        self.completion = None;
         true
     }
 
     pub fn execute_command_backspace(&mut self) -> bool {
-        self.command_buffer.pop();
+        self.command_line.backspace();
+        self.completion = None;
         true
     }
 
     pub fn execute_command_execute(&mut self) -> bool {
+        self.command_history.push(self.command_line.text());
         let result = self.execute_command();
-        self.command_buffer.clear();
+        self.command_line.clear();
         self.mode = Mode::Normal;
+        // A command entered from visual mode has now used the range
+        self.exit_visual_if_active();
         match result {
             CommandResult::None => false,
             CommandResult::Redraw => true,
@@ -72,8 +107,10 @@ impl App {
     }
 
     pub fn execute_command_cancel(&mut self) -> bool {
-        self.command_buffer.clear();
+        self.command_line.clear();
+        self.command_history.reset();
         self.mode = Mode::Normal;
+        self.exit_visual_if_active();
         true
     }
 }
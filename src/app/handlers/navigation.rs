@@ -1,15 +1,20 @@
 //! Navigation action handlers
 
+use std::path::Path;
+
 use crate::app::{App, FocusArea};
-use crate::input::Mode;
 
 impl App {
     pub fn execute_move_cursor(&mut self, delta: i32) -> bool {
         if self.focus_area == FocusArea::FeatureList {
             let feature_count = self.feature_list.features.len();
             self.feature_pane.move_cursor(delta, feature_count);
+        } else if self.focus_area == FocusArea::Quickfix {
+            let last = self.quickfix.hits.len().saturating_sub(1);
+            let cursor = &mut self.quickfix.cursor;
+            *cursor = cursor.saturating_add_signed(delta as isize).min(last);
         } else if self.focus_area == FocusArea::TaskList {
-            let job_count = self.job_queue.all_jobs().len();
+            let job_count = self.list_pane_jobs().len();
             if job_count > 0 {
                 let cursor_ref = if self.error_list.visible && !self.task_list.visible {
                     &mut self.error_list.cursor
@@ -24,18 +29,8 @@ impl App {
                     *cursor_ref = cursor_ref.saturating_sub(abs_delta);
                 }
             }
-        } else {
-            let cursor = if let Some(browser) = self.browser_mut() {
-                browser.move_cursor(delta);
-                Some(browser.cursor)
-            } else {
-                None
-            };
-            if self.mode == Mode::Visual
-                && let Some(c) = cursor
-            {
-                self.selection.add(c);
-            }
+        } else if let Some(browser) = self.browser_mut() {
+            browser.move_cursor(delta);
         }
         true
     }
@@ -68,15 +63,89 @@ impl App {
         true
     }
 
+    pub fn execute_next_group(&mut self) -> bool {
+        if let Some(browser) = self.browser_mut() {
+            browser.next_group();
+        }
+        true
+    }
+
+    pub fn execute_prev_group(&mut self) -> bool {
+        if let Some(browser) = self.browser_mut() {
+            browser.prev_group();
+        }
+        true
+    }
+
+    pub fn execute_go_to_line(&mut self, line: usize) -> bool {
+        if let Some(browser) = self.browser_mut() {
+            browser.go_to_line(line);
+        }
+        true
+    }
+
     pub fn execute_enter_directory(&mut self) -> bool {
         if self.focus_area == FocusArea::FeatureList {
             self.feature_pane.toggle_detail();
+        } else if self.focus_area == FocusArea::TaskList {
+            self.execute_toggle_job_output();
+        } else if self.focus_area == FocusArea::Quickfix {
+            self.jump_to_hit(self.quickfix.cursor);
         } else if let Some(browser) = self.browser_mut() {
             browser.enter_directory();
         }
         true
     }
 
+    pub fn execute_jump_back(&mut self) -> bool {
+        let result = self.browser_mut().and_then(|b| b.jump_back());
+        self.report_jump(result)
+    }
+
+    pub fn execute_jump_forward(&mut self) -> bool {
+        let result = self.browser_mut().and_then(|b| b.jump_forward());
+        self.report_jump(result)
+    }
+
+    /// Go to the directory containing the entry under the cursor, as when
+    /// it is listed among find results or in an expanded fold
+    pub fn execute_reveal_entry(&mut self) -> bool {
+        let Some(path) = self
+            .browser()
+            .and_then(|b| b.current_entry())
+            .filter(|e| e.name != "..")
+            .map(|e| e.path.clone())
+        else {
+            return false;
+        };
+        self.reveal_path(&path);
+        true
+    }
+
+    /// Show `path` in the focused browser, selected in its directory
+    pub(crate) fn reveal_path(&mut self, path: &Path) {
+        let Some(parent) = path.parent() else {
+            return;
+        };
+        if self.navigate_focused(parent) {
+            if let Some(browser) = self.browser_mut() {
+                browser.select_path(path);
+            }
+            self.focus_area = FocusArea::Splits;
+        }
+    }
+
+    fn report_jump(&mut self, result: Option<std::io::Result<()>>) -> bool {
+        match result {
+            Some(Ok(())) => true,
+            Some(Err(e)) => {
+                self.show_error(format!("jump: {e}"));
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn execute_parent_directory(&mut self) -> bool {
         if let Some(browser) = self.browser_mut() {
             browser.parent_directory();
@@ -0,0 +1,153 @@
+//! Pasting into a directory, asking about entries that already exist
+
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::app::App;
+use crate::filesystem::{self, ConflictPolicy, Resolution};
+use crate::jobs;
+
+/// A paste waiting on the answer to a conflict prompt
+pub struct PastePlan {
+    /// Source and destination of each entry still to paste; the first is
+    /// the conflict being asked about
+    items: VecDeque<(PathBuf, PathBuf)>,
+    is_cut: bool,
+    /// Answer given with "apply to all"
+    policy: Option<ConflictPolicy>,
+    /// Destinations of jobs already submitted, which may not exist yet
+    reserved: HashSet<PathBuf>,
+    /// Entries in the paste, for the batch label
+    total: usize,
+    /// Task list parent of the jobs, made with the first of several
+    batch: Option<jobs::JobId>,
+}
+
+impl PastePlan {
+    /// Status bar question for the pending conflict
+    pub fn prompt(&self) -> Option<String> {
+        let (_, dest) = self.items.front()?;
+        let name = dest.file_name().unwrap_or_default().to_string_lossy();
+        Some(format!(
+            "{name} exists: [o]verwrite [s]kip [r]ename [n]ewer (uppercase for all, Esc cancels)"
+        ))
+    }
+
+    fn is_taken(&self, path: &Path) -> bool {
+        self.reserved.contains(path) || path.symlink_metadata().is_ok()
+    }
+}
+
+impl App {
+    /// Copy or move `paths` into `dest_dir`, resolving name conflicts with
+    /// the configured policy
+    pub fn paste_paths(&mut self, paths: Vec<PathBuf>, dest_dir: &Path, is_cut: bool) {
+        let mut items = VecDeque::new();
+        for src in paths {
+            let Some(name) = src.file_name() else {
+                continue;
+            };
+            if let Err(e) = filesystem::check_not_into_itself(&src, dest_dir) {
+                self.show_error(e.to_string());
+                continue;
+            }
+            items.push_back((src.clone(), dest_dir.join(name)));
+        }
+        self.continue_paste(PastePlan {
+            total: items.len(),
+            items,
+            is_cut,
+            policy: None,
+            reserved: HashSet::new(),
+            batch: None,
+        });
+    }
+
+    /// Submit jobs until a conflict needs an answer
+    fn continue_paste(&mut self, mut plan: PastePlan) {
+        while let Some((src, dest)) = plan.items.pop_front() {
+            let resolution = if !plan.is_taken(&dest) {
+                Resolution::Write(dest)
+            } else if src == dest {
+                // Pasting a copy next to its source duplicates it; moving an
+                // entry onto itself does nothing
+                if plan.is_cut {
+                    Resolution::Skip
+                } else {
+                    Resolution::Write(filesystem::numbered_name(&dest, |p| plan.is_taken(p)))
+                }
+            } else {
+                match plan.policy.unwrap_or(self.conflict_policy) {
+                    ConflictPolicy::Prompt => {
+                        plan.items.push_front((src, dest));
+                        self.paste_conflict = Some(plan);
+                        return;
+                    }
+                    policy => filesystem::resolve(policy, &src, &dest, |p| plan.is_taken(p)),
+                }
+            };
+            self.submit_resolved(&mut plan, src, resolution);
+        }
+    }
+
+    fn submit_resolved(&mut self, plan: &mut PastePlan, src: PathBuf, resolution: Resolution) {
+        if let Resolution::Write(dest) = resolution {
+            plan.reserved.insert(dest.clone());
+            let kind = if plan.is_cut {
+                jobs::JobKind::Move { src, dest }
+            } else {
+                jobs::JobKind::Copy {
+                    src,
+                    dest,
+                    preserve: self.preserve,
+                }
+            };
+            if plan.batch.is_none() && plan.total > 1 {
+                let verb = if plan.is_cut { "Move" } else { "Paste" };
+                let label = format!("{verb} {} items", plan.total);
+                plan.batch = Some(self.job_queue.submit(jobs::JobKind::Batch { label }));
+            }
+            self.submit_job_in(kind, plan.batch);
+        }
+    }
+
+    /// Answer the conflict prompt: o, s, r or n for this entry, uppercase
+    /// for every remaining conflict, Esc or q to cancel the rest
+    pub fn handle_paste_conflict_key(&mut self, key: &str) -> bool {
+        let Some(mut plan) = self.paste_conflict.take() else {
+            return false;
+        };
+        let policy = match key.to_lowercase().as_str() {
+            "o" => ConflictPolicy::Overwrite,
+            "s" => ConflictPolicy::Skip,
+            "r" => ConflictPolicy::Rename,
+            "n" => ConflictPolicy::OverwriteIfNewer,
+            "\u{1b}" | "q" => {
+                // A cut only comes from the clipboard, which it emptied;
+                // what was never moved goes back to be pasted again
+                if plan.is_cut {
+                    self.clipboard
+                        .cut(plan.items.into_iter().map(|(src, _)| src).collect());
+                }
+                self.show_message("paste cancelled");
+                return true;
+            }
+            _ => {
+                self.paste_conflict = Some(plan);
+                return false;
+            }
+        };
+        if key.chars().all(|c| c.is_ascii_uppercase()) {
+            plan.policy = Some(policy);
+        }
+        if let Some((src, dest)) = plan.items.pop_front() {
+            let resolution = filesystem::resolve(policy, &src, &dest, |p| plan.is_taken(p));
+            self.submit_resolved(&mut plan, src, resolution);
+        }
+        self.continue_paste(plan);
+        if let Some(browser) = self.browser_mut() {
+            browser.refresh();
+        }
+        true
+    }
+}
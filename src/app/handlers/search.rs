@@ -7,32 +7,31 @@ impl App {
     pub fn execute_enter_search_mode(&mut self) -> bool {
         self.pre_search_cursor = self.browser().map(|b| b.cursor);
         self.mode = Mode::Search;
-        self.search_buffer.clear();
+        self.search_line.clear();
         true
     }
 
     pub fn execute_search_append(&mut self, c: char) -> bool {
-        self.search_buffer.push(c);
-
-        if self.search_narrowing {
-            self.apply_search_filter();
-        } else {
-            self.move_to_first_incremental_match();
-        }
+        self.search_line.insert(c);
+        self.update_incremental_search();
         true
     }
 
     pub fn execute_search_backspace(&mut self) -> bool {
-        self.search_buffer.pop();
+        self.search_line.backspace();
+        self.update_incremental_search();
+        true
+    }
 
+    /// Follow the search line as it is edited
+    pub(super) fn update_incremental_search(&mut self) {
         if self.search_narrowing {
             self.apply_search_filter();
-        } else if self.search_buffer.is_empty() {
+        } else if self.search_line.is_empty() {
             self.restore_pre_search_cursor();
         } else {
             self.move_to_first_incremental_match();
         }
-        true
     }
 
     fn restore_pre_search_cursor(&mut self) {
@@ -44,7 +43,8 @@ impl App {
     }
 
     pub fn execute_search_execute(&mut self) -> bool {
-        if self.search_buffer.is_empty() {
+        self.search_history.push(self.search_line.text());
+        if self.search_line.is_empty() {
             self.clear_search_state();
             self.pre_search_cursor = None;
             self.search_active = false;
@@ -58,7 +58,7 @@ impl App {
     }
 
     fn activate_search_after_enter(&mut self) {
-        self.last_search = Some(self.search_buffer.clone());
+        self.last_search = Some(self.search_line.text().to_string());
         self.compute_search_matches();
         self.search_highlight = true;
         self.current_match = self.find_first_match_after_cursor();
@@ -89,7 +89,8 @@ impl App {
 
     pub fn execute_search_cancel(&mut self) -> bool {
         self.restore_pre_search_cursor();
-        self.search_buffer.clear();
+        self.search_line.clear();
+        self.search_history.reset();
         self.pre_search_cursor = None;
         self.search_active = false;
 
@@ -4,11 +4,11 @@ use crate::app::App;
 
 impl App {
     pub(super) fn move_to_first_incremental_match(&mut self) {
-        if self.search_buffer.is_empty() {
+        if self.search_line.is_empty() {
             return;
         }
 
-        let pattern_lower = self.search_buffer.to_lowercase();
+        let pattern_lower = self.search_line.text().to_lowercase();
         let pre_cursor = self.pre_search_cursor.unwrap_or(0);
 
         if self.find_match_forward(&pattern_lower, pre_cursor) {
@@ -0,0 +1,56 @@
+//! Tagged file selection handlers
+
+use crate::app::{App, CommandResult};
+
+impl App {
+    /// Tag or untag the entry under the cursor and move to the next one
+    pub fn execute_toggle_select(&mut self) -> bool {
+        if let Some(browser) = self.splits.focused_content_mut() {
+            if let Some(entry) = browser.current_entry().filter(|e| e.name != "..") {
+                self.selection.toggle(entry);
+            }
+            browser.move_cursor(1);
+        }
+        true
+    }
+
+    pub fn execute_select_all(&mut self) -> bool {
+        if let Some(browser) = self.splits.focused_content() {
+            self.selection.select_all(&browser.entries);
+        }
+        true
+    }
+
+    pub fn execute_invert_selection(&mut self) -> bool {
+        if let Some(browser) = self.splits.focused_content() {
+            self.selection.invert(&browser.entries);
+        }
+        true
+    }
+
+    /// `:select <glob>` / `:unselect <glob>`; without a pattern, select
+    /// everything or clear the selection
+    pub(crate) fn execute_select_command(&mut self, pattern: &str, tag: bool) -> CommandResult {
+        if pattern.is_empty() {
+            if tag {
+                self.execute_select_all();
+            } else {
+                self.selection.clear();
+            }
+            return CommandResult::Redraw;
+        }
+        let pattern = match glob::Pattern::new(pattern) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                let command = if tag { "select" } else { "unselect" };
+                self.show_error(format!("{command}: {e}"));
+                return CommandResult::Redraw;
+            }
+        };
+        if let Some(browser) = self.splits.focused_content() {
+            self.selection
+                .select_matching(&browser.entries, &pattern, tag);
+        }
+        CommandResult::Redraw
+    }
+}
@@ -19,20 +19,29 @@ impl App {
     }
 
     pub fn execute_focus_up(&mut self) -> bool {
-        if self.focus_area == FocusArea::TaskList {
-            self.focus_area = FocusArea::Splits;
-        } else {
-            self.splits.focus_up();
+        match self.focus_area {
+            FocusArea::TaskList if self.quickfix.visible => self.focus_area = FocusArea::Quickfix,
+            FocusArea::TaskList | FocusArea::Quickfix => self.focus_area = FocusArea::Splits,
+            _ => {
+                self.splits.focus_up();
+            }
         }
         true
     }
 
+    /// Move down through the splits, then the grep results and the task
+    /// list below them
     pub fn execute_focus_down(&mut self) -> bool {
         let list_visible = self.task_list.visible || self.error_list.visible;
-        if self.focus_area == FocusArea::Splits && list_visible {
-            self.focus_area = FocusArea::TaskList;
-        } else if self.focus_area == FocusArea::Splits {
-            self.splits.focus_down();
+        match self.focus_area {
+            FocusArea::Splits if self.quickfix.visible => self.focus_area = FocusArea::Quickfix,
+            FocusArea::Splits | FocusArea::Quickfix if list_visible => {
+                self.focus_area = FocusArea::TaskList
+            }
+            FocusArea::Splits => {
+                self.splits.focus_down();
+            }
+            _ => {}
         }
         true
     }
@@ -61,6 +70,7 @@ impl App {
 
     pub fn execute_close_split(&mut self) -> bool {
         if self.splits.len() > 1 {
+            self.cancel_focused_find();
             self.splits.close_focused();
         }
         true
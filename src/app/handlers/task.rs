@@ -1,13 +1,254 @@
 //! Task and error list action handlers
 
-use crate::app::{App, FocusArea};
+use crate::app::{App, CommandResult, FocusArea};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::filesystem;
+use crate::jobs::{Job, JobId, JobKind, JobStatus};
+
+const CANNOT_CANCEL: &str = "only pending jobs, or running copies, moves, trashing, deletion, \
+     extraction, searches and shell commands can be cancelled";
 
 impl App {
+    /// Whether the list pane shows only the error list
+    pub fn showing_error_list(&self) -> bool {
+        self.error_list.visible && !self.task_list.visible
+    }
+
+    /// Jobs in the list pane, in display order. Children of a batch are
+    /// listed only while it is expanded, except in the error list.
+    pub fn list_pane_jobs(&self) -> Vec<&Job> {
+        let all_jobs = self.job_queue.all_jobs();
+        if self.showing_error_list() {
+            return self.job_queue.failed_jobs().collect();
+        }
+        let expanded: HashSet<JobId> = all_jobs
+            .iter()
+            .filter(|j| j.expanded)
+            .map(|j| j.id)
+            .collect();
+        let tasks_only = self.task_list.visible && !self.error_list.visible;
+        all_jobs
+            .iter()
+            .filter(|j| j.parent.is_none_or(|parent| expanded.contains(&parent)))
+            .filter(|j| !tasks_only || !j.is_failed())
+            .collect()
+    }
+
+    /// The job whose output is being shown, if any
+    pub fn job_output_shown(&self) -> Option<&Job> {
+        let (cursor, showing) = if self.showing_error_list() {
+            (self.error_list.cursor, self.error_list.showing_detail)
+        } else {
+            (self.task_list.cursor, self.task_list.showing_detail)
+        };
+        if !showing {
+            return None;
+        }
+        self.list_pane_jobs().get(cursor).copied()
+    }
+
+    /// Keys only the task list handles: `dd` cancels the job under the
+    /// cursor, `p` pauses or resumes it, `K`/`J` move it earlier or later in
+    /// the queue and Enter or Tab expands a batch. Failed jobs take the
+    /// error list keys. `None` passes the key on.
+    pub fn handle_task_list_key(&mut self, key: &str) -> Option<bool> {
+        if self.showing_error_list() {
+            let id = self.list_pane_jobs().get(self.error_list.cursor)?.id;
+            return self.handle_failed_job_key(id, key);
+        }
+        let pending_cancel = std::mem::take(&mut self.task_list.pending_cancel);
+        let job = self.list_pane_jobs().get(self.task_list.cursor).copied()?;
+        let (id, is_batch, is_failed) = (job.id, job.is_batch(), job.is_failed());
+        let cancellable = job.can_cancel();
+        match key {
+            "\n" | "\t" if is_batch => {
+                self.job_queue.toggle_expanded(id);
+                Some(true)
+            }
+            "K" | "J" => {
+                let delta = if key == "K" { -1 } else { 1 };
+                if self.job_queue.move_job(id, delta) {
+                    self.task_list.cursor = self
+                        .list_pane_jobs()
+                        .iter()
+                        .position(|j| j.id == id)
+                        .unwrap_or(self.task_list.cursor);
+                }
+                Some(true)
+            }
+            "d" if !cancellable => {
+                self.show_error(CANNOT_CANCEL);
+                Some(true)
+            }
+            "d" if !pending_cancel => {
+                self.task_list.pending_cancel = true;
+                Some(false)
+            }
+            "d" => {
+                if !self.job_queue.cancel(id) {
+                    self.show_error(CANNOT_CANCEL);
+                }
+                Some(true)
+            }
+            "p" => {
+                match self.job_queue.toggle_pause(id) {
+                    Some(true) => self.show_message("paused"),
+                    Some(false) => self.show_message("resumed"),
+                    None => {
+                        self.show_error("only running copies, moves and deletion can be paused")
+                    }
+                }
+                Some(true)
+            }
+            _ if is_failed => self.handle_failed_job_key(id, key),
+            _ => None,
+        }
+    }
+
+    /// Fixes for a failed job: `r` retries it, `R` retries replacing what
+    /// is in the way at its destination once done, `D` retries with a destination
+    /// typed on the command line, `o`/`O` reveal its source/destination, `y`
+    /// copies the error and `x` dismisses it
+    fn handle_failed_job_key(&mut self, id: JobId, key: &str) -> Option<bool> {
+        let job = self.job_queue.get(id).filter(|j| !j.is_batch())?;
+        let JobStatus::Failed(error) = &job.status else {
+            return None;
+        };
+        let (kind, error) = (job.kind.clone(), error.clone());
+        match key {
+            "r" => self.retry_job(id, kind),
+            "R" => match kind {
+                // Copies and moves replace their destination once complete
+                JobKind::Copy { .. } | JobKind::Move { .. } | JobKind::Replace { .. } => {
+                    self.retry_job(id, kind)
+                }
+                JobKind::Rename { .. } | JobKind::Symlink { .. } | JobKind::Restore { .. } => {
+                    let job = Box::new(kind);
+                    self.retry_job(id, JobKind::Replace { job });
+                }
+                _ => self.show_error(
+                    "only copies, moves, renames, links and restores can replace their destination",
+                ),
+            },
+            "D" => {
+                let dest = kind
+                    .destination()
+                    .map(|d| d.display().to_string())
+                    .unwrap_or_default();
+                self.execute_enter_command_mode();
+                self.command_line.set(&format!("retry {dest}"));
+            }
+            "o" | "O" => {
+                let path = if key == "o" {
+                    kind.source()
+                } else {
+                    kind.destination()
+                };
+                match path {
+                    Some(path) => self.reveal_path(path),
+                    None => self.show_error("the job has no such path"),
+                }
+            }
+            "y" => match filesystem::copy_text(&error.to_string()) {
+                Ok(()) => self.show_message("copied error"),
+                Err(e) => self.show_error(e.to_string()),
+            },
+            "x" => {
+                self.job_queue.remove(id);
+                self.clamp_list_cursor();
+            }
+            _ => return None,
+        }
+        Some(true)
+    }
+
+    /// `:retry [PATH]`: run the failed job under the list cursor again,
+    /// writing to PATH when given, or into it when it is a directory
+    pub fn execute_retry_command(&mut self, arg: &str) -> CommandResult {
+        let cursor = if self.showing_error_list() {
+            self.error_list.cursor
+        } else {
+            self.task_list.cursor
+        };
+        let Some(job) = self
+            .list_pane_jobs()
+            .get(cursor)
+            .copied()
+            .filter(|j| j.is_failed() && !j.is_batch())
+        else {
+            self.show_error("no failed job under the cursor");
+            return CommandResult::Redraw;
+        };
+        let (id, mut kind) = (job.id, job.kind.clone());
+        if !arg.is_empty() {
+            let mut dest = match self.resolve_path(arg) {
+                Ok(dest) => dest,
+                Err(e) => {
+                    self.show_error(e);
+                    return CommandResult::Redraw;
+                }
+            };
+            if dest.is_dir()
+                && !matches!(kind, JobKind::Extract { .. })
+                && let Some(name) = kind.source().and_then(Path::file_name)
+            {
+                dest = dest.join(name);
+            }
+            match kind.with_destination(dest) {
+                Some(retargeted) => kind = retargeted,
+                None => {
+                    self.show_error("this job cannot be given another destination");
+                    return CommandResult::Redraw;
+                }
+            }
+        }
+        self.retry_job(id, kind);
+        CommandResult::Redraw
+    }
+
+    /// Replace a failed job with a new one, in the same batch
+    fn retry_job(&mut self, id: JobId, kind: JobKind) {
+        let Some(job) = self.job_queue.remove(id) else {
+            return;
+        };
+        self.submit_job_in(kind, job.parent);
+        self.clamp_list_cursor();
+        self.show_message(format!("retrying {}", job.description));
+    }
+
+    /// Keep the list cursor on a job after jobs leave the list
+    fn clamp_list_cursor(&mut self) {
+        let last = self.list_pane_jobs().len().saturating_sub(1);
+        let cursor = if self.showing_error_list() {
+            &mut self.error_list.cursor
+        } else {
+            &mut self.task_list.cursor
+        };
+        *cursor = (*cursor).min(last);
+    }
+
+    /// Show or hide the output of the job under the list cursor
+    pub fn execute_toggle_job_output(&mut self) -> bool {
+        if self.showing_error_list() {
+            self.error_list.toggle_detail();
+        } else {
+            self.task_list.toggle_detail();
+        }
+        true
+    }
+
+    /// `]q`: the next grep result while they are shown, otherwise the next
+    /// task
     pub fn execute_next_task(&mut self) -> bool {
+        if self.quickfix.visible && !self.quickfix.hits.is_empty() {
+            return self.execute_step_hit(1);
+        }
         if !self.task_list.visible {
             self.task_list.show();
         } else {
-            let job_count = self.job_queue.all_jobs().len();
+            let job_count = self.list_pane_jobs().len();
             if job_count > 0 && self.task_list.cursor < job_count - 1 {
                 self.task_list.cursor += 1;
             }
@@ -16,6 +257,9 @@ impl App {
     }
 
     pub fn execute_prev_task(&mut self) -> bool {
+        if self.quickfix.visible && !self.quickfix.hits.is_empty() {
+            return self.execute_step_hit(-1);
+        }
         if !self.task_list.visible {
             self.task_list.show();
         } else if self.task_list.cursor > 0 {
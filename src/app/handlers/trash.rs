@@ -0,0 +1,76 @@
+//! Trash view commands: restoring, deleting and emptying
+
+use crate::app::{App, CommandResult};
+use crate::filesystem;
+use crate::jobs::JobKind;
+use crate::navigation::TRASH_URI;
+
+impl App {
+    pub(crate) fn in_trash_view(&self) -> bool {
+        self.browser().is_some_and(|b| b.in_trash())
+    }
+
+    /// Refuse renaming in the trash, where the entry's record would be
+    /// left behind and `:restore` would lose it, and inside archives;
+    /// returns whether it refused
+    pub(crate) fn refuse_rename_here(&mut self, command: &str) -> bool {
+        let place = match self.browser() {
+            Some(b) if b.in_trash() => "the trash",
+            Some(b) if b.in_archive() => "archives",
+            _ => return false,
+        };
+        self.show_error(format!("{command}: entries in {place} cannot be renamed"));
+        true
+    }
+
+    /// `:cd trash://` lists the trash in the focused pane
+    pub fn execute_open_trash(&mut self) -> CommandResult {
+        if let Some(browser) = self.browser_mut() {
+            browser.enter_trash();
+        }
+        CommandResult::Redraw
+    }
+
+    /// `:restore` moves the selected trash entries back to where they were
+    /// trashed from
+    pub fn execute_restore_command(&mut self) -> CommandResult {
+        let Some(browser) = self.browser().filter(|b| b.in_trash()) else {
+            self.show_error(format!("restore: only works in {TRASH_URI}"));
+            return CommandResult::Redraw;
+        };
+        let jobs: Vec<_> = self
+            .selected_paths()
+            .into_iter()
+            .filter_map(|path| {
+                let dest = browser.trashed_from(&path)?.to_path_buf();
+                Some(JobKind::Restore { path, dest })
+            })
+            .collect();
+        self.submit_batch("Restore", jobs);
+        self.exit_visual_if_active();
+        CommandResult::Redraw
+    }
+
+    /// `:emptytrash` permanently deletes everything in the trash, once
+    /// confirmed
+    pub fn execute_empty_trash_command(&mut self) -> CommandResult {
+        let paths: Vec<_> = filesystem::list_trash()
+            .into_iter()
+            .map(|item| item.path)
+            .collect();
+        if paths.is_empty() {
+            self.forget_orphaned_trash();
+            self.show_message("trash is empty");
+        } else {
+            self.ask_to_delete(paths, true);
+        }
+        CommandResult::Redraw
+    }
+
+    /// Drop the records of entries no longer in the trash
+    pub(crate) fn forget_orphaned_trash(&mut self) {
+        if let Err(e) = filesystem::forget_orphans() {
+            self.show_error(format!("emptytrash: {e}"));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Undo and redo of file operations
+
+use crate::app::App;
+use crate::jobs::JobKind;
+use crate::journal::Replay;
+
+impl App {
+    /// Feed jobs completed since the last poll to the journal
+    pub fn record_completed_jobs(&mut self) {
+        for job in self.job_queue.take_completed() {
+            self.journal.job_completed(job.id, job.parent, &job.kind);
+        }
+    }
+
+    pub fn execute_undo(&mut self) -> bool {
+        self.record_completed_jobs();
+        let result = self.journal.undo();
+        self.replay(result, Replay::Undo);
+        true
+    }
+
+    pub fn execute_redo(&mut self) -> bool {
+        self.record_completed_jobs();
+        let result = self.journal.redo();
+        self.replay(result, Replay::Redo);
+        true
+    }
+
+    /// Queue the jobs undo or redo asked for, or say why there are none
+    fn replay(&mut self, result: Result<Vec<JobKind>, String>, replay: Replay) {
+        let (verb, label) = match replay {
+            Replay::Undo => ("undo", "Undo"),
+            Replay::Redo => ("redo", "Redo"),
+        };
+        let kinds = match result {
+            Ok(kinds) => kinds,
+            Err(e) => {
+                self.show_error(format!("{verb}: {e}"));
+                return;
+            }
+        };
+        let count = kinds.len();
+        let parent = (count > 1).then(|| {
+            let label = format!("{label} {count} changes");
+            self.job_queue.submit(JobKind::Batch { label })
+        });
+        // Quick steps run now and in order, since renames through temporary
+        // names depend on the ones before; the first failure stops the rest,
+        // which were checked against what it would have done
+        for kind in kinds {
+            if kind.is_quick() {
+                let (_, result) =
+                    self.journal
+                        .run_now(&mut self.job_queue, kind, parent, Some(replay));
+                if let Err(e) = result {
+                    self.show_error(format!("{verb} stopped: {e}"));
+                    return;
+                }
+            } else {
+                let id = self.submit_job_in(kind, parent);
+                self.journal.replaying(id, replay);
+            }
+        }
+        let noun = if count == 1 { "change" } else { "changes" };
+        self.show_message(format!("{verb}: {count} {noun}"));
+    }
+}
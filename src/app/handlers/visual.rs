@@ -0,0 +1,99 @@
+//! Visual mode range handling
+
+use std::path::PathBuf;
+
+use crate::app::App;
+use crate::input::Mode;
+
+/// The last visual range, restored by `gv`
+#[derive(Clone, Debug)]
+pub struct LastVisual {
+    pub dir: PathBuf,
+    pub anchor: usize,
+    pub cursor: usize,
+}
+
+impl App {
+    /// Rebuild the selection from the anchor to the cursor
+    pub fn sync_visual_selection(&mut self) {
+        let Some(anchor) = self.visual_anchor else {
+            return;
+        };
+        let Some(browser) = self.splits.focused_content() else {
+            return;
+        };
+        let (start, end) = (anchor.min(browser.cursor), anchor.max(browser.cursor));
+        let range = browser.entries.get(start..=end).unwrap_or_default();
+        // Never let a range reach the parent entry
+        self.selection
+            .set_range(range.iter().filter(|e| e.name != ".."));
+    }
+
+    /// Drop the visual range, remembering it for `gv`
+    pub fn leave_visual(&mut self) {
+        if let Some(anchor) = self.visual_anchor.take()
+            && let Some(browser) = self.browser()
+        {
+            self.last_visual = Some(LastVisual {
+                dir: browser.path.clone(),
+                anchor,
+                cursor: browser.cursor,
+            });
+        }
+        self.selection.clear_range();
+    }
+
+    pub fn execute_visual_swap_anchor(&mut self) -> bool {
+        let Some(anchor) = self.visual_anchor else {
+            return false;
+        };
+        if let Some(browser) = self.splits.focused_content_mut() {
+            self.visual_anchor = Some(browser.cursor);
+            browser.cursor = anchor;
+        }
+        true
+    }
+
+    pub fn execute_reselect_visual(&mut self) -> bool {
+        let Some(last) = self.last_visual.clone() else {
+            return false;
+        };
+        let Some(browser) = self.browser_mut() else {
+            return false;
+        };
+        if browser.path != last.dir || browser.entries.is_empty() {
+            return false;
+        }
+        let max = browser.entries.len() - 1;
+        browser.cursor = last.cursor.min(max);
+        self.visual_anchor = Some(last.anchor.min(max));
+        self.mode = Mode::Visual;
+        self.sync_visual_selection();
+        true
+    }
+
+    /// Link the visual selection into the next split's directory
+    pub(super) fn link_selection_into_other_split(&mut self) {
+        let Some(dest_dir) = self.other_split_dir() else {
+            return;
+        };
+        let paths = self.selected_paths();
+        self.link_paths(paths, &dest_dir);
+        self.exit_visual_if_active();
+        for id in self.splits.leaf_ids() {
+            if let Some(browser) = self.splits.get_mut(id) {
+                browser.refresh();
+            }
+        }
+    }
+
+    pub(crate) fn other_split_dir(&self) -> Option<PathBuf> {
+        let focused = self.splits.focused()?;
+        let ids = self.splits.leaf_ids();
+        let pos = ids.iter().position(|&id| id == focused)?;
+        let next = ids
+            .get((pos + 1) % ids.len())
+            .filter(|&&id| id != focused)?;
+        self.splits.get(*next).map(|b| b.path.clone())
+    }
+}
@@ -7,18 +7,34 @@ mod bulk_rename;
 mod commands;
 mod execute;
 mod features_init;
+mod frecency;
+mod fuzzy_finder;
 mod handlers;
+mod operator;
+mod path_commands;
 mod pointer;
 mod pointer_helpers;
+mod quickfix;
+mod shell;
+mod substitute;
 
-use std::collections::HashMap;
 use std::path::PathBuf;
 
-use crate::config::{Config, Openers, SavedSettings, Theme};
+use crate::cmdline::{Completion, History, LineEditor};
+use crate::config::{Config, Keymap, Openers, SavedSettings, Theme};
 use crate::features;
+use crate::filesystem::ConflictPolicy;
 use crate::input::{Action, Mode, SortMode, handle_key};
 use crate::jobs;
-use crate::navigation::{Browser, Clipboard, Selection};
+use crate::journal::Journal;
+use crate::navigation::{Bookmarks, Browser, Clipboard, Frecency, MarksPane, Selection};
+
+pub use bulk_rename::RenamePreview;
+pub use fuzzy_finder::{FinderSource, FuzzyFinder};
+use handlers::{DeletePrompt, LastVisual, PastePlan};
+use operator::PendingOperator;
+pub use quickfix::Quickfix;
+pub use substitute::SubstitutePreview;
 
 use mkframe::{SplitDirection, SplitTree};
 
@@ -31,12 +47,21 @@ pub enum CommandResult {
     Exit,
 }
 
+/// A one-line message shown in the status bar until the next key press
+#[derive(Clone, Debug)]
+pub struct StatusMessage {
+    pub text: String,
+    pub is_error: bool,
+}
+
 /// Which area of the UI has focus
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum FocusArea {
     Splits,
     TaskList,
+    Quickfix,
     FeatureList,
+    Marks,
 }
 
 /// Main application state
@@ -48,15 +73,45 @@ pub struct App {
     pub splits: SplitTree<Browser>,
     pub clipboard: Clipboard,
     pub selection: Selection,
-    pub command_buffer: String,
-    pub pending_keys: String,
+    /// Entry the visual range started from, kept while a command runs on it
+    pub visual_anchor: Option<usize>,
+    pub last_visual: Option<LastVisual>,
+    /// Keep tagged files selected after leaving their directory
+    pub persist_selection: bool,
+    pub command_line: LineEditor,
+    pub command_history: History,
+    /// Tab candidates being cycled on the command line
+    pub completion: Option<Completion>,
+    /// Template of the last `:!` command, for `:!!`
+    pub last_shell_command: Option<String>,
+    /// What to do when a pasted entry already exists
+    pub conflict_policy: ConflictPolicy,
+    /// Paste waiting on an answer about an existing entry
+    pub paste_conflict: Option<PastePlan>,
+    /// Permanent delete waiting for confirmation
+    pub delete_prompt: Option<DeletePrompt>,
+    /// Bulk rename waiting for confirmation
+    pub rename_preview: Option<RenamePreview>,
+    /// Names a `:s` being typed would give
+    pub substitute_preview: Option<SubstitutePreview>,
+    /// Results of `:grep`
+    pub quickfix: Quickfix,
+    /// Fuzzy finder popup, while open
+    pub fuzzy_finder: Option<FuzzyFinder>,
+    /// Keep timestamps, extended attributes and hard links when copying
+    pub preserve: bool,
+    pub pending_keys: Vec<String>,
     pub overlay_enabled: bool,
     pub motion_count: Option<usize>,
+    pub pending_operator: Option<PendingOperator>,
     pub pending_theme: Option<Option<String>>,
     pub should_exit: bool,
+    pub message: Option<StatusMessage>,
     pub openers: Openers,
+    pub keymap: Keymap,
     // Search
-    pub search_buffer: String,
+    pub search_line: LineEditor,
+    pub search_history: History,
     pub last_search: Option<String>,
     pub search_highlight: bool,
     pub search_matches: Vec<usize>,
@@ -65,7 +120,10 @@ pub struct App {
     pub search_active: bool,
     pub search_narrowing: bool,
     // Bookmarks
-    pub bookmarks: HashMap<char, PathBuf>,
+    pub bookmarks: Bookmarks,
+    pub marks_pane: MarksPane,
+    /// Visited directories, for `:z`
+    pub frecency: Frecency,
     // Sorting
     pub sort_mode: SortMode,
     pub sort_reverse: bool,
@@ -75,6 +133,8 @@ pub struct App {
     pub job_queue: jobs::JobQueue,
     pub task_list: jobs::TaskListPane,
     pub error_list: jobs::ErrorListPane,
+    /// Completed file operations, for undo and redo
+    pub journal: Journal,
     pub runtime: tokio::runtime::Handle,
     // Focus
     pub focus_area: FocusArea,
@@ -106,6 +166,9 @@ impl App {
         let overlay_enabled = config.overlay().await.enabled;
         let vi_mode = config.vi_mode().await;
         let search_narrowing = config.search_narrowing().await;
+        let conflict_policy = config.paste_conflict().await;
+        let preserve = config.preserve().await;
+        let max_jobs_per_device = config.max_jobs_per_device().await;
         let icons_mode = config.icons().await;
         let icons_enabled = match icons_mode {
             crate::config::IconsMode::Enabled => true,
@@ -117,6 +180,7 @@ impl App {
         let theme_name = config.theme().await;
         let theme = Theme::load(theme_name.as_deref()).await;
         let openers = Openers::load();
+        let keymap = Keymap::load();
 
         let mut splits = SplitTree::new();
 
@@ -144,14 +208,32 @@ impl App {
             splits,
             clipboard: Clipboard::new(),
             selection: Selection::new(),
-            command_buffer: String::new(),
-            pending_keys: String::new(),
+            visual_anchor: None,
+            last_visual: None,
+            persist_selection: false,
+            command_line: LineEditor::new(),
+            command_history: History::load("command_history"),
+            completion: None,
+            last_shell_command: None,
+            conflict_policy,
+            paste_conflict: None,
+            delete_prompt: None,
+            rename_preview: None,
+            substitute_preview: None,
+            quickfix: Quickfix::default(),
+            fuzzy_finder: None,
+            preserve,
+            pending_keys: Vec::new(),
             overlay_enabled,
             motion_count: None,
+            pending_operator: None,
             pending_theme: None,
             should_exit: false,
+            message: None,
             openers,
-            search_buffer: String::new(),
+            keymap,
+            search_line: LineEditor::new(),
+            search_history: History::load("search_history"),
             last_search: None,
             search_highlight: false,
             search_matches: Vec::new(),
@@ -159,13 +241,16 @@ impl App {
             pre_search_cursor: None,
             search_active: false,
             search_narrowing,
-            bookmarks: HashMap::new(),
+            bookmarks: Bookmarks::load(),
+            marks_pane: MarksPane::new(),
+            frecency: Frecency::load(),
             sort_mode: SortMode::default(),
             sort_reverse: false,
             filter_pattern: None,
-            job_queue: jobs::JobQueue::new(),
+            job_queue: jobs::JobQueue::new(max_jobs_per_device),
             task_list: jobs::TaskListPane::new(),
             error_list: jobs::ErrorListPane::new(),
+            journal: Journal::new(),
             runtime: tokio::runtime::Handle::current(),
             focus_area: FocusArea::Splits,
             vi_mode,
@@ -205,20 +290,8 @@ impl App {
 
     /// Check if drag should start, return files to drag
     pub fn take_drag_files(&mut self) -> Option<Vec<PathBuf>> {
-        if self.dragging
-            && self.drag_start_pos.is_some()
-            && let Some(browser) = self.browser()
-        {
-            let files: Vec<PathBuf> = if self.selection.is_empty() {
-                browser
-                    .entries
-                    .get(browser.cursor)
-                    .map(|e| vec![e.path.clone()])
-                    .unwrap_or_default()
-            } else {
-                self.selection.to_paths(&browser.entries)
-            };
-
+        if self.dragging && self.drag_start_pos.is_some() {
+            let files = self.selected_paths();
             if !files.is_empty() {
                 self.drag_start_pos = None;
                 return Some(files);
@@ -227,8 +300,59 @@ impl App {
         None
     }
 
+    /// Show an informational message in the status bar
+    pub fn show_message(&mut self, text: impl Into<String>) {
+        self.message = Some(StatusMessage {
+            text: text.into(),
+            is_error: false,
+        });
+    }
+
+    /// Show an error in the status bar
+    pub fn show_error(&mut self, text: impl Into<String>) {
+        self.message = Some(StatusMessage {
+            text: text.into(),
+            is_error: true,
+        });
+    }
+
     /// Process a key press
     pub fn process_key(&mut self, key_str: &str) -> bool {
+        // Any key dismisses the last message, which then needs a redraw
+        let had_message = self.message.take().is_some();
+        if self.paste_conflict.is_some() {
+            return self.handle_paste_conflict_key(key_str) || had_message;
+        }
+        if self.delete_prompt.is_some() {
+            return self.handle_delete_prompt_key(key_str) || had_message;
+        }
+        if self.rename_preview.is_some() {
+            return self.handle_rename_preview_key(key_str) || had_message;
+        }
+        if self.fuzzy_finder.is_some() {
+            return self.handle_fuzzy_finder_key(key_str) || had_message;
+        }
+        if self.focus_area == FocusArea::Marks {
+            return self.handle_marks_pane_key(key_str) || had_message;
+        }
+        if self.focus_area == FocusArea::TaskList
+            && let Some(redraw) = self.handle_task_list_key(key_str)
+        {
+            return redraw || had_message;
+        }
+        if self.focus_area == FocusArea::Quickfix
+            && let Some(redraw) = self.handle_quickfix_key(key_str)
+        {
+            return redraw || had_message;
+        }
+        let redraw = self.dispatch_key(key_str);
+        if self.mode == Mode::Command || self.substitute_preview.is_some() {
+            self.update_substitute_preview();
+        }
+        redraw || had_message
+    }
+
+    fn dispatch_key(&mut self, key_str: &str) -> bool {
         if (self.mode == Mode::Normal || self.mode == Mode::Visual)
             && let Some(digit) = key_str.chars().next().filter(|c| c.is_ascii_digit())
         {
@@ -240,28 +364,64 @@ impl App {
             }
         }
 
-        let action = handle_key(self.mode, key_str, &self.pending_keys, self.vi_mode);
+        let action = self.lookup_key(key_str);
 
         match action {
             Action::Pending => {
-                self.pending_keys.push_str(key_str);
+                self.pending_keys.push(key_str.to_string());
                 false
             }
+            Action::Operator(operator) if self.mode == Mode::Normal => {
+                self.begin_operator(operator, key_str)
+            }
             _ => {
                 self.pending_keys.clear();
-                let count = self.motion_count.take().unwrap_or(1);
-                self.execute_with_count(action, count)
+                let count = self.motion_count.take();
+                let redraw = match self.pending_operator.take() {
+                    Some(pending) => self.complete_operator(pending, action, count),
+                    None => self.execute_with_count(action, count),
+                };
+                if self.mode == Mode::Visual {
+                    self.sync_visual_selection();
+                }
+                redraw
             }
         }
     }
 
-    fn execute_with_count(&mut self, action: Action, count: usize) -> bool {
-        match &action {
-            Action::MoveCursor(_) | Action::NextDirectory | Action::PrevDirectory => {
-                for _ in 0..count {
-                    self.execute(action.clone());
+    /// Resolve a key against the keymap, restarting the sequence when the
+    /// pending keys lead nowhere
+    fn lookup_key(&mut self, key_str: &str) -> Action {
+        let action = handle_key(
+            &self.keymap,
+            self.mode,
+            key_str,
+            &self.pending_keys,
+            self.vi_mode,
+        );
+        if matches!(action, Action::None) && !self.pending_keys.is_empty() {
+            self.pending_keys.clear();
+            if let Some(pending) = &mut self.pending_operator {
+                pending.prefix = false;
+            }
+            return handle_key(&self.keymap, self.mode, key_str, &[], self.vi_mode);
+        }
+        action
+    }
+
+    /// Execute an action with an optional count: `G`/`gg` jump to that
+    /// line, other repeatable actions run that many times
+    fn execute_with_count(&mut self, action: Action, count: Option<usize>) -> bool {
+        match (&action, count) {
+            (Action::CursorToTop | Action::CursorToBottom, Some(line)) => {
+                self.execute_go_to_line(line)
+            }
+            (_, Some(n)) if action.is_repeatable() => {
+                let mut redraw = false;
+                for _ in 0..n {
+                    redraw |= self.execute(action.clone());
                 }
-                true
+                redraw
             }
             _ => self.execute(action),
         }
@@ -277,30 +437,36 @@ impl App {
                 crate::preview::is_image_file(p)
                     || crate::preview::is_text_file(p)
                     || crate::preview::is_media_file(p)
+                    || self.preview_line().is_some()
             })
     }
 
-    /// Get selected file paths
+    /// Get selected file paths, falling back to the entry under the cursor
+    /// when nothing is tagged
     pub fn selected_paths(&self) -> Vec<PathBuf> {
-        if let Some(browser) = self.browser() {
-            if self.selection.is_empty() {
-                browser
-                    .current_entry()
-                    .map(|e| vec![e.path.clone()])
-                    .unwrap_or_default()
-            } else {
-                self.selection.to_paths(&browser.entries)
-            }
-        } else {
-            Vec::new()
+        let Some(browser) = self.browser() else {
+            return Vec::new();
+        };
+        let paths = self
+            .selection
+            .to_paths(&browser.entries, self.persist_selection);
+        if !paths.is_empty() || self.selection.has_range() {
+            return paths;
         }
+        browser
+            .current_entry()
+            .filter(|e| e.name != "..")
+            .map(|e| vec![e.path.clone()])
+            .unwrap_or_default()
     }
 
     /// Exit visual mode if active
     pub fn exit_visual_if_active(&mut self) {
         if self.mode == Mode::Visual {
             self.mode = Mode::Normal;
-            self.selection.clear();
+        }
+        if self.visual_anchor.is_some() {
+            self.leave_visual();
         }
     }
 }
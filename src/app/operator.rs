@@ -0,0 +1,119 @@
+//! Operator-pending state: operators combined with motions and counts
+
+use std::ops::RangeInclusive;
+
+use crate::input::{Action, Operator};
+
+use super::App;
+
+/// An operator waiting for the motion that gives it a range
+#[derive(Clone, Copy, Debug)]
+pub struct PendingOperator {
+    pub operator: Operator,
+    /// Count typed before the operator key
+    pub count: Option<usize>,
+    /// Whether the pending keys still start with the operator key, so longer
+    /// bindings under it (`yoh`) can complete instead of aborting
+    pub prefix: bool,
+}
+
+impl App {
+    /// Start an operator, or apply it to the current line(s) when it is
+    /// repeated (`yy`, `3dd`)
+    pub(super) fn begin_operator(&mut self, operator: Operator, key_str: &str) -> bool {
+        let count = self.motion_count.take();
+        match self.pending_operator.take() {
+            Some(pending) if pending.operator == operator => {
+                self.pending_keys.clear();
+                let lines = pending.count.unwrap_or(1) * count.unwrap_or(1);
+                self.execute_operator_line(operator, lines)
+            }
+            Some(_) => {
+                self.pending_keys.clear();
+                false
+            }
+            None => {
+                self.pending_operator = Some(PendingOperator {
+                    operator,
+                    count,
+                    prefix: true,
+                });
+                self.pending_keys = vec![key_str.to_string()];
+                false
+            }
+        }
+    }
+
+    /// Finish a pending operator with the action that followed it
+    pub(super) fn complete_operator(
+        &mut self,
+        pending: PendingOperator,
+        action: Action,
+        count: Option<usize>,
+    ) -> bool {
+        if action.is_motion() {
+            let count = match (pending.count, count) {
+                (Some(a), Some(b)) => Some(a * b),
+                (a, b) => a.or(b),
+            };
+            self.execute_operator_motion(pending.operator, action, count)
+        } else if pending.prefix {
+            self.execute_with_count(action, count)
+        } else {
+            false
+        }
+    }
+
+    /// Apply an operator to `lines` entries starting at the cursor
+    pub fn execute_operator_line(&mut self, operator: Operator, lines: usize) -> bool {
+        let Some(browser) = self.browser() else {
+            return false;
+        };
+        if browser.entries.is_empty() {
+            return false;
+        }
+        let start = browser.cursor;
+        let end = (start + lines.max(1) - 1).min(browser.entries.len() - 1);
+        self.apply_operator(operator, start..=end)
+    }
+
+    fn execute_operator_motion(
+        &mut self,
+        operator: Operator,
+        motion: Action,
+        count: Option<usize>,
+    ) -> bool {
+        let Some(start) = self.browser().map(|b| b.cursor) else {
+            return false;
+        };
+        self.execute_with_count(motion, count);
+        let Some(end) = self.browser().map(|b| b.cursor) else {
+            return false;
+        };
+        self.apply_operator(operator, start.min(end)..=start.max(end))
+    }
+
+    /// Run the operator's action over a range of entries, leaving the cursor
+    /// at the start of the range
+    fn apply_operator(&mut self, operator: Operator, range: RangeInclusive<usize>) -> bool {
+        let Some(browser) = self.splits.focused_content_mut() else {
+            return false;
+        };
+        browser.cursor = *range.start();
+        let entries = browser.entries.get(range).unwrap_or_default();
+        if entries.iter().all(|e| e.name == "..") {
+            return true;
+        }
+
+        self.selection
+            .set_range(entries.iter().filter(|e| e.name != ".."));
+        let redraw = match operator {
+            Operator::Yank => self.execute_yank(),
+            Operator::Cut => self.execute_cut(),
+            Operator::Delete => self.execute_delete(),
+            Operator::Trash => self.execute_trash(),
+        };
+        self.selection.clear_range();
+        redraw
+    }
+}
@@ -0,0 +1,186 @@
+//! Commands that take a path: `:cd`, `:edit`, `:pwd`, `:mkdir`, `:touch`
+//! and `:rename`
+
+use std::path::{Path, PathBuf};
+
+use super::{App, CommandResult};
+use crate::filesystem;
+use crate::jobs::JobKind;
+use crate::navigation::TRASH_URI;
+
+impl App {
+    /// `:cd [path]`, `:cd -` goes back to the previous directory and no
+    /// argument goes home
+    pub fn execute_cd_command(&mut self, arg: &str) -> CommandResult {
+        if arg == TRASH_URI {
+            return self.execute_open_trash();
+        }
+        let target = match arg {
+            "" => dirs::home_dir().ok_or_else(|| "home directory not found".to_string()),
+            "-" => self
+                .browser()
+                .and_then(|b| b.previous_path())
+                .map(Path::to_path_buf)
+                .ok_or_else(|| "no previous directory".to_string()),
+            _ => self.resolve_path(arg),
+        };
+        match target {
+            Ok(path) => {
+                if self.navigate_focused(&path) && arg == "-" {
+                    self.show_message(path.display().to_string());
+                }
+            }
+            Err(e) => self.show_error(format!("cd: {e}")),
+        }
+        CommandResult::Redraw
+    }
+
+    /// `:edit <path>` navigates to a directory or opens a file; without a
+    /// path it re-reads the current directory
+    pub fn execute_edit_command(&mut self, arg: &str) -> CommandResult {
+        if arg.is_empty() {
+            if let Some(browser) = self.browser_mut() {
+                browser.refresh();
+            }
+            return CommandResult::Redraw;
+        }
+        if arg == TRASH_URI {
+            return self.execute_open_trash();
+        }
+        let path = match self.resolve_path(arg) {
+            Ok(path) => path,
+            Err(e) => {
+                self.show_error(format!("edit: {e}"));
+                return CommandResult::Redraw;
+            }
+        };
+        match std::fs::metadata(&path) {
+            Ok(meta) if meta.is_dir() => {
+                self.navigate_focused(&path);
+            }
+            Ok(_) => self.openers.open_files(&[path]),
+            Err(e) => self.show_error(format!("edit: {}: {e}", path.display())),
+        }
+        CommandResult::Redraw
+    }
+
+    /// `:pwd` shows the focused directory
+    pub fn execute_pwd_command(&mut self) -> CommandResult {
+        if let Some(path) = self.browser().map(|b| b.path.display().to_string()) {
+            self.show_message(path);
+        }
+        CommandResult::Redraw
+    }
+
+    /// `:mkdir [-p] <path>`
+    pub fn execute_mkdir_command(&mut self, arg: &str) -> CommandResult {
+        let (parents, name) = match arg.strip_prefix("-p") {
+            Some(rest) if rest.is_empty() || rest.starts_with(' ') => (true, rest.trim()),
+            _ => (false, arg),
+        };
+        match self.resolve_new_path(name, "mkdir") {
+            Some(path) => self.create_entry(JobKind::Mkdir { path, parents }),
+            None => CommandResult::Redraw,
+        }
+    }
+
+    /// `:touch <path>`
+    pub fn execute_touch_command(&mut self, arg: &str) -> CommandResult {
+        match self.resolve_new_path(arg, "touch") {
+            Some(path) => self.create_entry(JobKind::Touch { path }),
+            None => CommandResult::Redraw,
+        }
+    }
+
+    /// `:rename <name>` renames the entry under the cursor; the new name is
+    /// relative to the entry's directory
+    pub fn execute_rename_command(&mut self, arg: &str) -> CommandResult {
+        let Some(entry) = self
+            .browser()
+            .and_then(|b| b.current_entry())
+            .filter(|e| e.name != "..")
+            .cloned()
+        else {
+            self.show_error("rename: no entry under the cursor");
+            return CommandResult::Redraw;
+        };
+        if self.refuse_rename_here("rename") {
+            return CommandResult::Redraw;
+        }
+        let dir = entry.path.parent().unwrap_or(Path::new("/"));
+        match filesystem::expand_name(arg, dir) {
+            Ok(dest) if dest == entry.path => CommandResult::Redraw,
+            Ok(dest) => self.create_entry(JobKind::Rename {
+                src: entry.path,
+                dest,
+            }),
+            Err(e) => {
+                self.show_error(format!("rename: {e}"));
+                CommandResult::Redraw
+            }
+        }
+    }
+
+    /// Run a create or rename job now and put the cursor on the result;
+    /// failures are recorded in the error list
+    fn create_entry(&mut self, kind: JobKind) -> CommandResult {
+        let target = match &kind {
+            JobKind::Mkdir { path, .. } | JobKind::Touch { path } => path.clone(),
+            JobKind::Rename { dest, .. } => dest.clone(),
+            _ => return CommandResult::None,
+        };
+        let (_, result) = self.journal.run_now(&mut self.job_queue, kind, None, None);
+        let succeeded = result.is_ok();
+        if let Some(browser) = self.browser_mut() {
+            browser.refresh();
+            if succeeded {
+                browser.select_path(&target);
+            }
+        }
+        CommandResult::Redraw
+    }
+
+    /// Resolve the name of an entry to create in the focused directory
+    fn resolve_new_path(&mut self, arg: &str, command: &str) -> Option<PathBuf> {
+        let browser = self.browser()?;
+        let (base, in_archive) = (browser.path.clone(), browser.in_archive());
+        if in_archive {
+            self.show_error(format!("{command}: not supported inside archives"));
+            return None;
+        }
+        if browser.in_trash() {
+            self.show_error(format!("{command}: not supported in the trash"));
+            return None;
+        }
+        match filesystem::expand_name(arg, &base) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                self.show_error(format!("{command}: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Navigate the focused browser, reporting failures in the status bar
+    pub(super) fn navigate_focused(&mut self, path: &Path) -> bool {
+        let Some(browser) = self.browser_mut() else {
+            return false;
+        };
+        match browser.navigate_to(path) {
+            Ok(()) => true,
+            Err(e) => {
+                self.show_error(format!("{}: {e}", path.display()));
+                false
+            }
+        }
+    }
+
+    /// Expand a typed path relative to the focused directory
+    pub(super) fn resolve_path(&self, arg: &str) -> Result<PathBuf, String> {
+        let base = self
+            .browser()
+            .map(|b| b.path.clone())
+            .ok_or_else(|| "no directory focused".to_string())?;
+        filesystem::expand_path(arg, &base)
+    }
+}
@@ -15,7 +15,12 @@ impl App {
         window_height: u32,
         ctrl_held: bool,
     ) -> bool {
-        let layout = PointerLayout::calculate(window_height, &self.task_list, &self.error_list);
+        let layout = PointerLayout::calculate(
+            window_height,
+            &self.task_list,
+            &self.error_list,
+            self.quickfix.visible,
+        );
 
         match &event.kind {
             PointerEventKind::Press(PointerButton::Left) => {
@@ -45,7 +50,13 @@ impl App {
 
         if y < layout.main_content_height as f64 {
             self.handle_browser_click(x, y, layout.main_content_height, window_width, ctrl_held)
-        } else if y < (layout.main_content_height + layout.list_pane_height) as f64 {
+        } else if y < (layout.main_content_height + layout.quickfix_height) as f64 {
+            self.drag_start_pos = None;
+            self.focus_area = FocusArea::Quickfix;
+            true
+        } else if y
+            < (layout.main_content_height + layout.quickfix_height + layout.list_pane_height) as f64
+        {
             self.drag_start_pos = None;
             self.focus_area = FocusArea::TaskList;
             true
@@ -132,10 +143,12 @@ impl App {
     }
 
     fn handle_ctrl_click(&mut self, entry_index: usize) -> bool {
-        if let Some(browser) = self.browser_mut() {
+        if let Some(browser) = self.splits.focused_content_mut() {
             browser.cursor = entry_index;
+            if let Some(entry) = browser.entries.get(entry_index).filter(|e| e.name != "..") {
+                self.selection.toggle(entry);
+            }
         }
-        self.selection.toggle(entry_index);
         true
     }
 
@@ -4,6 +4,8 @@ use crate::jobs::{ErrorListPane, TaskListPane};
 
 pub struct PointerLayout {
     pub main_content_height: u32,
+    /// Grep results, between the splits and the task list
+    pub quickfix_height: u32,
     pub list_pane_height: u32,
 }
 
@@ -12,18 +14,24 @@ impl PointerLayout {
         window_height: u32,
         task_list: &TaskListPane,
         error_list: &ErrorListPane,
+        quickfix_visible: bool,
     ) -> Self {
         let status_height = 28u32;
-        let list_pane_visible = task_list.visible || error_list.visible;
-        let list_pane_height = if list_pane_visible {
-            (window_height as f32 * 0.20).round() as u32
-        } else {
-            0
+        let pane_height = |visible: bool| {
+            if visible {
+                (window_height as f32 * 0.20).round() as u32
+            } else {
+                0
+            }
         };
-        let main_content_height = window_height.saturating_sub(status_height + list_pane_height);
+        let quickfix_height = pane_height(quickfix_visible);
+        let list_pane_height = pane_height(task_list.visible || error_list.visible);
+        let main_content_height =
+            window_height.saturating_sub(status_height + quickfix_height + list_pane_height);
 
         Self {
             main_content_height,
+            quickfix_height,
             list_pane_height,
         }
     }
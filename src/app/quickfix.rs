@@ -0,0 +1,175 @@
+//! `:grep` and its results pane
+//!
+//! Matching lines are listed as `path:line:text` as the search finds them.
+//! While the pane is open `]q`/`[q` step through them, showing each file in
+//! the browser with the matching line highlighted in the preview; Enter
+//! jumps to the hit under the pane's cursor and `q` closes it. A search
+//! that reaches `HIT_LIMIT` stops there and says so in the pane's title.
+
+use std::path::{Path, PathBuf};
+
+use super::{App, CommandResult, FocusArea};
+use crate::filesystem::{GrepHit, HIT_LIMIT};
+use crate::jobs::{JobId, JobKind};
+
+/// Results of the last `:grep`
+#[derive(Default)]
+pub struct Quickfix {
+    /// The search filling the list
+    pub job: Option<JobId>,
+    pub pattern: String,
+    /// Directory searched; hits are listed by their path from it
+    pub root: PathBuf,
+    pub hits: Vec<GrepHit>,
+    pub cursor: usize,
+    /// The hit last jumped to
+    pub current: Option<usize>,
+    pub visible: bool,
+}
+
+impl Quickfix {
+    pub fn current_hit(&self) -> Option<&GrepHit> {
+        self.hits.get(self.current?)
+    }
+
+    /// Whether the search stopped at the hit limit, leaving files unsearched
+    pub fn truncated(&self) -> bool {
+        self.hits.len() >= HIT_LIMIT
+    }
+
+    /// How a hit is listed
+    pub fn hit_label(&self, hit: &GrepHit) -> String {
+        let path = hit.path.strip_prefix(&self.root).unwrap_or(&hit.path);
+        let path = if path.as_os_str().is_empty() {
+            Path::new(hit.path.file_name().unwrap_or_default())
+        } else {
+            path
+        };
+        format!("{}:{}:{}", path.display(), hit.line, hit.text.trim())
+    }
+}
+
+impl App {
+    /// `:grep PATTERN [PATH]` searches the files below PATH, or the focused
+    /// directory, replacing the results of any earlier search; `:grep`
+    /// alone shows the last results again
+    pub fn execute_grep_command(&mut self, args: &str) -> CommandResult {
+        if args.is_empty() {
+            if self.quickfix.job.is_none() {
+                self.show_error("grep: expected a pattern");
+            }
+            self.quickfix.visible = self.quickfix.job.is_some();
+            return CommandResult::Redraw;
+        }
+        // The last word is the path when it names one; otherwise everything
+        // is the pattern
+        let (pattern, root) = match args.rsplit_once(' ') {
+            Some((pattern, path)) => match self.resolve_path(path) {
+                Ok(root) if root.exists() => (pattern.trim(), Some(root)),
+                _ => (args, None),
+            },
+            None => (args, None),
+        };
+        let root = match root {
+            Some(root) => root,
+            None => match self.browser() {
+                Some(b) if b.get_archive_path().is_none() && !b.in_trash() => b.path.clone(),
+                Some(_) => {
+                    self.show_error("grep: only works in a directory");
+                    return CommandResult::Redraw;
+                }
+                None => return CommandResult::None,
+            },
+        };
+        let regex = match regex::Regex::new(pattern) {
+            Ok(regex) => regex,
+            Err(e) => {
+                self.show_error(format!("grep: {e}"));
+                return CommandResult::Redraw;
+            }
+        };
+        if let Some(previous) = self.quickfix.job {
+            self.job_queue.cancel(previous);
+        }
+        let job = self.submit_job_in(
+            JobKind::Grep {
+                root: root.clone(),
+                regex,
+            },
+            None,
+        );
+        self.quickfix = Quickfix {
+            job: Some(job),
+            pattern: pattern.to_string(),
+            root,
+            visible: true,
+            ..Quickfix::default()
+        };
+        CommandResult::Redraw
+    }
+
+    /// Add the matches of the running search to the list; returns whether
+    /// any arrived
+    pub fn deliver_grep_hits(&mut self) -> bool {
+        let matched = self.job_queue.take_matched();
+        for (job, hits) in &matched {
+            if self.quickfix.job == Some(*job) {
+                self.quickfix.hits.extend(hits.iter().cloned());
+            }
+        }
+        !matched.is_empty()
+    }
+
+    /// Whether the search filling the results pane is still running
+    pub fn grep_running(&self) -> bool {
+        self.quickfix
+            .job
+            .and_then(|id| self.job_queue.get(id))
+            .is_some_and(|job| job.is_active())
+    }
+
+    /// Keys only the results pane handles: `q` closes it. `None` passes the
+    /// key on.
+    pub fn handle_quickfix_key(&mut self, key: &str) -> Option<bool> {
+        match key {
+            "q" => {
+                self.quickfix.visible = false;
+                self.focus_area = FocusArea::Splits;
+                Some(true)
+            }
+            _ => None,
+        }
+    }
+
+    /// Step `delta` hits from the one last jumped to, or go to the first
+    pub fn execute_step_hit(&mut self, delta: isize) -> bool {
+        let last = self.quickfix.hits.len().saturating_sub(1);
+        let index = match self.quickfix.current {
+            Some(current) => current.saturating_add_signed(delta).min(last),
+            None => 0,
+        };
+        self.jump_to_hit(index)
+    }
+
+    /// Show the hit at `index`: its file selected in the browser and its
+    /// line in the preview
+    pub fn jump_to_hit(&mut self, index: usize) -> bool {
+        let Some(path) = self.quickfix.hits.get(index).map(|hit| hit.path.clone()) else {
+            return false;
+        };
+        self.quickfix.cursor = index;
+        self.quickfix.current = Some(index);
+        let focus = self.focus_area;
+        self.reveal_path(&path);
+        // Keep stepping through the list from the pane
+        self.focus_area = focus;
+        true
+    }
+
+    /// Line of the hit last jumped to, while its file is under the cursor
+    pub fn preview_line(&self) -> Option<usize> {
+        let hit = self.quickfix.current_hit()?;
+        let entry = self.browser()?.current_entry()?;
+        (entry.path == hit.path).then_some(hit.line)
+    }
+}
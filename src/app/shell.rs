@@ -0,0 +1,68 @@
+//! Shell commands: `:!cmd`, `:!!` and `:shell`
+
+use super::{App, CommandResult};
+use crate::cmdline::{self, Substitutions};
+use crate::jobs::JobKind;
+
+impl App {
+    /// `:!cmd` runs `cmd` in the focused directory as a job, with `%f`,
+    /// `%s`, `%d` and `%D` replaced by paths
+    pub fn execute_shell_command(&mut self, template: &str) -> CommandResult {
+        if template.is_empty() {
+            self.show_error("!: no command given");
+            return CommandResult::Redraw;
+        }
+        let Some(browser) = self.browser() else {
+            return CommandResult::None;
+        };
+        let cwd = browser.path.clone();
+        let subs = Substitutions {
+            current: browser
+                .current_entry()
+                .filter(|e| e.name != "..")
+                .map(|e| e.path.clone()),
+            selected: self.selected_paths(),
+            dir: cwd.clone(),
+            other_dir: self.other_split_dir(),
+        };
+        self.last_shell_command = Some(template.to_string());
+
+        match cmdline::substitute(template, &subs) {
+            Ok(command) => self.submit_job(JobKind::Shell { command, cwd }),
+            Err(e) => self.show_error(format!("!: {e}")),
+        }
+        CommandResult::Redraw
+    }
+
+    /// `:!!` runs the last shell command again against the current files
+    pub fn execute_repeat_shell_command(&mut self) -> CommandResult {
+        match self.last_shell_command.clone() {
+            Some(template) => self.execute_shell_command(&template),
+            None => {
+                self.show_error("!!: no previous command");
+                CommandResult::Redraw
+            }
+        }
+    }
+
+    /// `:shell` opens `$TERMINAL` in the focused directory
+    pub fn execute_open_terminal(&mut self) -> CommandResult {
+        let Some(cwd) = self.browser().map(|b| b.path.clone()) else {
+            return CommandResult::None;
+        };
+        let Some(terminal) = std::env::var_os("TERMINAL") else {
+            self.show_error("shell: $TERMINAL is not set");
+            return CommandResult::Redraw;
+        };
+        let spawned = std::process::Command::new(&terminal)
+            .current_dir(&cwd)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        if let Err(e) = spawned {
+            self.show_error(format!("shell: {}: {e}", terminal.to_string_lossy()));
+        }
+        CommandResult::Redraw
+    }
+}
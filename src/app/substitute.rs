@@ -0,0 +1,107 @@
+//! Regex renaming with `:s/pattern/replacement/flags`
+//!
+//! `:s` renames the selection, or the entry under the cursor, and `:%s`
+//! every entry listed. While the command is typed the new names are shown
+//! in the file list.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::{App, CommandResult};
+use crate::filesystem::{RenamePlan, Substitution};
+use crate::input::Mode;
+
+/// New names of a substitution being typed, shown in the file list
+pub struct SubstitutePreview {
+    pub names: HashMap<PathBuf, String>,
+    /// Why the renames could not be made as they stand
+    pub error: Option<String>,
+}
+
+impl App {
+    /// Run `:s` or `:%s`; `None` when `cmd` is neither
+    pub(super) fn execute_substitute_command(&mut self, cmd: &str) -> Option<CommandResult> {
+        let (whole, command) = split_command(cmd)?;
+        self.substitute_preview = None;
+        if self.refuse_rename_here("s") {
+            return Some(CommandResult::Redraw);
+        }
+        let substitution = match Substitution::parse(command) {
+            Ok(substitution) => substitution,
+            Err(e) => {
+                self.show_error(format!("s: {e}"));
+                return Some(CommandResult::Redraw);
+            }
+        };
+        let (paths, names) = self.substituted_names(whole, &substitution);
+        match RenamePlan::from_names(&paths, &names) {
+            Ok(plan) if plan.is_empty() => self.show_message("no names changed"),
+            Ok(plan) => self.apply_rename_plan(plan),
+            Err(e) => self.show_error(format!("s: {e}")),
+        }
+        Some(CommandResult::Redraw)
+    }
+
+    /// Follow the command line, previewing the names a substitution would
+    /// give once it parses
+    pub(super) fn update_substitute_preview(&mut self) {
+        let substitution = (self.mode == Mode::Command)
+            .then(|| split_command(self.command_line.text()))
+            .flatten()
+            .and_then(|(whole, command)| Some((whole, Substitution::parse(command).ok()?)));
+        self.substitute_preview = substitution.map(|(whole, substitution)| {
+            let (paths, names) = self.substituted_names(whole, &substitution);
+            let error = RenamePlan::from_names(&paths, &names).err();
+            SubstitutePreview {
+                names: paths.into_iter().zip(names).collect(),
+                error,
+            }
+        });
+    }
+
+    /// Entries the substitution matches, in listing order, with their new
+    /// names; the counter numbers them from 1. Names that are not UTF-8
+    /// are left alone, as a regex could only see them mangled.
+    fn substituted_names(
+        &self,
+        whole: bool,
+        substitution: &Substitution,
+    ) -> (Vec<PathBuf>, Vec<String>) {
+        let paths = if whole {
+            self.browser()
+                .map(|b| {
+                    b.entries
+                        .iter()
+                        .filter(|e| e.name != "..")
+                        .map(|e| e.path.clone())
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            self.selected_paths()
+        };
+        let mut renamed = (Vec::new(), Vec::new());
+        for path in paths {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if let Some(new_name) = substitution.apply(name, renamed.0.len() + 1) {
+                renamed.0.push(path);
+                renamed.1.push(new_name);
+            }
+        }
+        renamed
+    }
+}
+
+/// Whether `cmd` is `:%s` and the substitution after its `s`, for commands
+/// where the `s` is directly followed by a delimiter
+fn split_command(cmd: &str) -> Option<(bool, &str)> {
+    let (whole, rest) = match cmd.strip_prefix('%') {
+        Some(rest) => (true, rest),
+        None => (false, cmd),
+    };
+    let command = rest.strip_prefix('s')?;
+    let delimiter = command.chars().next()?;
+    (!delimiter.is_alphanumeric() && !delimiter.is_whitespace()).then_some((whole, command))
+}
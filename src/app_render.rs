@@ -18,8 +18,10 @@ pub fn render_app(canvas: &mut Canvas, text_renderer: &mut TextRenderer, app: &A
     canvas.clear(colors.bg);
 
     let list_pane_height = calculate_list_pane_height(app, height);
+    let quickfix_height = calculate_quickfix_height(app, height);
     let main_content_height = height
         .saturating_sub(layout.status_height as u32)
+        .saturating_sub(quickfix_height)
         .saturating_sub(list_pane_height);
 
     render_split_panes(
@@ -33,6 +35,22 @@ pub fn render_app(canvas: &mut Canvas, text_renderer: &mut TextRenderer, app: &A
         main_content_height,
     );
 
+    if app.quickfix.visible {
+        render::render_quickfix_pane(
+            canvas,
+            text_renderer,
+            &app.quickfix,
+            app.grep_running(),
+            0,
+            main_content_height as i32,
+            width,
+            quickfix_height,
+            app.focus_area == FocusArea::Quickfix,
+            &colors,
+            &layout,
+        );
+    }
+
     if app.task_list.visible || app.error_list.visible {
         render_task_pane(
             canvas,
@@ -40,7 +58,7 @@ pub fn render_app(canvas: &mut Canvas, text_renderer: &mut TextRenderer, app: &A
             app,
             &colors,
             &layout,
-            main_content_height,
+            main_content_height + quickfix_height,
             width,
             list_pane_height,
         );
@@ -61,6 +79,47 @@ pub fn render_app(canvas: &mut Canvas, text_renderer: &mut TextRenderer, app: &A
         );
     }
 
+    if app.marks_pane.visible {
+        render::render_marks_panel(
+            canvas,
+            text_renderer,
+            &app.bookmarks,
+            &app.marks_pane,
+            width,
+            height,
+            theme,
+            &colors,
+            &layout,
+        );
+    }
+
+    if let Some(finder) = &app.fuzzy_finder {
+        render::render_fuzzy_finder(
+            canvas,
+            text_renderer,
+            finder,
+            app.fuzzy_finder_indexing(),
+            width,
+            height,
+            theme,
+            &colors,
+            &layout,
+        );
+    }
+
+    if let Some(preview) = &app.rename_preview {
+        render::render_rename_preview(
+            canvas,
+            text_renderer,
+            preview,
+            width,
+            height,
+            theme,
+            &colors,
+            &layout,
+        );
+    }
+
     render_status(canvas, text_renderer, app, &colors, &layout, height, width);
 }
 
@@ -72,6 +131,14 @@ fn calculate_list_pane_height(app: &App, height: u32) -> u32 {
     }
 }
 
+fn calculate_quickfix_height(app: &App, height: u32) -> u32 {
+    if app.quickfix.visible {
+        (height as f32 * 0.20).round() as u32
+    } else {
+        0
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn render_split_panes(
     canvas: &mut Canvas,
@@ -103,6 +170,9 @@ fn render_split_panes(
                 colors,
                 layout,
                 app.icons_enabled,
+                is_focused
+                    .then_some(app.substitute_preview.as_ref())
+                    .flatten(),
             );
         });
 }
@@ -130,6 +200,7 @@ fn render_task_pane(
         y as i32,
         width,
         height,
+        app.job_output_shown(),
         app.focus_area == FocusArea::TaskList,
         colors,
         layout,
@@ -137,31 +208,13 @@ fn render_task_pane(
 }
 
 fn prepare_task_pane_data(app: &App) -> (Vec<&Job>, usize, &'static str, &'static str) {
-    let all_jobs = app.job_queue.all_jobs();
-    if app.error_list.visible && !app.task_list.visible {
-        (
-            all_jobs.iter().filter(|j| j.is_failed()).collect(),
-            app.error_list.cursor,
-            "Errors",
-            "No errors",
-        )
+    let jobs = app.list_pane_jobs();
+    if app.showing_error_list() {
+        (jobs, app.error_list.cursor, "Errors", "No errors")
     } else if app.task_list.visible && !app.error_list.visible {
-        (
-            all_jobs
-                .iter()
-                .filter(|j| j.is_active() || j.is_complete())
-                .collect(),
-            app.task_list.cursor,
-            "Tasks",
-            "No active tasks",
-        )
+        (jobs, app.task_list.cursor, "Tasks", "No active tasks")
     } else {
-        (
-            all_jobs.iter().collect(),
-            app.task_list.cursor,
-            "Tasks & Errors",
-            "No tasks",
-        )
+        (jobs, app.task_list.cursor, "Tasks & Errors", "No tasks")
     }
 }
 
@@ -175,12 +228,21 @@ fn render_status(
     width: u32,
 ) {
     let cursor_info = app.browser().map(|b| (b.cursor, b.entries.len()));
+    let selection_info = app
+        .browser()
+        .map(|b| app.selection.summary(&b.entries, app.persist_selection))
+        .filter(|&(count, _)| count > 0);
+    let prompt = app
+        .paste_conflict
+        .as_ref()
+        .and_then(|plan| plan.prompt())
+        .or_else(|| app.delete_prompt.as_ref().map(|d| d.prompt()));
     render::render_status_bar(
         canvas,
         text_renderer,
         &app.mode,
-        &app.command_buffer,
-        &app.search_buffer,
+        &app.command_line,
+        &app.search_line,
         app.last_search.as_deref(),
         app.search_highlight,
         &app.search_matches,
@@ -188,6 +250,11 @@ fn render_status(
         app.job_queue.active_count(),
         app.job_queue.failed_count(),
         cursor_info,
+        selection_info,
+        prompt
+            .as_deref()
+            .map(|p| (p, false))
+            .or_else(|| app.message.as_ref().map(|m| (m.text.as_str(), m.is_error))),
         (height - layout.status_height as u32) as i32,
         width,
         colors,
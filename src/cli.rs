@@ -37,18 +37,102 @@ fn print_help() -> ! {
     eprintln!("  h/l               Parent/enter directory");
     eprintln!("  gg/G              Go to top/bottom");
     eprintln!("  v                 Enter visual mode");
-    eprintln!("  yy                Yank selected");
-    eprintln!("  d                 Cut selected");
+    eprintln!("  }}/{{               Next/previous run of directories or files");
+    eprintln!("  y{{motion}}, yy     Yank");
+    eprintln!("  d{{motion}}, dd     Cut");
+    eprintln!("  x{{motion}}, xx     Delete for good, after asking");
+    eprintln!("  X{{motion}}, XX     Move to trash");
+    eprintln!("  [count]           Repeat an action or extend a motion (3j, d5j, 10G)");
     eprintln!("  p                 Paste");
+    eprintln!("  u/Ctrl+r          Undo/redo a move, rename, trash, link, mkdir or chmod");
+    eprintln!("                    (Ctrl+z/Ctrl+y in standard mode)");
+    eprintln!("  Space             Tag/untag the entry and move down");
+    eprintln!("  a/A               Rename (cursor before the extension/at the end)");
     eprintln!("  =                 Open file with default app");
     eprintln!("  :q                Quit");
     eprintln!();
+    eprintln!("Visual mode:");
+    eprintln!("  motions           Extend the range (j/k, gg/G, counts, n/N)");
+    eprintln!("  o                 Swap the cursor and the range anchor");
+    eprintln!("  y/d/x/X           Yank, cut, delete or trash the range");
+    eprintln!("  e/=/r             Extract, open or bulk rename the range");
+    eprintln!("  L                 Symlink the range into the next split");
+    eprintln!("  :                 Run a command (e.g. :chmod) on the range");
+    eprintln!("  gv                Reselect the last range (from normal mode)");
+    eprintln!("  gf                Go to the directory containing the entry");
+    eprintln!("  C-p, <leader>f    Fuzzy find a file below the directory (Enter opens,");
+    eprintln!("                    C-v/C-s open in a new split; [keys] leader, \\ by default)");
+    eprintln!();
     eprintln!("Split commands (Ctrl+w prefix):");
     eprintln!("  Ctrl+w v          Create vertical split");
     eprintln!("  Ctrl+w s          Create horizontal split");
     eprintln!("  Ctrl+w h/j/k/l    Focus left/down/up/right pane");
     eprintln!("  Ctrl+w c/q        Close current split");
     eprintln!();
+    eprintln!("Command and search line:");
+    eprintln!("  Left/Right, C-a/C-e  Move the cursor");
+    eprintln!("  C-w, C-u          Delete a word, or everything before the cursor");
+    eprintln!("  Up/Down           Recall history matching what was typed");
+    eprintln!("  Tab, S-Tab        Complete commands, options, themes and paths");
+    eprintln!();
+    eprintln!("Paths (~, $VAR and globs matching one path are expanded):");
+    eprintln!("  :cd [PATH]        Change directory (home without a path)");
+    eprintln!("  :cd -             Go back to the previous directory");
+    eprintln!("  :cd trash://      Browse the trash (x deletes for good)");
+    eprintln!("  :restore          Put trash entries back where they came from");
+    eprintln!("  :emptytrash       Delete everything in the trash, after asking");
+    eprintln!("  :e PATH           Open a file, or change to a directory");
+    eprintln!("  :pwd              Show the current directory");
+    eprintln!();
+    eprintln!("Files:");
+    eprintln!("  :mkdir [-p] PATH  Create a directory (and missing parents with -p)");
+    eprintln!("  :touch PATH       Create an empty file");
+    eprintln!("  :rename NAME      Rename the entry under the cursor");
+    eprintln!("  :rename           Bulk rename in $EDITOR");
+    eprintln!("                    (a/b moves into a new directory; swaps are safe and");
+    eprintln!("                    the old -> new names are shown for confirmation)");
+    eprintln!("  :s/PAT/REP/[gi]   Regex rename the selection; :%s renames every entry");
+    eprintln!("                    (\\1 groups, \\U/\\L/\\E case, {{n:03}} counter)");
+    eprintln!();
+    eprintln!("Shell:");
+    eprintln!("  :!CMD             Run CMD in the current directory as a task");
+    eprintln!("                    %f entry, %s selection, %d dir, %D other split's dir");
+    eprintln!("  :!!               Run the last command again");
+    eprintln!("  :shell            Open $TERMINAL in the current directory");
+    eprintln!("  Enter (task list) Show a task's output");
+    eprintln!(
+        "  dd (task list)    Cancel a queued task, or a running transfer, delete, search or command"
+    );
+    eprintln!("  p (task list)     Pause or resume a copy, move or delete");
+    eprintln!("  K/J (task list)   Move a queued task earlier/later");
+    eprintln!("  Enter (batch)     Show or hide the tasks of a multi-item paste");
+    eprintln!("  r/R (failed)      Retry a failed task, R replacing its destination");
+    eprintln!("  D (failed)        Retry with another destination (:retry PATH)");
+    eprintln!("  o/O (failed)      Reveal the source/destination of a failed task");
+    eprintln!("  y/x (failed)      Copy the error text / dismiss the failed task");
+    eprintln!();
+    eprintln!("Marks (uppercase marks are saved, lowercase last the session):");
+    eprintln!("  m{{a-z,A-Z}}        Set a mark on the current directory");
+    eprintln!("  '{{a-z,A-Z}}        Jump to a mark");
+    eprintln!("  :find [OPTS] PAT  List matches below the directory (glob, or -regex PAT;");
+    eprintln!("                    -maxdepth N, -type f|d|l, -size +1M, -mtime -7, -gitignore)");
+    eprintln!("  :grep PAT [PATH]  Search file contents below the directory or PATH;");
+    eprintln!("                    ]q/[q step through the matches, Enter jumps, q closes");
+    eprintln!("  :marks            List marks (Enter jump, d delete, r rename)");
+    eprintln!("  Ctrl+o/Ctrl+i     Back/forward through visited directories");
+    eprintln!("                    (Alt+Left/Alt+Right in standard mode)");
+    eprintln!("  :z TERMS          Go to the most frecent visited directory matching");
+    eprintln!("                    TERMS in order, the last in its name");
+    eprintln!("  :zi [TERMS]       Pick from the matching visited directories");
+    eprintln!("  :zimport SRC [P]  Import a zoxide or autojump database");
+    eprintln!("  :delmarks a-c X   Delete marks; :delmarks! deletes all lowercase");
+    eprintln!("  [bookmarks]       Define marks in config.toml, e.g. d = \"~/Downloads\"");
+    eprintln!();
+    eprintln!("Selection:");
+    eprintln!("  :select [GLOB]    Tag matching entries (all without a pattern)");
+    eprintln!("  :unselect [GLOB]  Untag matching entries (all without a pattern)");
+    eprintln!("  :invert           Invert the tagged entries");
+    eprintln!();
     eprintln!("Settings (:set command):");
     eprintln!("  :set hidden       Show hidden files");
     eprintln!("  :set nohidden     Hide hidden files");
@@ -56,7 +140,11 @@ fn print_help() -> ! {
     eprintln!("  :set nooverlay    Disable preview overlay");
     eprintln!("  :set parent       Show parent directory entry (..)");
     eprintln!("  :set noparent     Hide parent directory entry");
+    eprintln!("  :set persistselect Keep tagged files when changing directory");
+    eprintln!("  :set nopreserve   Copy without timestamps, xattrs and hard links");
     eprintln!("  :set theme=NAME   Change theme (e.g., :set theme=dracula)");
     eprintln!("  :set theme=       Reset to default theme");
+    eprintln!("  :set conflict=X   When a pasted name exists: prompt, skip, overwrite,");
+    eprintln!("                    rename or newer (paste_conflict in config.toml)");
     std::process::exit(0);
 }
@@ -0,0 +1,325 @@
+//! Tab completion for the command line
+
+use std::path::{Path, PathBuf};
+
+/// Argument a command takes, for completion
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgKind {
+    None,
+    Path,
+    SetOption,
+    Sort,
+}
+
+/// Commands known to the command line and the argument each takes
+pub const COMMANDS: &[(&str, ArgKind)] = &[
+    ("bulkrename", ArgKind::None),
+    ("cd", ArgKind::Path),
+    ("chmod", ArgKind::None),
+    ("delmarks", ArgKind::None),
+    ("edit", ArgKind::Path),
+    ("emptytrash", ArgKind::None),
+    ("filter", ArgKind::None),
+    ("find", ArgKind::None),
+    ("grep", ArgKind::Path),
+    ("invert", ArgKind::None),
+    ("ln", ArgKind::None),
+    ("marks", ArgKind::None),
+    ("mkdir", ArgKind::Path),
+    ("pwd", ArgKind::None),
+    ("qall", ArgKind::None),
+    ("quit", ArgKind::None),
+    ("rename", ArgKind::None),
+    ("restore", ArgKind::None),
+    ("retry", ArgKind::Path),
+    ("select", ArgKind::None),
+    ("set", ArgKind::SetOption),
+    ("shell", ArgKind::None),
+    ("sort", ArgKind::Sort),
+    ("split", ArgKind::None),
+    ("symlink", ArgKind::None),
+    ("touch", ArgKind::Path),
+    ("unselect", ArgKind::None),
+    ("vsplit", ArgKind::None),
+    ("wq", ArgKind::None),
+    ("write", ArgKind::None),
+    ("z", ArgKind::None),
+    ("zi", ArgKind::None),
+    ("zimport", ArgKind::Path),
+];
+
+/// `:set` arguments offered for completion
+const SET_OPTIONS: &[&str] = &[
+    "hidden",
+    "nohidden",
+    "overlay",
+    "nooverlay",
+    "parent",
+    "noparent",
+    "persistselect",
+    "nopersistselect",
+    "preserve",
+    "nopreserve",
+    "theme=",
+    "conflict=",
+];
+
+/// `:set conflict=` policies offered for completion
+const CONFLICT_POLICIES: &[&str] = &["prompt", "skip", "overwrite", "rename", "newer"];
+
+const SORT_MODES: &[&str] = &["name", "size", "date", "type"];
+
+/// What completion needs to know about the application
+pub struct CompletionContext {
+    /// Directory relative paths are completed against
+    pub cwd: PathBuf,
+    pub themes: Vec<String>,
+}
+
+impl CompletionContext {
+    pub fn new(cwd: PathBuf) -> Self {
+        Self {
+            cwd,
+            themes: theme_names(),
+        }
+    }
+}
+
+/// Candidates for the word ending at the cursor, cycled with Tab
+#[derive(Clone, Debug)]
+pub struct Completion {
+    /// Byte offset where the completed word starts
+    pub start: usize,
+    pub candidates: Vec<String>,
+    index: Option<usize>,
+}
+
+impl Completion {
+    /// Advance to the next (or previous) candidate
+    pub fn cycle(&mut self, forward: bool) -> &str {
+        let len = self.candidates.len();
+        let next = match (self.index, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+        };
+        self.index = Some(next);
+        &self.candidates[next]
+    }
+}
+
+/// Complete the text before the cursor
+pub fn complete(line: &str, ctx: &CompletionContext) -> Option<Completion> {
+    let trimmed = line.trim_start();
+    let offset = line.len() - trimmed.len();
+
+    let Some(cmd_end) = trimmed.find(char::is_whitespace) else {
+        let candidates = filter_prefix(COMMANDS.iter().map(|(name, _)| *name), trimmed);
+        return make(offset, candidates);
+    };
+
+    let cmd = &trimmed[..cmd_end];
+    let start = line
+        .rfind(char::is_whitespace)
+        .map(|i| i + 1)
+        .unwrap_or(line.len());
+    let word = &line[start..];
+
+    let candidates = match arg_kind(cmd) {
+        ArgKind::None => return None,
+        ArgKind::Path => complete_path(word, &ctx.cwd),
+        ArgKind::Sort => filter_prefix(SORT_MODES.iter().copied(), word),
+        ArgKind::SetOption => {
+            if let Some(theme) = word.strip_prefix("theme=") {
+                filter_prefix(ctx.themes.iter().map(String::as_str), theme)
+                    .into_iter()
+                    .map(|t| format!("theme={t}"))
+                    .collect()
+            } else if let Some(policy) = word.strip_prefix("conflict=") {
+                filter_prefix(CONFLICT_POLICIES.iter().copied(), policy)
+                    .into_iter()
+                    .map(|p| format!("conflict={p}"))
+                    .collect()
+            } else {
+                filter_prefix(SET_OPTIONS.iter().copied(), word)
+            }
+        }
+    };
+    make(start, candidates)
+}
+
+fn make(start: usize, candidates: Vec<String>) -> Option<Completion> {
+    if candidates.is_empty() {
+        None
+    } else {
+        Some(Completion {
+            start,
+            candidates,
+            index: None,
+        })
+    }
+}
+
+/// Argument kind for a command name or unambiguous abbreviation
+fn arg_kind(cmd: &str) -> ArgKind {
+    if let Some((_, kind)) = COMMANDS.iter().find(|(name, _)| *name == cmd) {
+        return *kind;
+    }
+    let mut matches = COMMANDS.iter().filter(|(name, _)| name.starts_with(cmd));
+    match (matches.next(), matches.next()) {
+        (Some((_, kind)), None) => *kind,
+        _ => match cmd {
+            "se" => ArgKind::SetOption,
+            "e" => ArgKind::Path,
+            _ => ArgKind::None,
+        },
+    }
+}
+
+fn filter_prefix<'a>(items: impl Iterator<Item = &'a str>, prefix: &str) -> Vec<String> {
+    items
+        .filter(|item| item.starts_with(prefix))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Complete a filesystem path, keeping the directory part as typed
+fn complete_path(word: &str, cwd: &Path) -> Vec<String> {
+    if word == "~" {
+        return vec!["~/".to_string()];
+    }
+    let (dir_part, prefix) = match word.rfind('/') {
+        Some(i) => (&word[..=i], &word[i + 1..]),
+        None => ("", word),
+    };
+    let dir = if let Some(rest) = dir_part.strip_prefix("~/") {
+        dirs::home_dir().map(|h| h.join(rest))
+    } else if dir_part.is_empty() {
+        Some(cwd.to_path_buf())
+    } else {
+        Some(cwd.join(dir_part))
+    };
+    let Some(read_dir) = dir.and_then(|d| std::fs::read_dir(d).ok()) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<String> = read_dir
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let is_dir = entry.path().is_dir();
+            Some(format!("{dir_part}{name}{}", if is_dir { "/" } else { "" }))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+/// Theme names available in the user's themes directory
+fn theme_names() -> Vec<String> {
+    let Some(dir) = dirs::config_dir().map(|d| d.join("mkfm").join("themes")) else {
+        return Vec::new();
+    };
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = read_dir
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            path.is_file()
+                .then(|| path.file_stem())
+                .flatten()
+                .map(|s| s.to_string_lossy().into_owned())
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn ctx() -> CompletionContext {
+        CompletionContext {
+            cwd: std::env::temp_dir(),
+            themes: vec!["dracula".to_string(), "nord".to_string()],
+        }
+    }
+
+    fn candidates(line: &str) -> Vec<String> {
+        complete(line, &ctx())
+            .map(|c| c.candidates)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_complete_command_names() {
+        assert_eq!(candidates("so"), vec!["sort"]);
+        assert_eq!(candidates("s").len(), 6);
+        assert!(candidates("zzz").is_empty());
+    }
+
+    #[test]
+    fn test_complete_set_options_and_themes() {
+        assert_eq!(candidates("set nohi"), vec!["nohidden"]);
+        assert_eq!(candidates("se theme=d"), vec!["theme=dracula"]);
+        assert_eq!(candidates("set conflict=o"), vec!["conflict=overwrite"]);
+        assert_eq!(candidates("set nopr"), vec!["nopreserve"]);
+        let completion = complete("set theme=", &ctx()).unwrap();
+        assert_eq!(completion.start, 4);
+        assert_eq!(completion.candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_complete_sort_modes() {
+        assert_eq!(candidates("sort s"), vec!["size"]);
+        assert!(candidates("chmod ").is_empty());
+    }
+
+    #[test]
+    fn test_path_commands_take_paths() {
+        assert_eq!(arg_kind("cd"), ArgKind::Path);
+        assert_eq!(arg_kind("e"), ArgKind::Path);
+        assert_eq!(arg_kind("edi"), ArgKind::Path);
+        assert_eq!(arg_kind("pwd"), ArgKind::None);
+        assert_eq!(arg_kind("grep"), ArgKind::Path);
+        assert_eq!(arg_kind("retry"), ArgKind::Path);
+        assert_eq!(arg_kind("zimport"), ArgKind::Path);
+        assert_eq!(arg_kind("restore"), ArgKind::None);
+        assert_eq!(candidates("z"), vec!["z", "zi", "zimport"]);
+    }
+
+    #[test]
+    fn test_complete_path() {
+        let dir = TempDir::new("complete");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("file.txt"), "").unwrap();
+        std::fs::write(dir.join(".hidden"), "").unwrap();
+
+        assert_eq!(complete_path("", &dir), vec!["file.txt", "sub/"]);
+        assert_eq!(complete_path("s", &dir), vec!["sub/"]);
+        assert_eq!(complete_path(".h", &dir), vec![".hidden"]);
+        let nested = format!("{}/f", dir.display());
+        assert_eq!(
+            complete_path(&nested, Path::new("/")),
+            vec![format!("{}/file.txt", dir.display())]
+        );
+    }
+
+    #[test]
+    fn test_cycle_wraps() {
+        let mut completion = complete("set theme=", &ctx()).unwrap();
+        assert_eq!(completion.cycle(true), "theme=dracula");
+        assert_eq!(completion.cycle(true), "theme=nord");
+        assert_eq!(completion.cycle(true), "theme=dracula");
+        assert_eq!(completion.cycle(false), "theme=nord");
+    }
+}
@@ -0,0 +1,135 @@
+//! Persistent command-line history
+
+use std::path::PathBuf;
+
+/// Most lines kept per history file
+const MAX_ENTRIES: usize = 500;
+
+/// Recall list for one kind of command line (commands or searches)
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    entries: Vec<String>,
+    /// Entry currently recalled, counting from the oldest
+    position: Option<usize>,
+    /// What was typed before recalling, used as a prefix filter
+    draft: String,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Load `name` from the state directory (`~/.local/state/mkfm`), falling
+    /// back to the data directory on platforms without one
+    pub fn load(name: &str) -> Self {
+        let path = dirs::state_dir()
+            .or_else(dirs::data_dir)
+            .map(|dir| dir.join("mkfm").join(name));
+        let entries = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|s| s.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        Self {
+            entries,
+            path,
+            ..Self::default()
+        }
+    }
+
+    /// Record a submitted line, moving duplicates to the end, and save
+    pub fn push(&mut self, line: &str) {
+        self.reset();
+        if line.trim().is_empty() {
+            return;
+        }
+        self.entries.retain(|e| e != line);
+        self.entries.push(line.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+        if let Err(e) = self.save() {
+            eprintln!("warning: failed to save history: {e}");
+        }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        std::fs::write(path, content)
+    }
+
+    /// Step to an older entry starting with what was typed, returning the
+    /// line to show
+    pub fn prev(&mut self, current: &str) -> Option<String> {
+        if self.position.is_none() {
+            self.draft = current.to_string();
+        }
+        let end = self.position.unwrap_or(self.entries.len());
+        let found = self.entries[..end]
+            .iter()
+            .rposition(|e| e.starts_with(&self.draft))?;
+        self.position = Some(found);
+        Some(self.entries[found].clone())
+    }
+
+    /// Step to a newer entry, returning the typed draft after the newest
+    pub fn next(&mut self) -> Option<String> {
+        let start = self.position? + 1;
+        match self.entries[start..]
+            .iter()
+            .position(|e| e.starts_with(&self.draft))
+        {
+            Some(offset) => {
+                self.position = Some(start + offset);
+                Some(self.entries[start + offset].clone())
+            }
+            None => {
+                self.position = None;
+                Some(std::mem::take(&mut self.draft))
+            }
+        }
+    }
+
+    /// Forget the recall position (new line being typed)
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(lines: &[&str]) -> History {
+        History {
+            entries: lines.iter().map(|s| s.to_string()).collect(),
+            ..History::default()
+        }
+    }
+
+    #[test]
+    fn test_recall_with_prefix() {
+        let mut h = history(&["set hidden", "sort size", "set theme=nord"]);
+        assert_eq!(h.prev("se").as_deref(), Some("set theme=nord"));
+        assert_eq!(h.prev("").as_deref(), Some("set hidden"));
+        assert_eq!(h.prev(""), None);
+        assert_eq!(h.next().as_deref(), Some("set theme=nord"));
+        assert_eq!(h.next().as_deref(), Some("se"));
+        assert_eq!(h.next(), None);
+    }
+
+    #[test]
+    fn test_push_moves_duplicates_to_end() {
+        let mut h = history(&["a", "b", "c"]);
+        h.push("a");
+        assert_eq!(h.entries, vec!["b", "c", "a"]);
+        h.push("  ");
+        assert_eq!(h.entries.len(), 3);
+    }
+}
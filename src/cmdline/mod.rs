@@ -0,0 +1,217 @@
+//! Command-line editing for Command and Search modes
+//!
+//! `LineEditor` holds the text being typed and its cursor, `History` recalls
+//! and persists earlier lines, `complete` produces Tab candidates and
+//! `substitute` fills in the paths of a `:!` shell command.
+
+mod complete;
+mod history;
+mod shell;
+
+pub use complete::{Completion, CompletionContext, complete};
+pub use history::History;
+pub use shell::{Substitutions, substitute};
+
+/// Editing keys understood by the line editor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditKey {
+    Left,
+    Right,
+    Home,
+    End,
+    Delete,
+    DeleteWord,
+    DeleteToStart,
+    HistoryPrev,
+    HistoryNext,
+    Complete,
+    CompletePrev,
+}
+
+/// A single line of editable text with a cursor
+#[derive(Clone, Debug, Default)]
+pub struct LineEditor {
+    text: String,
+    /// Byte offset of the cursor, always on a char boundary
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Text before the cursor
+    pub fn before_cursor(&self) -> &str {
+        &self.text[..self.cursor]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    /// Replace the text, putting the cursor at the end
+    pub fn set(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+    }
+
+    /// Replace the text, putting the cursor at a byte offset (clamped)
+    pub fn set_with_cursor(&mut self, text: &str, cursor: usize) {
+        self.text = text.to_string();
+        self.cursor = cursor.min(self.text.len());
+        while !self.text.is_char_boundary(self.cursor) {
+            self.cursor -= 1;
+        }
+    }
+
+    /// Replace `text[start..cursor]` with `replacement`
+    pub fn replace_before_cursor(&mut self, start: usize, replacement: &str) {
+        self.text.replace_range(start..self.cursor, replacement);
+        self.cursor = start + replacement.len();
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(c) = self.before_cursor().chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if let Some(c) = self.before_cursor().chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// Delete the word before the cursor along with trailing whitespace
+    /// (`C-w`); `/` also ends a word so path components go one at a time
+    pub fn delete_word(&mut self) {
+        let before = self.before_cursor();
+        let trimmed = before.trim_end_matches(|c: char| c.is_whitespace() || c == '/');
+        let start = trimmed
+            .rfind(|c: char| c.is_whitespace() || c == '/')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// Delete everything before the cursor (`C-u`)
+    pub fn delete_to_start(&mut self) {
+        self.text.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+
+    /// Apply a cursor or deletion key; history and completion keys are
+    /// handled by the caller
+    pub fn apply(&mut self, key: EditKey) {
+        match key {
+            EditKey::Left => self.left(),
+            EditKey::Right => self.right(),
+            EditKey::Home => self.home(),
+            EditKey::End => self.end(),
+            EditKey::Delete => self.delete(),
+            EditKey::DeleteWord => self.delete_word(),
+            EditKey::DeleteToStart => self.delete_to_start(),
+            EditKey::HistoryPrev
+            | EditKey::HistoryNext
+            | EditKey::Complete
+            | EditKey::CompletePrev => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str, cursor: usize) -> LineEditor {
+        let mut ed = LineEditor::new();
+        ed.set_with_cursor(text, cursor);
+        ed
+    }
+
+    #[test]
+    fn test_insert_and_move() {
+        let mut ed = LineEditor::new();
+        for c in "sot".chars() {
+            ed.insert(c);
+        }
+        ed.left();
+        ed.insert('r');
+        assert_eq!(ed.text(), "sort");
+        ed.home();
+        ed.delete();
+        assert_eq!(ed.text(), "ort");
+        ed.end();
+        ed.backspace();
+        assert_eq!((ed.text(), ed.cursor()), ("or", 2));
+    }
+
+    #[test]
+    fn test_multibyte_cursor() {
+        let mut ed = editor("añb", 3);
+        ed.backspace();
+        assert_eq!(ed.text(), "ab");
+        ed.right();
+        assert_eq!(ed.cursor(), 2);
+    }
+
+    #[test]
+    fn test_delete_word() {
+        let mut ed = editor("cd ~/src/mkfm/", 14);
+        ed.delete_word();
+        assert_eq!(ed.text(), "cd ~/src/");
+        ed.delete_word();
+        ed.delete_word();
+        assert_eq!(ed.text(), "cd ");
+        ed.delete_word();
+        assert_eq!(ed.text(), "");
+    }
+
+    #[test]
+    fn test_delete_to_start_keeps_rest() {
+        let mut ed = editor("set hidden", 4);
+        ed.delete_to_start();
+        assert_eq!((ed.text(), ed.cursor()), ("hidden", 0));
+    }
+}
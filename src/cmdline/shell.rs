@@ -0,0 +1,135 @@
+//! Placeholder substitution for `:!` shell commands
+
+use std::ffi::OsString;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+
+/// Paths the placeholders in a shell command refer to
+pub struct Substitutions {
+    /// `%f`: the entry under the cursor
+    pub current: Option<PathBuf>,
+    /// `%s`: the selected paths
+    pub selected: Vec<PathBuf>,
+    /// `%d`: the focused directory
+    pub dir: PathBuf,
+    /// `%D`: the other split's directory
+    pub other_dir: Option<PathBuf>,
+}
+
+/// Replace `%f`, `%s`, `%d` and `%D` with shell-quoted paths; `%%` is a
+/// literal `%` and any other `%` sequence is left as typed. Paths go in
+/// byte for byte, so the command may not be UTF-8.
+pub fn substitute(template: &str, subs: &Substitutions) -> Result<OsString, String> {
+    let mut out = Vec::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            continue;
+        }
+        match chars.peek().copied() {
+            Some('%') => out.push(b'%'),
+            Some('f') => {
+                let current = subs
+                    .current
+                    .as_deref()
+                    .ok_or("%f: no entry under the cursor")?;
+                out.extend(quote(current));
+            }
+            Some('s') => {
+                if subs.selected.is_empty() {
+                    return Err("%s: nothing selected".to_string());
+                }
+                let quoted: Vec<Vec<u8>> = subs.selected.iter().map(|p| quote(p)).collect();
+                out.extend(quoted.join(&b' '));
+            }
+            Some('d') => out.extend(quote(&subs.dir)),
+            Some('D') => {
+                let other = subs.other_dir.as_deref().ok_or("%D: no other split")?;
+                out.extend(quote(other));
+            }
+            _ => {
+                out.push(b'%');
+                continue;
+            }
+        }
+        chars.next();
+    }
+    Ok(OsString::from_vec(out))
+}
+
+/// Quote a path for `sh`, leaving plain paths readable
+pub fn quote(path: &Path) -> Vec<u8> {
+    let bytes = path.as_os_str().as_bytes();
+    let plain = !bytes.is_empty()
+        && bytes
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || b"/._-+,:@=".contains(b));
+    if plain {
+        return bytes.to_vec();
+    }
+    let mut quoted = vec![b'\''];
+    for &byte in bytes {
+        match byte {
+            b'\'' => quoted.extend_from_slice(br"'\''"),
+            _ => quoted.push(byte),
+        }
+    }
+    quoted.push(b'\'');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subs() -> Substitutions {
+        Substitutions {
+            current: Some(PathBuf::from("/music/a song.flac")),
+            selected: vec![
+                PathBuf::from("/music/one.flac"),
+                PathBuf::from("/music/it's.flac"),
+            ],
+            dir: PathBuf::from("/music"),
+            other_dir: None,
+        }
+    }
+
+    #[test]
+    fn test_placeholders_are_quoted() {
+        assert_eq!(
+            substitute("ffmpeg -i %f %d/out.mp3", &subs()).unwrap(),
+            "ffmpeg -i '/music/a song.flac' /music/out.mp3"
+        );
+        assert_eq!(
+            substitute("ls %s", &subs()).unwrap(),
+            r"ls /music/one.flac '/music/it'\''s.flac'"
+        );
+    }
+
+    #[test]
+    fn test_literal_percent_and_missing_paths() {
+        assert_eq!(
+            substitute("date +%%Y %x 100%", &subs()).unwrap(),
+            "date +%Y %x 100%"
+        );
+        assert!(substitute("cp %f %D", &subs()).is_err());
+        let empty = Substitutions {
+            selected: Vec::new(),
+            ..subs()
+        };
+        assert!(substitute("rm %s", &empty).is_err());
+    }
+
+    #[test]
+    fn test_paths_that_are_not_utf8() {
+        let subs = Substitutions {
+            current: Some(PathBuf::from(OsString::from_vec(b"/caf\xe9 x".to_vec()))),
+            ..subs()
+        };
+        assert_eq!(
+            substitute("cat %f", &subs).unwrap().into_vec(),
+            b"cat '/caf\xe9 x'"
+        );
+    }
+}
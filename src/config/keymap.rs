@@ -0,0 +1,780 @@
+//! Key binding tables
+//!
+//! Bindings map key sequences to named actions. The built-in tables below
+//! are the defaults; `[keys.normal]`, `[keys.visual]` and `[keys.standard]`
+//! in config.toml override them per sequence. `<leader>` in a sequence is
+//! the key set by `leader` under `[keys]`, `\` unless changed.
+
+use std::collections::HashMap;
+
+use crate::input::{Action, NAMED_KEYS, Operator};
+
+use super::saved::SavedSettings;
+
+/// Stands for the leader key in a parsed sequence until the keymap puts
+/// the configured key in its place
+const LEADER: &str = "<leader>";
+
+/// The leader key unless `[keys] leader` sets another, as in vim
+const DEFAULT_LEADER: &str = "\\";
+
+/// Built-in vi-mode normal bindings
+const DEFAULT_NORMAL: &[(&str, &str)] = &[
+    // Unimpaired-style toggles, enable and disable
+    ("yoo", "toggle_overlay"),
+    ("yoh", "toggle_hidden"),
+    ("[oo", "enable_overlay"),
+    ("[oh", "enable_hidden"),
+    ("]oo", "disable_overlay"),
+    ("]oh", "disable_hidden"),
+    // Unimpaired-style navigation
+    ("[d", "prev_directory"),
+    ("]d", "next_directory"),
+    ("]q", "next_task"),
+    ("[q", "prev_task"),
+    ("]l", "next_error"),
+    ("[l", "prev_error"),
+    ("gg", "cursor_to_top"),
+    ("gv", "reselect_visual"),
+    ("gf", "reveal_entry"),
+    // Fuzzy finder
+    ("<C-p>", "fuzzy_find"),
+    ("<leader>f", "fuzzy_find"),
+    // Splits
+    ("<C-w>h", "focus_left"),
+    ("<C-w>j", "focus_down"),
+    ("<C-w>k", "focus_up"),
+    ("<C-w>l", "focus_right"),
+    ("<C-w>v", "split_vertical"),
+    ("<C-w>s", "split_horizontal"),
+    ("<C-w>c", "close_split"),
+    ("<C-w>q", "close_split"),
+    // Bookmarks
+    ("m", "set_mark"),
+    ("'", "jump_to_mark"),
+    // Jump list
+    ("<C-o>", "jump_back"),
+    ("<C-i>", "jump_forward"),
+    // Search
+    ("/", "enter_search_mode"),
+    ("n", "search_next"),
+    ("N", "search_prev"),
+    ("<C-l>", "clear_search_highlight"),
+    // Sorting
+    ("s", "cycle_sort"),
+    ("S", "reverse_sort"),
+    // Archives
+    ("e", "extract_archive"),
+    // Fold (inline expansion)
+    ("zo", "fold_open"),
+    ("zc", "fold_close"),
+    ("za", "fold_toggle"),
+    ("zO", "fold_open_recursive"),
+    ("zC", "fold_close_recursive"),
+    ("<F12>", "toggle_feature_list"),
+    // Single key commands
+    ("j", "move_down"),
+    ("k", "move_up"),
+    ("G", "cursor_to_bottom"),
+    ("}", "next_group"),
+    ("{", "prev_group"),
+    ("l", "enter_directory"),
+    ("<CR>", "enter_directory"),
+    ("h", "parent_directory"),
+    ("-", "parent_directory"),
+    ("=", "open_file"),
+    ("v", "enter_visual_mode"),
+    ("<Space>", "toggle_select"),
+    // Operators, followed by a motion or repeated for the current entry
+    ("y", "operator_yank"),
+    ("d", "operator_cut"),
+    ("x", "operator_delete"),
+    ("X", "operator_trash"),
+    ("p", "paste"),
+    ("u", "undo"),
+    ("<C-r>", "redo"),
+    ("a", "rename_entry"),
+    ("A", "rename_entry_append"),
+    (".", "toggle_hidden"),
+    (":", "enter_command_mode"),
+    ("<Esc>", "exit_visual_mode"),
+];
+
+/// Built-in vi-mode visual bindings
+const DEFAULT_VISUAL: &[(&str, &str)] = &[
+    // Motions extend the range
+    ("j", "move_down"),
+    ("k", "move_up"),
+    ("gg", "cursor_to_top"),
+    ("G", "cursor_to_bottom"),
+    ("}", "next_group"),
+    ("{", "prev_group"),
+    ("[d", "prev_directory"),
+    ("]d", "next_directory"),
+    ("n", "search_next"),
+    ("N", "search_prev"),
+    ("o", "visual_swap_anchor"),
+    // Actions apply to the range
+    ("y", "yank"),
+    ("d", "cut"),
+    ("x", "delete"),
+    ("X", "trash"),
+    ("e", "extract_archive"),
+    ("=", "open_file"),
+    ("r", "bulk_rename"),
+    ("L", "create_symlink"),
+    (":", "enter_command_mode"),
+    ("<Esc>", "exit_visual_mode"),
+    ("v", "exit_visual_mode"),
+];
+
+/// Built-in bindings for non-vim (standard) mode
+const DEFAULT_STANDARD: &[(&str, &str)] = &[
+    ("<Up>", "move_up"),
+    ("<Down>", "move_down"),
+    ("<Home>", "cursor_to_top"),
+    ("<End>", "cursor_to_bottom"),
+    ("<PageUp>", "page_up"),
+    ("<PageDown>", "page_down"),
+    ("<CR>", "enter_directory"),
+    ("<Right>", "enter_directory"),
+    ("<Left>", "parent_directory"),
+    ("<BS>", "parent_directory"),
+    ("<A-Left>", "jump_back"),
+    ("<A-Right>", "jump_forward"),
+    ("<Delete>", "trash"),
+    ("<C-c>", "yank"),
+    ("<C-x>", "cut"),
+    ("<C-v>", "paste"),
+    ("<C-z>", "undo"),
+    ("<C-y>", "redo"),
+    ("<C-f>", "enter_search_mode"),
+    ("<F3>", "search_next"),
+    ("<S-F3>", "search_prev"),
+    // Refresh (re-enter current dir)
+    ("<F5>", "parent_directory"),
+    ("<Esc>", "clear_search_highlight"),
+    ("<C-h>", "toggle_hidden"),
+    ("<F2>", "open_file"),
+    ("<Insert>", "toggle_select"),
+    ("<C-a>", "select_all"),
+    ("<C-p>", "fuzzy_find"),
+    ("<F12>", "toggle_feature_list"),
+];
+
+/// What a key sequence is bound to
+#[derive(Clone)]
+enum Binding {
+    Action(Action),
+    /// Consumes the following key as a character argument (marks)
+    WithChar(fn(char) -> Action),
+}
+
+impl Binding {
+    /// Operators resolve immediately but may still prefix longer sequences
+    fn is_operator(&self) -> bool {
+        matches!(self, Binding::Action(Action::Operator(_)))
+    }
+}
+
+/// Key sequence table for one input mode
+#[derive(Clone, Default)]
+pub struct Bindings {
+    map: HashMap<Vec<String>, Binding>,
+}
+
+impl Bindings {
+    fn from_defaults(defaults: &[(&str, &str)]) -> Self {
+        let mut bindings = Self::default();
+        for (seq, name) in defaults {
+            let seq = parse_sequence(seq).expect("invalid default key sequence");
+            let binding = parse_action(name).expect("unknown default action");
+            bindings.bind(seq, binding);
+        }
+        bindings
+    }
+
+    /// Resolve a key given the keys already pending
+    pub fn lookup(&self, pending: &[String], key: &str) -> Action {
+        if let Some(Binding::WithChar(make)) = self.map.get(pending) {
+            let mut chars = key.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphabetic() => make(c),
+                _ => Action::None,
+            };
+        }
+
+        let mut seq = pending.to_vec();
+        seq.push(key.to_string());
+        match self.map.get(&seq) {
+            Some(Binding::Action(action)) => action.clone(),
+            Some(Binding::WithChar(_)) => Action::Pending,
+            None if self.is_prefix(&seq) => Action::Pending,
+            None => Action::None,
+        }
+    }
+
+    fn is_prefix(&self, seq: &[String]) -> bool {
+        self.map
+            .keys()
+            .any(|k| k.len() > seq.len() && k.starts_with(seq))
+    }
+
+    /// Sequences that overlap `seq` (one is a prefix of the other)
+    fn conflicts(&self, seq: &[String], binding: &Binding) -> Vec<Vec<String>> {
+        self.map
+            .iter()
+            .filter(|(k, other)| {
+                k.as_slice() != seq
+                    && ((seq.starts_with(k) && !other.is_operator())
+                        || (k.starts_with(seq) && !binding.is_operator()))
+            })
+            .map(|(k, _)| k.clone())
+            .collect()
+    }
+
+    /// Bind a sequence, replacing any bindings it would shadow
+    fn bind(&mut self, seq: Vec<String>, binding: Binding) {
+        for other in self.conflicts(&seq, &binding) {
+            self.map.remove(&other);
+        }
+        self.map.insert(seq, binding);
+    }
+
+    fn unbind(&mut self, seq: &[String]) {
+        self.map.remove(seq);
+    }
+
+    /// Put `leader` in place of `<leader>` in every sequence, the leader
+    /// sequences shadowing the bindings they overlap
+    fn set_leader(&mut self, leader: &str) {
+        let with_leader: Vec<Vec<String>> = self
+            .map
+            .keys()
+            .filter(|seq| seq.iter().any(|k| k == LEADER))
+            .cloned()
+            .collect();
+        for seq in with_leader {
+            let Some(binding) = self.map.remove(&seq) else {
+                continue;
+            };
+            let seq = seq
+                .into_iter()
+                .map(|k| if k == LEADER { leader.to_string() } else { k })
+                .collect();
+            self.bind(seq, binding);
+        }
+    }
+
+    /// Apply a user `[keys.<mode>]` table on top of these bindings. A user
+    /// binding still replaces the defaults it overlaps, but they are
+    /// reported unless the table unbinds them too.
+    fn apply_user_table(
+        &mut self,
+        section: &str,
+        table: &toml::map::Map<String, toml::Value>,
+        errors: &mut Vec<String>,
+    ) {
+        let mut user = Bindings::default();
+        let unbound: Vec<Vec<String>> = table
+            .iter()
+            .filter(|(_, value)| matches!(value.as_str(), Some("" | "none" | "nop")))
+            .filter_map(|(seq_str, _)| parse_sequence(seq_str))
+            .collect();
+
+        for (seq_str, value) in table {
+            let Some(seq) = parse_sequence(seq_str) else {
+                errors.push(format!("[keys.{section}] invalid key sequence '{seq_str}'"));
+                continue;
+            };
+            let Some(name) = value.as_str() else {
+                errors.push(format!(
+                    "[keys.{section}] '{seq_str}' must map to an action name"
+                ));
+                continue;
+            };
+            if matches!(name, "" | "none" | "nop") {
+                self.unbind(&seq);
+                continue;
+            }
+            let Some(binding) = parse_action(name) else {
+                errors.push(format!(
+                    "[keys.{section}] unknown action '{name}' for '{seq_str}'"
+                ));
+                continue;
+            };
+            if let Some(other) = user.conflicts(&seq, &binding).first() {
+                errors.push(format!(
+                    "[keys.{section}] '{seq_str}' conflicts with '{}'",
+                    format_sequence(other)
+                ));
+                continue;
+            }
+            let mut shadowed: Vec<String> = self
+                .conflicts(&seq, &binding)
+                .iter()
+                .filter(|other| !unbound.contains(other))
+                .map(|other| format!("'{}'", format_sequence(other)))
+                .collect();
+            if !shadowed.is_empty() {
+                shadowed.sort();
+                errors.push(format!(
+                    "[keys.{section}] '{seq_str}' shadows the default {}",
+                    shadowed.join(", ")
+                ));
+            }
+            user.map.insert(seq.clone(), binding.clone());
+            self.bind(seq, binding);
+        }
+    }
+}
+
+/// Key bindings for every mode that uses a binding table
+#[derive(Clone)]
+pub struct Keymap {
+    pub normal: Bindings,
+    pub visual: Bindings,
+    pub standard: Bindings,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self::with_defaults();
+        keymap.set_leader(DEFAULT_LEADER);
+        keymap
+    }
+}
+
+impl Keymap {
+    /// Load the keymap from config file, reporting invalid entries
+    pub fn load() -> Self {
+        let table = SavedSettings::load_existing();
+        let (keymap, errors) = Self::from_table(&table);
+        for error in errors {
+            eprintln!("warning: {error}");
+        }
+        keymap
+    }
+
+    /// The built-in tables, `<leader>` still unresolved
+    fn with_defaults() -> Self {
+        Self {
+            normal: Bindings::from_defaults(DEFAULT_NORMAL),
+            visual: Bindings::from_defaults(DEFAULT_VISUAL),
+            standard: Bindings::from_defaults(DEFAULT_STANDARD),
+        }
+    }
+
+    fn set_leader(&mut self, leader: &str) {
+        for bindings in [&mut self.normal, &mut self.visual, &mut self.standard] {
+            bindings.set_leader(leader);
+        }
+    }
+
+    fn from_table(table: &toml::map::Map<String, toml::Value>) -> (Self, Vec<String>) {
+        let mut keymap = Self::with_defaults();
+        let mut errors = Vec::new();
+        let mut leader = DEFAULT_LEADER.to_string();
+
+        let Some(toml::Value::Table(keys)) = table.get("keys") else {
+            keymap.set_leader(&leader);
+            return (keymap, errors);
+        };
+
+        for (section, value) in keys {
+            if section == "leader" {
+                match value.as_str().and_then(parse_sequence).as_deref() {
+                    Some([key]) if key != LEADER => leader = key.clone(),
+                    _ => errors.push("[keys] leader must be a single key".to_string()),
+                }
+                continue;
+            }
+            let bindings = match section.as_str() {
+                "normal" => &mut keymap.normal,
+                "visual" => &mut keymap.visual,
+                "standard" => &mut keymap.standard,
+                _ => {
+                    errors.push(format!("unknown key table [keys.{section}]"));
+                    continue;
+                }
+            };
+            match value {
+                toml::Value::Table(t) => bindings.apply_user_table(section, t, &mut errors),
+                _ => errors.push(format!("[keys.{section}] must be a table")),
+            }
+        }
+
+        keymap.set_leader(&leader);
+        (keymap, errors)
+    }
+}
+
+/// Parse vim-style key notation (`gg`, `<C-w>v`, `<Esc>`) into key strings
+fn parse_sequence(s: &str) -> Option<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut rest = s;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '<'
+            && let Some(end) = rest.find('>')
+            && end > 1
+        {
+            keys.push(parse_special_key(&rest[1..end])?);
+            rest = &rest[end + 1..];
+        } else {
+            keys.push(c.to_string());
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    if keys.is_empty() { None } else { Some(keys) }
+}
+
+fn parse_special_key(name: &str) -> Option<String> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "cr" | "enter" | "return" => "\n".to_string(),
+        "esc" => "\u{1b}".to_string(),
+        "bs" | "backspace" => "\u{8}".to_string(),
+        "tab" => "\t".to_string(),
+        "space" => " ".to_string(),
+        "lt" => "<".to_string(),
+        "leader" => LEADER.to_string(),
+        lower => {
+            if let Some(c) = lower.strip_prefix("c-")
+                && c.chars().count() == 1
+            {
+                return Some(format!("C-{c}"));
+            }
+            parse_modified_key(name)?
+        }
+    };
+    Some(key)
+}
+
+/// Parse a named key with optional `C-`/`A-`/`S-` modifiers into the form
+/// `input::key_event_string` produces (`A-Left`, `S-F3`, `C-PageDown`)
+fn parse_modified_key(name: &str) -> Option<String> {
+    let (mut ctrl, mut alt, mut shift) = (false, false, false);
+    let mut rest = name;
+    while rest.len() > 2 && rest.as_bytes()[1] == b'-' {
+        match rest.as_bytes()[0].to_ascii_lowercase() {
+            b'c' => ctrl = true,
+            b'a' | b'm' => alt = true,
+            b's' => shift = true,
+            _ => return None,
+        }
+        rest = &rest[2..];
+    }
+
+    let base = if rest.chars().count() == 1 {
+        rest.to_string()
+    } else {
+        let lower = rest.to_ascii_lowercase();
+        match NAMED_KEYS.iter().find(|n| n.to_ascii_lowercase() == lower) {
+            Some(n) => n.to_string(),
+            None if lower == "del" => "Delete".to_string(),
+            None if lower == "ins" => "Insert".to_string(),
+            None => return None,
+        }
+    };
+
+    let mut key = String::new();
+    if ctrl {
+        key.push_str("C-");
+    }
+    if alt {
+        key.push_str("A-");
+    }
+    if shift {
+        key.push_str("S-");
+    }
+    key.push_str(&base);
+    Some(key)
+}
+
+fn format_sequence(seq: &[String]) -> String {
+    seq.iter()
+        .map(|k| match k.as_str() {
+            "\n" => "<CR>".to_string(),
+            "\u{1b}" => "<Esc>".to_string(),
+            "\u{8}" => "<BS>".to_string(),
+            "\t" => "<Tab>".to_string(),
+            " " => "<Space>".to_string(),
+            k if k.chars().count() > 1 => format!("<{k}>"),
+            k => k.to_string(),
+        })
+        .collect()
+}
+
+/// Map an action name from config to its binding
+fn parse_action(name: &str) -> Option<Binding> {
+    let action = match name {
+        "set_mark" => return Some(Binding::WithChar(Action::SetMark)),
+        "jump_to_mark" => return Some(Binding::WithChar(Action::JumpToMark)),
+        "move_down" => Action::MoveCursor(1),
+        "move_up" => Action::MoveCursor(-1),
+        "page_down" => Action::MoveCursor(10),
+        "page_up" => Action::MoveCursor(-10),
+        "cursor_to_top" => Action::CursorToTop,
+        "cursor_to_bottom" => Action::CursorToBottom,
+        "next_directory" => Action::NextDirectory,
+        "prev_directory" => Action::PrevDirectory,
+        "next_group" => Action::NextGroup,
+        "prev_group" => Action::PrevGroup,
+        "operator_yank" => Action::Operator(Operator::Yank),
+        "operator_cut" => Action::Operator(Operator::Cut),
+        "operator_delete" => Action::Operator(Operator::Delete),
+        "operator_trash" => Action::Operator(Operator::Trash),
+        "enter_directory" => Action::EnterDirectory,
+        "parent_directory" => Action::ParentDirectory,
+        "open_file" => Action::OpenFile,
+        "enter_visual_mode" => Action::EnterVisualMode,
+        "exit_visual_mode" => Action::ExitVisualMode,
+        "enter_command_mode" => Action::EnterCommandMode,
+        "yank" => Action::Yank,
+        "cut" => Action::Cut,
+        "paste" => Action::Paste,
+        "delete" => Action::Delete,
+        "trash" => Action::Trash,
+        "toggle_hidden" => Action::ToggleHidden,
+        "enable_hidden" => Action::EnableHidden,
+        "disable_hidden" => Action::DisableHidden,
+        "toggle_overlay" => Action::ToggleOverlay,
+        "enable_overlay" => Action::EnableOverlay,
+        "disable_overlay" => Action::DisableOverlay,
+        "focus_left" => Action::FocusLeft,
+        "focus_right" => Action::FocusRight,
+        "focus_up" => Action::FocusUp,
+        "focus_down" => Action::FocusDown,
+        "split_vertical" => Action::SplitVertical,
+        "split_horizontal" => Action::SplitHorizontal,
+        "close_split" => Action::CloseSplit,
+        "enter_search_mode" => Action::EnterSearchMode,
+        "search_next" => Action::SearchNext,
+        "search_prev" => Action::SearchPrev,
+        "jump_back" => Action::JumpBack,
+        "jump_forward" => Action::JumpForward,
+        "cycle_sort" => Action::CycleSort,
+        "reverse_sort" => Action::ReverseSort,
+        "clear_filter" => Action::ClearFilter,
+        "extract_archive" => Action::ExtractArchive,
+        "create_symlink" => Action::CreateSymlink,
+        "rename_entry" => Action::RenameEntry,
+        "rename_entry_append" => Action::RenameEntryAppend,
+        "bulk_rename" => Action::BulkRename,
+        "undo" => Action::Undo,
+        "redo" => Action::Redo,
+        "reveal_entry" => Action::RevealEntry,
+        "fuzzy_find" => Action::FuzzyFind,
+        "visual_swap_anchor" => Action::VisualSwapAnchor,
+        "reselect_visual" => Action::ReselectVisual,
+        "toggle_select" => Action::ToggleSelect,
+        "select_all" => Action::SelectAll,
+        "invert_selection" => Action::InvertSelection,
+        "clear_search_highlight" => Action::ClearSearchHighlight,
+        "fold_open" => Action::FoldOpen,
+        "fold_close" => Action::FoldClose,
+        "fold_toggle" => Action::FoldToggle,
+        "fold_open_recursive" => Action::FoldOpenRecursive,
+        "fold_close_recursive" => Action::FoldCloseRecursive,
+        "next_task" => Action::NextTask,
+        "prev_task" => Action::PrevTask,
+        "toggle_task_list" => Action::ToggleTaskList,
+        "next_error" => Action::NextError,
+        "prev_error" => Action::PrevError,
+        "toggle_error_list" => Action::ToggleErrorList,
+        "toggle_feature_list" => Action::ToggleFeatureList,
+        _ => return None,
+    };
+    Some(Binding::Action(action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(seq: &[&str]) -> Vec<String> {
+        seq.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn user_keymap(toml_str: &str) -> (Keymap, Vec<String>) {
+        let table: toml::Table = toml_str.parse().unwrap();
+        Keymap::from_table(&table)
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        assert_eq!(parse_sequence("gg"), Some(keys(&["g", "g"])));
+        assert_eq!(parse_sequence("<C-w>v"), Some(keys(&["C-w", "v"])));
+        assert_eq!(parse_sequence("[o"), Some(keys(&["[", "o"])));
+        assert_eq!(parse_sequence("<Esc>"), Some(keys(&["\u{1b}"])));
+        assert_eq!(parse_sequence("<CR>"), Some(keys(&["\n"])));
+        assert_eq!(parse_sequence("<S-F3>"), Some(keys(&["S-F3"])));
+        assert_eq!(parse_sequence("<A-Left>"), Some(keys(&["A-Left"])));
+        assert_eq!(parse_sequence("<c-pagedown>"), Some(keys(&["C-PageDown"])));
+        assert_eq!(parse_sequence("<S-Tab>"), Some(keys(&["S-Tab"])));
+        assert_eq!(parse_sequence("<Bogus>"), None);
+        assert_eq!(parse_sequence("<"), Some(keys(&["<"])));
+        assert_eq!(parse_sequence(""), None);
+        assert_eq!(parse_sequence("<C-ww>"), None);
+        assert_eq!(parse_sequence("<Leader>f"), Some(keys(&[LEADER, "f"])));
+    }
+
+    #[test]
+    fn test_leader_key() {
+        let keymap = Keymap::default();
+        assert!(matches!(
+            keymap.normal.lookup(&keys(&["\\"]), "f"),
+            Action::FuzzyFind
+        ));
+
+        let (keymap, errors) = user_keymap(
+            r#"
+            [keys]
+            leader = "<Space>"
+
+            [keys.normal]
+            "<leader>x" = "toggle_hidden"
+            "#,
+        );
+        assert!(errors.is_empty());
+        assert!(matches!(
+            keymap.normal.lookup(&keys(&[" "]), "f"),
+            Action::FuzzyFind
+        ));
+        assert!(matches!(
+            keymap.normal.lookup(&keys(&[" "]), "x"),
+            Action::ToggleHidden
+        ));
+        assert!(matches!(keymap.normal.lookup(&[], " "), Action::Pending));
+
+        let (_, errors) = user_keymap("[keys]\nleader = \"ab\"");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_default_tables_have_no_conflicts() {
+        for defaults in [DEFAULT_NORMAL, DEFAULT_VISUAL, DEFAULT_STANDARD] {
+            let bindings = Bindings::from_defaults(defaults);
+            assert_eq!(bindings.map.len(), defaults.len());
+        }
+    }
+
+    #[test]
+    fn test_user_binding_overrides_default() {
+        let (keymap, errors) = user_keymap(
+            r#"
+            [keys.normal]
+            J = "move_down"
+            j = "page_down"
+            "<C-w>x" = "close_split"
+            "#,
+        );
+        assert!(errors.is_empty());
+        assert!(matches!(
+            keymap.normal.lookup(&[], "j"),
+            Action::MoveCursor(10)
+        ));
+        assert!(matches!(
+            keymap.normal.lookup(&[], "J"),
+            Action::MoveCursor(1)
+        ));
+        assert!(matches!(
+            keymap.normal.lookup(&keys(&["C-w"]), "x"),
+            Action::CloseSplit
+        ));
+    }
+
+    #[test]
+    fn test_user_binding_shadows_default_prefix() {
+        let (keymap, errors) = user_keymap(
+            r#"
+            [keys.normal]
+            g = "cursor_to_top"
+            "#,
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("'g' shadows the default 'gf', 'gg'"));
+        assert!(matches!(
+            keymap.normal.lookup(&[], "g"),
+            Action::CursorToTop
+        ));
+
+        // Unbinding what it shadows says the overlap is meant
+        let table: String = Bindings::from_defaults(DEFAULT_NORMAL)
+            .map
+            .keys()
+            .filter(|seq| seq.len() > 1 && seq[0] == "g")
+            .map(|seq| format!("\"{}\" = \"none\"\n", format_sequence(seq)))
+            .collect();
+        let (keymap, errors) =
+            user_keymap(&format!("[keys.normal]\ng = \"cursor_to_top\"\n{table}"));
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(matches!(
+            keymap.normal.lookup(&[], "g"),
+            Action::CursorToTop
+        ));
+    }
+
+    #[test]
+    fn test_operator_key_keeps_longer_bindings() {
+        let keymap = Keymap::default();
+        assert!(matches!(
+            keymap.normal.lookup(&[], "y"),
+            Action::Operator(Operator::Yank)
+        ));
+        assert!(matches!(
+            keymap.normal.lookup(&keys(&["y", "o"]), "h"),
+            Action::ToggleHidden
+        ));
+    }
+
+    #[test]
+    fn test_user_unbind() {
+        let (keymap, errors) = user_keymap(
+            r#"
+            [keys.normal]
+            x = "none"
+            "#,
+        );
+        assert!(errors.is_empty());
+        assert!(matches!(keymap.normal.lookup(&[], "x"), Action::None));
+    }
+
+    #[test]
+    fn test_unknown_action_reported() {
+        let (keymap, errors) = user_keymap(
+            r#"
+            [keys.visual]
+            q = "launch_rockets"
+            "#,
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("launch_rockets"));
+        assert!(matches!(keymap.visual.lookup(&[], "q"), Action::None));
+    }
+
+    #[test]
+    fn test_conflicting_user_bindings_reported() {
+        let (_, errors) = user_keymap(
+            r#"
+            [keys.standard]
+            "<C-k>" = "move_up"
+            "<C-k>k" = "cursor_to_top"
+            "#,
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("conflicts"));
+    }
+
+    #[test]
+    fn test_unknown_section_reported() {
+        let (_, errors) = user_keymap(
+            r#"
+            [keys.insert]
+            j = "move_down"
+            "#,
+        );
+        assert_eq!(errors.len(), 1);
+    }
+}
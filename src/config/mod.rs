@@ -3,11 +3,13 @@
 //! Split into submodules for reduced complexity.
 
 mod colors;
+mod keymap;
 mod openers;
 mod overlay;
 mod saved;
 mod theme;
 
+pub use keymap::Keymap;
 pub use openers::Openers;
 pub use overlay::{Dimension, OverlayConfig, OverlayPosition};
 pub use saved::SavedSettings;
@@ -24,6 +26,8 @@ pub enum IconsMode {
 
 use prefer::Config as PreferConfig;
 
+use crate::filesystem::ConflictPolicy;
+
 pub struct Config {
     inner: PreferConfig,
 }
@@ -86,6 +90,33 @@ impl Config {
         self.get_bool("search_narrowing").await.unwrap_or(false)
     }
 
+    /// Jobs allowed to run at once on each device
+    pub async fn max_jobs_per_device(&self) -> usize {
+        self.get_i64("max_jobs_per_device")
+            .await
+            .and_then(|n| usize::try_from(n).ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(2)
+    }
+
+    pub async fn preserve(&self) -> bool {
+        self.get_bool("preserve").await.unwrap_or(true)
+    }
+
+    pub async fn paste_conflict(&self) -> ConflictPolicy {
+        let value = self.get_str("paste_conflict").await;
+        value
+            .as_deref()
+            .and_then(|v| {
+                let policy = ConflictPolicy::parse(v);
+                if policy.is_none() {
+                    eprintln!("warning: unknown paste_conflict '{v}', asking instead");
+                }
+                policy
+            })
+            .unwrap_or_default()
+    }
+
     pub async fn icons(&self) -> IconsMode {
         match self.get_str("icons").await.as_deref() {
             Some("true") | Some("enabled") | Some("on") => IconsMode::Enabled,
@@ -13,6 +13,7 @@ pub struct Theme {
     pub selection_bg: Rgba,
     pub search_highlight_bg: Rgba,
     pub directory: Rgb,
+    pub error: Rgb,
     pub header_bg: Rgba,
     pub status_bg: Rgba,
     pub border: Rgba,
@@ -31,6 +32,7 @@ impl Default for Theme {
             selection_bg: Rgba::new(80, 60, 60, 255),
             search_highlight_bg: Rgba::new(180, 180, 0, 100),
             directory: Rgb::new(138, 79, 255),
+            error: Rgb::new(235, 95, 95),
             header_bg: Rgba::new(40, 40, 50, 255),
             status_bg: Rgba::new(50, 50, 60, 255),
             border: Rgba::new(80, 80, 100, 255),
@@ -80,6 +82,9 @@ impl Theme {
         if let Some((r, g, b)) = get_rgb(config, "directory").await {
             self.directory = Rgb::new(r, g, b);
         }
+        if let Some((r, g, b)) = get_rgb(config, "error").await {
+            self.error = Rgb::new(r, g, b);
+        }
         if let Some((r, g, b, a)) = get_rgba(config, "header_bg").await {
             self.header_bg = Rgba::new(r, g, b, a);
         }
@@ -1,9 +1,6 @@
 //! Event loop helpers
 
-use std::path::Path;
-
 use crate::app::App;
-use crate::jobs;
 
 /// Handle files dropped from external applications
 pub fn handle_drop_events(
@@ -17,11 +14,8 @@ pub fn handle_drop_events(
             continue;
         }
 
-        if let Some(browser) = app.browser() {
-            let dest_dir = browser.path.clone();
-            for file in drop_event.files {
-                submit_copy_job(app, &file, &dest_dir);
-            }
+        if let Some(dest_dir) = app.browser().map(|b| b.path.clone()) {
+            app.paste_paths(drop_event.files, &dest_dir, false);
         }
 
         if let Some(browser) = app.browser_mut() {
@@ -33,25 +27,14 @@ pub fn handle_drop_events(
     needs_redraw
 }
 
-fn submit_copy_job(app: &mut App, file: &Path, dest_dir: &Path) {
-    if let Some(file_name) = file.file_name() {
-        let dest = dest_dir.join(file_name);
-        let kind = jobs::JobKind::Copy {
-            src: file.to_path_buf(),
-            dest,
-        };
-        let job_id = app.job_queue.submit(kind.clone());
-        app.runtime
-            .spawn(jobs::execute_job(job_id, kind, app.job_queue.sender()));
-    }
-}
-
 /// Poll and handle job updates
 pub fn poll_job_updates(app: &mut App) -> bool {
     let had_active_jobs = app.job_queue.has_active_jobs();
     app.job_queue.poll_updates();
+    app.record_completed_jobs();
+    app.start_ready_jobs();
 
-    let mut needs_redraw = false;
+    let mut needs_redraw = app.deliver_found_entries() | app.deliver_grep_hits();
 
     if had_active_jobs && !app.job_queue.has_active_jobs() {
         if let Some(browser) = app.browser_mut() {
@@ -6,13 +6,18 @@ use std::process::Command;
 use super::ArchiveFormat;
 
 /// Extract an archive to the destination directory
-pub fn extract_archive(archive_path: &Path, dest_dir: &Path) -> std::io::Result<()> {
+/// Extract an archive, killing the extractor once `should_stop` returns true
+pub fn extract_archive(
+    archive_path: &Path,
+    dest_dir: &Path,
+    should_stop: &dyn Fn() -> bool,
+) -> std::io::Result<()> {
     let format = ArchiveFormat::detect(archive_path);
     let file = archive_path.to_string_lossy().to_string();
     let dest = dest_dir.to_string_lossy().to_string();
 
     let (cmd, args) = build_extract_args(format, &file, &dest)?;
-    run_extract_command(cmd, &args)
+    run_extract_command(cmd, &args, should_stop)
 }
 
 fn build_extract_args(
@@ -53,13 +58,32 @@ fn build_extract_args(
     Ok(result)
 }
 
-fn run_extract_command(cmd: &str, args: &[String]) -> std::io::Result<()> {
-    let status = Command::new(cmd)
+fn run_extract_command(
+    cmd: &str,
+    args: &[String],
+    should_stop: &dyn Fn() -> bool,
+) -> std::io::Result<()> {
+    let mut child = Command::new(cmd)
         .args(args)
         .stdin(std::process::Stdio::null())
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
-        .status()?;
+        .spawn()?;
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if should_stop() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "extraction stopped",
+            ));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    };
 
     if status.success() {
         Ok(())
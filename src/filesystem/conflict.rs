@@ -0,0 +1,151 @@
+//! Name conflicts when pasting into a directory
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What to do when a pasted entry already exists at its destination
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Ask for each conflict
+    #[default]
+    Prompt,
+    Skip,
+    /// Replace the existing entry, directories included, once the pasted
+    /// one is complete
+    Overwrite,
+    /// Paste under a free `name (1).ext` style name
+    Rename,
+    /// Overwrite only when the source was modified more recently
+    OverwriteIfNewer,
+}
+
+impl ConflictPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "prompt" | "ask" => Some(Self::Prompt),
+            "skip" => Some(Self::Skip),
+            "overwrite" => Some(Self::Overwrite),
+            "rename" => Some(Self::Rename),
+            "newer" | "overwrite-if-newer" | "overwrite_if_newer" => Some(Self::OverwriteIfNewer),
+            _ => None,
+        }
+    }
+}
+
+/// Where a conflicting entry should go, for policies other than `Prompt`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    Write(PathBuf),
+    Skip,
+}
+
+/// Decide a conflict for `src` pasted onto the existing `dest`; `taken`
+/// reports names that are in use, including ones reserved by earlier pastes
+pub fn resolve(
+    policy: ConflictPolicy,
+    src: &Path,
+    dest: &Path,
+    taken: impl Fn(&Path) -> bool,
+) -> Resolution {
+    match policy {
+        ConflictPolicy::Prompt | ConflictPolicy::Skip => Resolution::Skip,
+        ConflictPolicy::Overwrite => Resolution::Write(dest.to_path_buf()),
+        ConflictPolicy::Rename => Resolution::Write(numbered_name(dest, taken)),
+        ConflictPolicy::OverwriteIfNewer => {
+            let modified = |p: &Path| p.metadata().and_then(|m| m.modified()).ok();
+            match (modified(src), modified(dest)) {
+                (Some(s), Some(d)) if s > d => Resolution::Write(dest.to_path_buf()),
+                _ => Resolution::Skip,
+            }
+        }
+    }
+}
+
+/// The first free `name (N).ext` next to `dest`
+pub fn numbered_name(dest: &Path, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    let name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    // Hidden files without another dot have no extension: `.bashrc (1)`
+    let (stem, ext) = match name.rfind('.') {
+        Some(dot) if dot > 0 => name.split_at(dot),
+        _ => (name.as_str(), ""),
+    };
+    (1..)
+        .map(|n| dest.with_file_name(format!("{stem} ({n}){ext}")))
+        .find(|candidate| !taken(candidate))
+        .expect("unbounded range always yields a free name")
+}
+
+/// Refuse to paste a directory into itself or one of its descendants
+pub fn check_not_into_itself(src: &Path, dest_dir: &Path) -> io::Result<()> {
+    if !src.is_dir() {
+        return Ok(());
+    }
+    let src = src.canonicalize()?;
+    let dest_dir = dest_dir.canonicalize()?;
+    if dest_dir.starts_with(&src) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("cannot paste {} into itself", src.display()),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_numbered_name_skips_taken() {
+        let taken = |p: &Path| p == Path::new("/d/a (1).txt");
+        assert_eq!(
+            numbered_name(Path::new("/d/a.txt"), taken),
+            PathBuf::from("/d/a (2).txt")
+        );
+        assert_eq!(
+            numbered_name(Path::new("/d/.bashrc"), |_| false),
+            PathBuf::from("/d/.bashrc (1)")
+        );
+        assert_eq!(
+            numbered_name(Path::new("/d/archive.tar.gz"), |_| false),
+            PathBuf::from("/d/archive.tar (1).gz")
+        );
+    }
+
+    #[test]
+    fn test_resolve_policies() {
+        let dest = Path::new("/d/a.txt");
+        assert_eq!(
+            resolve(ConflictPolicy::Skip, dest, dest, |_| true),
+            Resolution::Skip
+        );
+        assert_eq!(
+            resolve(ConflictPolicy::Overwrite, dest, dest, |_| true),
+            Resolution::Write(dest.to_path_buf())
+        );
+        // Missing timestamps never overwrite
+        assert_eq!(
+            resolve(ConflictPolicy::OverwriteIfNewer, dest, dest, |_| true),
+            Resolution::Skip
+        );
+        assert_eq!(
+            ConflictPolicy::parse("Newer"),
+            Some(ConflictPolicy::OverwriteIfNewer)
+        );
+        assert_eq!(ConflictPolicy::parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_directory_into_itself() {
+        let dir = TempDir::new("conflict");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+
+        assert!(check_not_into_itself(&dir, &dir.join("sub")).is_err());
+        assert!(check_not_into_itself(&dir, &dir).is_err());
+        assert!(check_not_into_itself(&dir.join("sub"), &dir).is_ok());
+    }
+}
@@ -0,0 +1,422 @@
+//! Recursive copying
+//!
+//! Symlinks are copied as symlinks and never followed, so link loops cannot
+//! recurse. FIFOs are recreated; sockets and device nodes are skipped inside
+//! directories since they only mean something to the process or kernel that
+//! made them. Permission bits are always kept. With `preserve`, access and
+//! modification times, extended attributes and hard links between the copied
+//! files are kept as well.
+
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs::{self, Metadata};
+use std::io::{self, Read, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, symlink};
+use std::path::{Path, PathBuf};
+
+/// Size of the chunks file contents are copied in, and so how often
+/// progress is reported within a file
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Running totals of a copy
+#[derive(Clone, Debug, Default)]
+pub struct CopyProgress {
+    pub bytes: u64,
+    /// Regular files finished
+    pub files: u64,
+    /// Source file being copied
+    pub current: PathBuf,
+}
+
+/// Copy a file, directory, symlink or FIFO to `dest`
+pub fn copy_file(src: &Path, dest: &Path, preserve: bool) -> io::Result<()> {
+    copy_file_with_progress(src, dest, preserve, &mut |_| Ok(()))
+}
+
+/// Copy like `copy_file`, calling `on_progress` after every chunk and file.
+/// An error from `on_progress` stops the copy and removes what it created,
+/// along with the partly written file.
+pub fn copy_file_with_progress(
+    src: &Path,
+    dest: &Path,
+    preserve: bool,
+    on_progress: &mut dyn FnMut(&CopyProgress) -> io::Result<()>,
+) -> io::Result<()> {
+    let meta = fs::symlink_metadata(src)?;
+    if is_socket_or_device(&meta) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("cannot copy socket or device {}", src.display()),
+        ));
+    }
+    let mut copier = Copier {
+        preserve,
+        links: HashMap::new(),
+        progress: CopyProgress::default(),
+        on_progress,
+        stopped: false,
+        created: Vec::new(),
+        partial: None,
+    };
+    let result = copier.copy(src, dest, &meta);
+    if copier.stopped {
+        copier.remove_partial();
+    }
+    result
+}
+
+/// Total bytes and regular files a copy of `src` will write, counting what
+/// `copy_file` would copy: symlinks are not followed
+pub fn scan_tree(src: &Path) -> io::Result<(u64, u64)> {
+    let meta = fs::symlink_metadata(src)?;
+    if meta.is_file() {
+        return Ok((meta.len(), 1));
+    }
+    if !meta.is_dir() {
+        return Ok((0, 0));
+    }
+    let mut totals = (0, 0);
+    for entry in fs::read_dir(src)? {
+        let (bytes, files) = scan_tree(&entry?.path())?;
+        totals.0 += bytes;
+        totals.1 += files;
+    }
+    Ok(totals)
+}
+
+struct Copier<'a> {
+    preserve: bool,
+    /// First copy of each multiply-linked file, by source device and inode
+    links: HashMap<(u64, u64), PathBuf>,
+    progress: CopyProgress,
+    on_progress: &'a mut dyn FnMut(&CopyProgress) -> io::Result<()>,
+    /// Set when `on_progress` stopped the copy
+    stopped: bool,
+    /// Entries that did not exist before the copy, in creation order
+    created: Vec<PathBuf>,
+    /// File being written, which may have existed before
+    partial: Option<PathBuf>,
+}
+
+impl Copier<'_> {
+    fn copy(&mut self, src: &Path, dest: &Path, meta: &Metadata) -> io::Result<()> {
+        if dest.symlink_metadata().is_err() {
+            self.created.push(dest.to_path_buf());
+        }
+        let file_type = meta.file_type();
+        if file_type.is_symlink() {
+            symlink(fs::read_link(src)?, dest)?;
+        } else if file_type.is_dir() {
+            self.copy_directory(src, dest)?;
+        } else if file_type.is_fifo() {
+            make_fifo(dest, meta.mode())?;
+        } else {
+            self.progress.current = src.to_path_buf();
+            if self.link_to_earlier_copy(dest, meta)? {
+                self.progress.bytes += meta.len();
+            } else {
+                self.copy_contents(src, dest)?;
+                apply_metadata(src, dest, meta, self.preserve)?;
+            }
+            self.progress.files += 1;
+            return self.report();
+        }
+        apply_metadata(src, dest, meta, self.preserve)
+    }
+
+    fn report(&mut self) -> io::Result<()> {
+        let result = (self.on_progress)(&self.progress);
+        self.stopped = result.is_err();
+        result
+    }
+
+    /// Remove what a stopped copy created, newest first so directories are
+    /// empty by the time they are reached
+    fn remove_partial(&mut self) {
+        if let Some(partial) = self.partial.take() {
+            let _ = fs::remove_file(partial);
+        }
+        for path in self.created.drain(..).rev() {
+            let is_dir = path.symlink_metadata().is_ok_and(|m| m.is_dir());
+            let _ = if is_dir {
+                fs::remove_dir(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+        }
+    }
+
+    fn copy_contents(&mut self, src: &Path, dest: &Path) -> io::Result<()> {
+        let mut reader = fs::File::open(src)?;
+        self.partial = Some(dest.to_path_buf());
+        let mut writer = fs::File::create(dest)?;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        loop {
+            let n = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            writer.write_all(&buffer[..n])?;
+            self.progress.bytes += n as u64;
+            self.report()?;
+        }
+        writer.flush()?;
+        self.partial = None;
+        Ok(())
+    }
+
+    fn copy_directory(&mut self, src: &Path, dest: &Path) -> io::Result<()> {
+        fs::create_dir_all(dest)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            let src_path = entry.path();
+            let meta = fs::symlink_metadata(&src_path)?;
+            if is_socket_or_device(&meta) {
+                eprintln!("warning: not copying special file {}", src_path.display());
+                continue;
+            }
+            self.copy(&src_path, &dest.join(entry.file_name()), &meta)?;
+        }
+        Ok(())
+    }
+
+    /// Hard link `dest` to the copy of a file already copied under another
+    /// name, returning whether it did
+    fn link_to_earlier_copy(&mut self, dest: &Path, meta: &Metadata) -> io::Result<bool> {
+        if !self.preserve || meta.nlink() < 2 {
+            return Ok(false);
+        }
+        let key = (meta.dev(), meta.ino());
+        if let Some(first) = self.links.get(&key) {
+            fs::hard_link(first, dest)?;
+            return Ok(true);
+        }
+        self.links.insert(key, dest.to_path_buf());
+        Ok(false)
+    }
+}
+
+fn is_socket_or_device(meta: &Metadata) -> bool {
+    let file_type = meta.file_type();
+    file_type.is_socket() || file_type.is_block_device() || file_type.is_char_device()
+}
+
+fn apply_metadata(src: &Path, dest: &Path, meta: &Metadata, preserve: bool) -> io::Result<()> {
+    // Symlink permissions are meaningless and chmod would follow the link
+    if meta.file_type().is_symlink() {
+        return if preserve {
+            set_times(dest, meta)
+        } else {
+            Ok(())
+        };
+    }
+    if preserve {
+        copy_xattrs(src, dest);
+    }
+    fs::set_permissions(dest, meta.permissions())?;
+    if preserve {
+        set_times(dest, meta)?;
+    }
+    Ok(())
+}
+
+fn c_path(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains a NUL byte"))
+}
+
+fn make_fifo(path: &Path, mode: u32) -> io::Result<()> {
+    let path = c_path(path)?;
+    // SAFETY: `path` is a valid NUL-terminated string
+    if unsafe { libc::mkfifo(path.as_ptr(), (mode & 0o7777) as libc::mode_t) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Set access and modification times without following symlinks
+fn set_times(path: &Path, meta: &Metadata) -> io::Result<()> {
+    let path = c_path(path)?;
+    let times = [
+        libc::timespec {
+            tv_sec: meta.atime() as libc::time_t,
+            tv_nsec: meta.atime_nsec() as _,
+        },
+        libc::timespec {
+            tv_sec: meta.mtime() as libc::time_t,
+            tv_nsec: meta.mtime_nsec() as _,
+        },
+    ];
+    // SAFETY: `path` is NUL-terminated and `times` holds the two entries
+    // utimensat reads
+    let result = unsafe {
+        libc::utimensat(
+            libc::AT_FDCWD,
+            path.as_ptr(),
+            times.as_ptr(),
+            libc::AT_SYMLINK_NOFOLLOW,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Copy extended attributes; ones the destination refuses, such as
+/// `security.*` without privileges, are left out
+#[cfg(target_os = "linux")]
+fn copy_xattrs(src: &Path, dest: &Path) {
+    let (Ok(src), Ok(dest)) = (c_path(src), c_path(dest)) else {
+        return;
+    };
+    let Some(names) = read_xattr_buffer(|buf, len| {
+        // SAFETY: `buf` points to `len` writable bytes, or is null with 0
+        unsafe { libc::llistxattr(src.as_ptr(), buf.cast(), len) }
+    }) else {
+        return;
+    };
+    for name in names.split(|&b| b == 0).filter(|n| !n.is_empty()) {
+        let Ok(name) = CString::new(name) else {
+            continue;
+        };
+        let Some(value) = read_xattr_buffer(|buf, len| {
+            // SAFETY: as above, and `name` is NUL-terminated
+            unsafe { libc::lgetxattr(src.as_ptr(), name.as_ptr(), buf.cast(), len) }
+        }) else {
+            continue;
+        };
+        // SAFETY: all pointers are valid for the lengths given
+        unsafe {
+            libc::lsetxattr(
+                dest.as_ptr(),
+                name.as_ptr(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+            );
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn copy_xattrs(_src: &Path, _dest: &Path) {}
+
+/// Call an xattr function once for the size and again to fill a buffer
+#[cfg(target_os = "linux")]
+fn read_xattr_buffer(read: impl Fn(*mut u8, usize) -> libc::ssize_t) -> Option<Vec<u8>> {
+    let len = usize::try_from(read(std::ptr::null_mut(), 0)).ok()?;
+    let mut buf = vec![0u8; len];
+    let read_len = usize::try_from(read(buf.as_mut_ptr(), len)).ok()?;
+    buf.truncate(read_len);
+    Some(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::time::{Duration, SystemTime};
+
+    fn temp_dir(name: &str) -> TempDir {
+        TempDir::new(&format!("copy-{name}"))
+    }
+
+    #[test]
+    fn test_symlinks_are_copied_not_followed() {
+        let dir = temp_dir("symlink");
+        let src = dir.join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("file"), "data").unwrap();
+        // A loop back to the directory itself
+        symlink("..", src.join("loop")).unwrap();
+
+        copy_file(&src, &dir.join("dest"), false).unwrap();
+
+        let link = dir.join("dest/loop");
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_link(&link).unwrap(), PathBuf::from(".."));
+        assert_eq!(fs::read_to_string(dir.join("dest/file")).unwrap(), "data");
+    }
+
+    #[test]
+    fn test_preserve_keeps_times_and_hard_links() {
+        let dir = temp_dir("preserve");
+        let src = dir.join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("a"), "data").unwrap();
+        fs::hard_link(src.join("a"), src.join("b")).unwrap();
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        fs::File::options()
+            .write(true)
+            .open(src.join("a"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        copy_file(&src, &dir.join("kept"), true).unwrap();
+        copy_file(&src, &dir.join("plain"), false).unwrap();
+
+        let kept_a = fs::metadata(dir.join("kept/a")).unwrap();
+        let kept_b = fs::metadata(dir.join("kept/b")).unwrap();
+        assert_eq!(kept_a.modified().unwrap(), old);
+        assert_eq!(kept_a.ino(), kept_b.ino());
+
+        assert_eq!(scan_tree(&src).unwrap(), (8, 2));
+        let mut last = CopyProgress::default();
+        copy_file_with_progress(&src, &dir.join("counted"), true, &mut |p| {
+            last = p.clone();
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!((last.bytes, last.files), (8, 2));
+
+        let plain_a = fs::metadata(dir.join("plain/a")).unwrap();
+        let plain_b = fs::metadata(dir.join("plain/b")).unwrap();
+        assert_ne!(plain_a.modified().unwrap(), old);
+        assert_ne!(plain_a.ino(), plain_b.ino());
+    }
+
+    #[test]
+    fn test_fifo_is_recreated_and_modes_kept() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("fifo");
+        let src = dir.join("src");
+        fs::create_dir(&src).unwrap();
+        make_fifo(&src.join("pipe"), 0o600).unwrap();
+        fs::set_permissions(&src, fs::Permissions::from_mode(0o750)).unwrap();
+
+        copy_file(&src, &dir.join("dest"), false).unwrap();
+
+        let pipe = fs::symlink_metadata(dir.join("dest/pipe")).unwrap();
+        assert!(pipe.file_type().is_fifo());
+        let mode = fs::metadata(dir.join("dest")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+    }
+
+    #[test]
+    fn test_stopped_copy_removes_what_it_created() {
+        let dir = temp_dir("stop");
+        let src = dir.join("src");
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::write(src.join("sub/a"), "data").unwrap();
+        fs::write(src.join("sub/b"), "data").unwrap();
+
+        let result = copy_file_with_progress(&src, &dir.join("dest"), false, &mut |p| {
+            if p.files == 1 {
+                Err(io::Error::other("cancelled"))
+            } else {
+                Ok(())
+            }
+        });
+
+        assert!(result.is_err());
+        assert!(!dir.join("dest").exists());
+    }
+}
@@ -0,0 +1,180 @@
+//! Path expansion for typed paths
+//!
+//! Expands `~`, environment variables and globs the way a shell would, then
+//! resolves the result against a base directory.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Expand a typed path and resolve it against `base`
+///
+/// Supports `~` and `~/...`, `$VAR` and `${VAR}`, and glob patterns that
+/// must match exactly one path. `.` and `..` are resolved lexically so a
+/// path through a symlinked directory goes back the way it came.
+pub fn expand_path(input: &str, base: &Path) -> Result<PathBuf, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("no path given".to_string());
+    }
+
+    let expanded = expand_vars(&expand_tilde(input)?)?;
+    let path = normalize(&base.join(&expanded));
+
+    if !has_glob(&expanded) {
+        return Ok(path);
+    }
+    // Only the typed part is a pattern; the base may contain `*` or `[`
+    let pattern = if Path::new(&expanded).is_absolute() {
+        expanded.clone()
+    } else {
+        let base = glob::Pattern::escape(&normalize(base).to_string_lossy());
+        format!("{}/{}", base.trim_end_matches('/'), expanded)
+    };
+    let mut matches = glob::glob(&pattern)
+        .map_err(|e| format!("bad pattern {input}: {}", e.msg))?
+        .flatten();
+    match (matches.next(), matches.next()) {
+        (Some(found), None) => Ok(normalize(&found)),
+        (None, _) => Err(format!("no match for {input}")),
+        (Some(_), Some(_)) => Err(format!("{input} matches more than one path")),
+    }
+}
+
+/// Resolve a name for a new entry against `base`
+///
+/// Only `~` is expanded; `$` and glob characters are legal in file names and
+/// are kept as typed.
+pub fn expand_name(input: &str, base: &Path) -> Result<PathBuf, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("no name given".to_string());
+    }
+    Ok(normalize(&base.join(expand_tilde(input)?)))
+}
+
+fn expand_tilde(input: &str) -> Result<String, String> {
+    let rest = match input.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+        _ => return Ok(input.to_string()),
+    };
+    let home = dirs::home_dir().ok_or("home directory not found")?;
+    Ok(format!("{}{rest}", home.display()))
+}
+
+fn expand_vars(input: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+        let name: String = if chars.next_if_eq(&'{').is_some() {
+            let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+            if name.is_empty() {
+                return Err("empty variable name in ${}".to_string());
+            }
+            name
+        } else {
+            // Names start with a letter or underscore, so `$5` stays literal
+            let mut name = String::new();
+            while let Some(c) = chars.next_if(|c| {
+                c.is_ascii_alphabetic() || *c == '_' || (!name.is_empty() && c.is_ascii_digit())
+            }) {
+                name.push(c);
+            }
+            name
+        };
+        if name.is_empty() {
+            out.push('$');
+            continue;
+        }
+        let value = std::env::var(&name).map_err(|_| format!("${name} is not set"))?;
+        out.push_str(&value);
+    }
+    Ok(out)
+}
+
+fn has_glob(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_relative_and_parent_components() {
+        let base = Path::new("/usr/share");
+        assert_eq!(
+            expand_path("doc", base),
+            Ok(PathBuf::from("/usr/share/doc"))
+        );
+        assert_eq!(
+            expand_path("../lib/./x", base),
+            Ok(PathBuf::from("/usr/lib/x"))
+        );
+        assert_eq!(expand_path("/etc", base), Ok(PathBuf::from("/etc")));
+        assert!(expand_path("  ", base).is_err());
+    }
+
+    #[test]
+    fn test_tilde_and_variables() {
+        let home = dirs::home_dir().unwrap();
+        let base = Path::new("/");
+        assert_eq!(expand_path("~", base), Ok(home.clone()));
+        assert_eq!(expand_path("~/a", base), Ok(home.join("a")));
+        assert_eq!(expand_path("~user", base), Ok(PathBuf::from("/~user")));
+
+        let path = std::env::var("PATH").unwrap();
+        assert_eq!(
+            expand_vars("${PATH}/x and $PATH.").unwrap(),
+            format!("{path}/x and {path}.")
+        );
+        assert_eq!(expand_vars("cost $5 $").unwrap(), "cost $5 $");
+        assert!(expand_vars("$MKFM_SURELY_UNSET_VARIABLE").is_err());
+    }
+
+    #[test]
+    fn test_names_keep_special_characters() {
+        let base = Path::new("/tmp");
+        assert_eq!(
+            expand_name("$cost [draft]*.txt", base),
+            Ok(PathBuf::from("/tmp/$cost [draft]*.txt"))
+        );
+        assert_eq!(expand_name("a/../b", base), Ok(PathBuf::from("/tmp/b")));
+        assert!(expand_name("", base).is_err());
+    }
+
+    #[test]
+    fn test_glob_needs_unique_match() {
+        // Brackets in the directory must not be taken for a glob
+        let dir = TempDir::new("expand-[x]");
+        std::fs::create_dir_all(dir.join("alpha")).unwrap();
+        std::fs::create_dir_all(dir.join("beta")).unwrap();
+        std::fs::create_dir_all(dir.join("bravo")).unwrap();
+
+        assert_eq!(expand_path("al*", &dir), Ok(dir.join("alpha")));
+        assert!(
+            expand_path("b*", &dir)
+                .unwrap_err()
+                .contains("more than one")
+        );
+        assert!(expand_path("z*", &dir).unwrap_err().contains("no match"));
+    }
+}
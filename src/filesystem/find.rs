@@ -0,0 +1,415 @@
+//! Recursive search below a directory
+//!
+//! `:find` takes find-style options followed by an optional name pattern:
+//! a glob when it has wildcards, otherwise a case-insensitive substring,
+//! or a regex given with `-regex`. `-maxdepth N`, `-type f|d|l`,
+//! `-size [+-]N[kMG]` and `-mtime [+-]DAYS` narrow the results as in
+//! find(1), and `-gitignore` skips what git would ignore.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+use walkdir::{DirEntry, WalkDir};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Clone, Debug)]
+enum NameMatcher {
+    Glob(Pattern),
+    Regex(Regex),
+    /// Lowercased substring
+    Contains(String),
+}
+
+impl NameMatcher {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NameMatcher::Glob(pattern) => pattern.matches(name),
+            NameMatcher::Regex(regex) => regex.is_match(name),
+            NameMatcher::Contains(text) => name.to_lowercase().contains(text),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EntryType {
+    File,
+    Dir,
+    Symlink,
+}
+
+/// `+N`, `-N` or `N` of a find(1) numeric test
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    Above(u64),
+    Below(u64),
+    Exactly(u64),
+}
+
+impl Bound {
+    fn parse(text: &str) -> Option<(Self, &str)> {
+        let (make, rest): (fn(u64) -> Bound, &str) = match text.as_bytes().first()? {
+            b'+' => (Bound::Above, &text[1..]),
+            b'-' => (Bound::Below, &text[1..]),
+            _ => (Bound::Exactly, text),
+        };
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let number = rest[..digits].parse().ok()?;
+        Some((make(number), &rest[digits..]))
+    }
+
+    fn holds(self, value: u64) -> bool {
+        match self {
+            Bound::Above(n) => value > n,
+            Bound::Below(n) => value < n,
+            Bound::Exactly(n) => value == n,
+        }
+    }
+}
+
+/// What `:find` looks for
+#[derive(Clone, Debug)]
+pub struct FindQuery {
+    /// The arguments as typed, for the job list and pane header
+    pub text: String,
+    name: Option<NameMatcher>,
+    max_depth: Option<usize>,
+    entry_type: Option<EntryType>,
+    /// Size in units of `size_unit` bytes, rounded up
+    size: Option<Bound>,
+    size_unit: u64,
+    /// Age in whole days
+    mtime: Option<Bound>,
+    gitignore: bool,
+}
+
+impl FindQuery {
+    pub fn parse(args: &str) -> Result<Self, String> {
+        let mut query = Self {
+            text: args.trim().to_string(),
+            name: None,
+            max_depth: None,
+            entry_type: None,
+            size: None,
+            size_unit: 1,
+            mtime: None,
+            gitignore: false,
+        };
+        let mut words = args.split_whitespace();
+        while let Some(word) = words.next() {
+            let mut value = |option: &str| {
+                words
+                    .next()
+                    .ok_or_else(|| format!("{option} needs a value"))
+            };
+            match word {
+                "-maxdepth" => {
+                    let depth = value(word)?;
+                    query.max_depth =
+                        Some(depth.parse().map_err(|_| format!("bad depth {depth}"))?);
+                }
+                "-type" => {
+                    query.entry_type = Some(match value(word)? {
+                        "f" => EntryType::File,
+                        "d" => EntryType::Dir,
+                        "l" => EntryType::Symlink,
+                        other => return Err(format!("bad type {other}, expected f, d or l")),
+                    });
+                }
+                "-size" => {
+                    let size = value(word)?;
+                    let (bound, unit) =
+                        Bound::parse(size).ok_or_else(|| format!("bad size {size}"))?;
+                    query.size_unit = match unit {
+                        "" | "c" => 1,
+                        "k" => 1 << 10,
+                        "M" => 1 << 20,
+                        "G" => 1 << 30,
+                        _ => return Err(format!("bad size unit in {size}")),
+                    };
+                    query.size = Some(bound);
+                }
+                "-mtime" => {
+                    let days = value(word)?;
+                    query.mtime = match Bound::parse(days) {
+                        Some((bound, "")) => Some(bound),
+                        _ => return Err(format!("bad day count {days}")),
+                    };
+                }
+                "-regex" => {
+                    let regex = Regex::new(value(word)?).map_err(|e| e.to_string())?;
+                    query.name = Some(NameMatcher::Regex(regex));
+                }
+                "-gitignore" => query.gitignore = true,
+                option if option.starts_with('-') => {
+                    return Err(format!("unknown option {option}"));
+                }
+                pattern if query.name.is_none() => {
+                    query.name = Some(if pattern.contains(['*', '?', '[']) {
+                        NameMatcher::Glob(Pattern::new(pattern).map_err(|e| e.to_string())?)
+                    } else {
+                        NameMatcher::Contains(pattern.to_lowercase())
+                    });
+                }
+                extra => return Err(format!("unexpected {extra}")),
+            }
+        }
+        Ok(query)
+    }
+
+    fn matches(&self, entry: &DirEntry, now: SystemTime) -> bool {
+        let name = entry.file_name().to_string_lossy();
+        if self.name.as_ref().is_some_and(|m| !m.matches(&name)) {
+            return false;
+        }
+        let file_type = entry.file_type();
+        let entry_type = if file_type.is_symlink() {
+            EntryType::Symlink
+        } else if file_type.is_dir() {
+            EntryType::Dir
+        } else {
+            EntryType::File
+        };
+        if self.entry_type.is_some_and(|t| t != entry_type) {
+            return false;
+        }
+        if self.size.is_none() && self.mtime.is_none() {
+            return true;
+        }
+        let Ok(meta) = entry.metadata() else {
+            return false;
+        };
+        if let Some(bound) = self.size
+            && !bound.holds(meta.len().div_ceil(self.size_unit))
+        {
+            return false;
+        }
+        if let Some(bound) = self.mtime {
+            let age = meta
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .unwrap_or_default();
+            if !bound.holds(age.as_secs() / DAY.as_secs()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Walk below `root` in name order, passing each entry matching `query` to
+/// `found`; an error from `found` or `should_stop` stops the walk.
+/// Unreadable directories are skipped.
+pub fn find(
+    root: &Path,
+    query: &FindQuery,
+    should_stop: &dyn Fn() -> bool,
+    found: &mut dyn FnMut(PathBuf) -> io::Result<()>,
+) -> io::Result<()> {
+    let now = SystemTime::now();
+    let mut ignores = query.gitignore.then(|| Gitignore::new(root));
+    let walker = WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(query.max_depth.unwrap_or(usize::MAX))
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            ignores
+                .as_mut()
+                .is_none_or(|ignores| !ignores.is_ignored(entry.path(), entry.file_type().is_dir()))
+        });
+    for entry in walker.filter_map(|e| e.ok()) {
+        if should_stop() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "search stopped"));
+        }
+        if query.matches(&entry, now) {
+            found(entry.into_path())?;
+        }
+    }
+    Ok(())
+}
+
+/// One line of a `.gitignore`
+#[derive(Debug)]
+struct IgnoreRule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+    /// Matched against the path from the `.gitignore`'s directory rather
+    /// than the name alone
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        Some(Self {
+            pattern: Pattern::new(line).ok()?,
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, relative: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::default()
+        };
+        if self.anchored {
+            self.pattern.matches_path_with(relative, options)
+        } else {
+            relative
+                .file_name()
+                .is_some_and(|name| self.pattern.matches_with(&name.to_string_lossy(), options))
+        }
+    }
+}
+
+/// `.gitignore` rules of the directories walked, read as they are reached
+struct Gitignore {
+    /// Outermost directory whose `.gitignore` applies: the repository's
+    /// top when the walk starts inside one
+    top: PathBuf,
+    rules: HashMap<PathBuf, Vec<IgnoreRule>>,
+}
+
+impl Gitignore {
+    fn new(root: &Path) -> Self {
+        let top = root
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(root)
+            .to_path_buf();
+        Self {
+            top,
+            rules: HashMap::new(),
+        }
+    }
+
+    fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        if path.file_name().is_some_and(|name| name == ".git") {
+            return true;
+        }
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+        // Deeper files override shallower ones and later lines earlier ones
+        let mut dirs: Vec<&Path> = parent
+            .ancestors()
+            .take_while(|dir| dir.starts_with(&self.top))
+            .collect();
+        dirs.reverse();
+        let mut ignored = false;
+        for dir in dirs {
+            let rules = self.rules.entry(dir.to_path_buf()).or_insert_with(|| {
+                fs::read_to_string(dir.join(".gitignore"))
+                    .map(|text| text.lines().filter_map(IgnoreRule::parse).collect())
+                    .unwrap_or_default()
+            });
+            let relative = path.strip_prefix(dir).unwrap_or(path);
+            for rule in rules.iter() {
+                if rule.matches(relative, is_dir) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn temp_tree(name: &str) -> TempDir {
+        let dir = TempDir::new(&format!("find-{name}"));
+        for sub in ["src/app", "target/debug", ".git"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        for (file, content) in [
+            ("src/main.rs", "fn main() {}"),
+            ("src/app/mod.rs", ""),
+            ("target/debug/build.rs", ""),
+            ("notes.txt", "a much longer file than the others"),
+            (".gitignore", "/target/\n*.txt\n"),
+        ] {
+            fs::write(dir.join(file), content).unwrap();
+        }
+        dir
+    }
+
+    fn names(root: &Path, args: &str) -> Vec<String> {
+        let query = FindQuery::parse(args).unwrap();
+        let mut found = Vec::new();
+        find(root, &query, &|| false, &mut |path| {
+            let relative = path.strip_prefix(root).unwrap();
+            found.push(relative.to_string_lossy().into_owned());
+            Ok(())
+        })
+        .unwrap();
+        found
+    }
+
+    #[test]
+    fn test_patterns_and_predicates() {
+        let root = temp_tree("predicates");
+        assert_eq!(
+            names(&root, "*.rs"),
+            ["src/app/mod.rs", "src/main.rs", "target/debug/build.rs"]
+        );
+        assert_eq!(names(&root, "-maxdepth 2 -type f *.rs"), ["src/main.rs"]);
+        assert_eq!(
+            names(&root, "-regex ^m.*s$"),
+            ["src/app/mod.rs", "src/main.rs"]
+        );
+        assert_eq!(names(&root, "-type d APP"), ["src/app"]);
+        assert_eq!(names(&root, "-size +20c -type f"), ["notes.txt"]);
+        assert_eq!(names(&root, "-mtime +1 -type f"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_gitignore_is_honoured() {
+        let root = temp_tree("gitignore");
+        assert_eq!(
+            names(&root, "-gitignore -type f"),
+            [".gitignore", "src/app/mod.rs", "src/main.rs"]
+        );
+        fs::write(root.join("src/.gitignore"), "main.rs\n").unwrap();
+        assert_eq!(names(&root, "-gitignore *.rs"), ["src/app/mod.rs"]);
+    }
+
+    #[test]
+    fn test_bad_queries_are_rejected() {
+        assert!(FindQuery::parse("-type x").is_err());
+        assert!(FindQuery::parse("-maxdepth").is_err());
+        assert!(FindQuery::parse("-size 3X").is_err());
+        assert!(FindQuery::parse("-regex (").is_err());
+        assert!(FindQuery::parse("a b").is_err());
+    }
+}
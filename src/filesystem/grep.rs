@@ -0,0 +1,173 @@
+//! Searching file contents
+//!
+//! `:grep` looks for a regex in every text file below a directory, line by
+//! line. A file counts as binary, and is skipped, when its first block
+//! holds a NUL byte, as git and grep(1) decide it. The search stops at
+//! `HIT_LIMIT` matching lines so a broad pattern cannot fill memory.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use walkdir::WalkDir;
+
+/// How much of a file is looked at to tell whether it is binary
+const BINARY_CHECK_LEN: usize = 8 * 1024;
+
+/// Matching lines after which a search stops looking
+pub const HIT_LIMIT: usize = 10_000;
+
+/// A line matching a search
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrepHit {
+    pub path: PathBuf,
+    /// Line number, from 1
+    pub line: usize,
+    pub text: String,
+}
+
+/// Search the files below `root`, or `root` itself when it is a file, in
+/// name order, passing each matching line to `found` until `HIT_LIMIT` of
+/// them have been; an error from `found` or `should_stop` stops the search.
+/// Unreadable files and directories, binary files and `.git` directories
+/// are skipped.
+pub fn grep(
+    root: &Path,
+    regex: &Regex,
+    should_stop: &dyn Fn() -> bool,
+    found: &mut dyn FnMut(GrepHit) -> io::Result<()>,
+) -> io::Result<()> {
+    grep_limited(root, regex, HIT_LIMIT, should_stop, found)
+}
+
+fn grep_limited(
+    root: &Path,
+    regex: &Regex,
+    limit: usize,
+    should_stop: &dyn Fn() -> bool,
+    found: &mut dyn FnMut(GrepHit) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut left = limit;
+    let walker = WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || entry.file_name() != ".git");
+    for entry in walker.filter_map(|e| e.ok()) {
+        if left == 0 {
+            break;
+        }
+        if should_stop() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "search stopped"));
+        }
+        if entry.file_type().is_file() {
+            grep_file(entry.path(), regex, should_stop, &mut left, found)?;
+        }
+    }
+    Ok(())
+}
+
+/// Search one file for at most `left` more hits, counting them off; a file
+/// that cannot be read, or stops being readable, has nothing more to find
+fn grep_file(
+    path: &Path,
+    regex: &Regex,
+    should_stop: &dyn Fn() -> bool,
+    left: &mut usize,
+    found: &mut dyn FnMut(GrepHit) -> io::Result<()>,
+) -> io::Result<()> {
+    let Ok(mut file) = File::open(path) else {
+        return Ok(());
+    };
+    let mut start = Vec::with_capacity(BINARY_CHECK_LEN);
+    if (&mut file)
+        .take(BINARY_CHECK_LEN as u64)
+        .read_to_end(&mut start)
+        .is_err()
+        || start.contains(&0)
+    {
+        return Ok(());
+    }
+    let mut reader = BufReader::new(io::Cursor::new(start).chain(file));
+    let mut buf = Vec::new();
+    let mut line = 0;
+    while matches!(reader.read_until(b'\n', &mut buf), Ok(len) if len > 0) {
+        line += 1;
+        if line % 1024 == 0 && should_stop() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "search stopped"));
+        }
+        let text = String::from_utf8_lossy(&buf);
+        let text = text.trim_end_matches(['\n', '\r']);
+        if regex.is_match(text) {
+            found(GrepHit {
+                path: path.to_path_buf(),
+                line,
+                text: text.to_string(),
+            })?;
+            *left -= 1;
+            if *left == 0 {
+                break;
+            }
+        }
+        buf.clear();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_matching_lines_of_text_files() {
+        let root = TempDir::new("grep");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\r\n    todo!()\r\n}\r\n",
+        )
+        .unwrap();
+        fs::write(root.join("notes.txt"), "TODO: tests\nnothing\ntodo").unwrap();
+        fs::write(root.join("image.bin"), b"todo\0\x89").unwrap();
+        fs::write(root.join(".git/todo"), "todo").unwrap();
+
+        let search = |root: &Path, pattern: &str| {
+            let regex = Regex::new(pattern).unwrap();
+            let mut hits = Vec::new();
+            grep(root, &regex, &|| false, &mut |hit| {
+                let relative = hit.path.strip_prefix(root).unwrap_or(&hit.path);
+                hits.push(format!("{}:{}:{}", relative.display(), hit.line, hit.text));
+                Ok(())
+            })
+            .unwrap();
+            hits
+        };
+        assert_eq!(
+            search(&root, "(?i)todo"),
+            [
+                "notes.txt:1:TODO: tests",
+                "notes.txt:3:todo",
+                "src/main.rs:2:    todo!()"
+            ]
+        );
+        assert_eq!(search(&root.join("notes.txt"), "^no"), [":2:nothing"]);
+    }
+
+    #[test]
+    fn test_search_stops_at_the_limit() {
+        let root = TempDir::new("grep-limit");
+        fs::write(root.join("a.txt"), "x\nx\n").unwrap();
+        fs::write(root.join("b.txt"), "x\n").unwrap();
+        let regex = Regex::new("x").unwrap();
+        let mut hits = Vec::new();
+        grep_limited(&root, &regex, 2, &|| false, &mut |hit| {
+            hits.push(hit.line);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(hits, [1, 2]);
+    }
+}
@@ -3,7 +3,15 @@
 //! Split into modules for reduced complexity.
 
 mod archive;
+mod conflict;
+mod copy;
+mod expand;
+mod find;
+mod grep;
 mod ops;
+mod rename_plan;
+mod substitute;
+mod trash;
 mod utils;
 
 use std::cmp::Ordering;
@@ -12,8 +20,19 @@ use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 pub use archive::{ArchiveEntry, extract_archive, extract_files_from_archive, list_archive};
-pub use ops::{copy_file, create_symlink, delete, move_file};
-pub use utils::{chmod, format_size, is_archive, trash};
+pub use conflict::{ConflictPolicy, Resolution, check_not_into_itself, numbered_name, resolve};
+pub use copy::{copy_file, copy_file_with_progress, scan_tree};
+pub use expand::{expand_name, expand_path};
+pub use find::{FindQuery, find};
+pub use grep::{GrepHit, HIT_LIMIT, grep};
+pub use ops::{
+    create_directory, create_symlink, delete, delete_with_progress, rename, replace, staging_path,
+    touch,
+};
+pub use rename_plan::{RenamePlan, RenameStep};
+pub use substitute::Substitution;
+pub use trash::{TrashItem, forget_orphans, forget_trashed, list_trash, restore, trash};
+pub use utils::{chmod, copy_text, format_size, is_archive};
 
 /// Filesystem entry (file or directory)
 #[derive(Clone)]
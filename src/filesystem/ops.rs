@@ -1,39 +1,77 @@
-//! File operations (copy, move, delete)
+//! File operations (move, delete, create)
 
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-/// Copy a file or directory
-pub fn copy_file(src: &Path, dest: &Path) -> std::io::Result<()> {
-    if src.is_dir() {
-        copy_directory(src, dest)
-    } else {
-        fs::copy(src, dest).map(|_| ())
-    }
+/// Where a copy or move onto `dest` should write: nowhere else when
+/// `dest` is free, otherwise a free hidden name next to it, so what is
+/// there is only replaced once the new entry is complete
+pub fn staging_path(dest: &Path) -> Option<PathBuf> {
+    dest.symlink_metadata().ok()?;
+    Some(free_sibling(dest, "new"))
 }
 
-/// Copy a directory recursively
-pub fn copy_directory(src: &Path, dest: &Path) -> std::io::Result<()> {
-    fs::create_dir_all(dest)?;
+/// Put `new` in place of `dest`, whatever either of them is. A symlink at
+/// `dest` is replaced itself, never the entry it points to, and a
+/// directory is replaced rather than merged into.
+pub fn replace(new: &Path, dest: &Path) -> io::Result<()> {
+    let is_dir = |path: &Path| path.symlink_metadata().is_ok_and(|m| m.is_dir());
+    if !is_dir(new) && !is_dir(dest) {
+        // rename(2) swaps non-directories atomically
+        return fs::rename(new, dest);
+    }
+    let old = free_sibling(dest, "old");
+    match fs::rename(dest, &old) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return fs::rename(new, dest),
+        Err(e) => return Err(e),
+    }
+    if let Err(e) = fs::rename(new, dest) {
+        let _ = fs::rename(&old, dest);
+        return Err(e);
+    }
+    delete(&old)
+}
 
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let src_path = entry.path();
-        let dest_path = dest.join(entry.file_name());
+/// A free `.<name>.mkfm-<purpose>-<pid>` style name next to `path`
+fn free_sibling(path: &Path, purpose: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let pid = std::process::id();
+    (0..)
+        .map(|n| path.with_file_name(format!(".{name}.mkfm-{purpose}-{pid}-{n}")))
+        .find(|candidate| candidate.symlink_metadata().is_err())
+        .expect("unbounded range always yields a free name")
+}
 
-        if src_path.is_dir() {
-            copy_directory(&src_path, &dest_path)?;
-        } else {
-            fs::copy(&src_path, &dest_path)?;
-        }
+/// Rename a file or directory, refusing to replace an existing entry
+pub fn rename(src: &Path, dest: &Path) -> io::Result<()> {
+    if dest.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dest.display()),
+        ));
     }
+    fs::rename(src, dest)
+}
 
-    Ok(())
+/// Create a directory, along with missing parents when `parents` is set
+pub fn create_directory(path: &Path, parents: bool) -> io::Result<()> {
+    if parents {
+        fs::create_dir_all(path)
+    } else {
+        fs::create_dir(path)
+    }
 }
 
-/// Move a file or directory
-pub fn move_file(src: &Path, dest: &Path) -> std::io::Result<()> {
-    fs::rename(src, dest)
+/// Create an empty file, or update the modification time of an existing one
+pub fn touch(path: &Path) -> io::Result<()> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.set_modified(SystemTime::now())
 }
 
 /// Delete a file or directory
@@ -45,23 +83,69 @@ pub fn delete(path: &Path) -> std::io::Result<()> {
     }
 }
 
-/// Create a symbolic link
-pub fn create_symlink(src: &Path, dest_dir: &Path) -> std::io::Result<()> {
-    let name = src
-        .file_name()
-        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "No filename"))?;
-
-    let link_path = dest_dir.join(name);
+/// Delete a file or directory tree, calling `on_removed` after each entry
+/// other than a directory; an error from it stops the deletion, leaving
+/// whatever was not removed yet
+pub fn delete_with_progress(
+    path: &Path,
+    on_removed: &mut dyn FnMut(&Path, &fs::Metadata) -> io::Result<()>,
+) -> io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        for entry in fs::read_dir(path)? {
+            delete_with_progress(&entry?.path(), on_removed)?;
+        }
+        fs::remove_dir(path)
+    } else {
+        fs::remove_file(path)?;
+        on_removed(path, &meta)
+    }
+}
 
+/// Create a symbolic link at `link_path` pointing to `src`
+pub fn create_symlink(src: &Path, link_path: &Path) -> std::io::Result<()> {
     #[cfg(unix)]
-    std::os::unix::fs::symlink(src, &link_path)?;
+    std::os::unix::fs::symlink(src, link_path)?;
 
     #[cfg(windows)]
     if src.is_dir() {
-        std::os::windows::fs::symlink_dir(src, &link_path)?;
+        std::os::windows::fs::symlink_dir(src, link_path)?;
     } else {
-        std::os::windows::fs::symlink_file(src, &link_path)?;
+        std::os::windows::fs::symlink_file(src, link_path)?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_delete_reports_each_entry_and_stops_on_error() {
+        let temp = TempDir::new("delete");
+        let dir = temp.join("dir");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("sub/b"), "b").unwrap();
+
+        let mut removed = 0;
+        let stopped = delete_with_progress(&dir, &mut |_, _| {
+            removed += 1;
+            Err(io::Error::from(io::ErrorKind::Interrupted))
+        });
+        assert!(stopped.is_err());
+        assert_eq!(removed, 1);
+        assert!(dir.exists());
+
+        let mut removed = 0;
+        delete_with_progress(&dir, &mut |_, _| {
+            removed += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(removed, 1);
+        assert!(!dir.exists());
+    }
+}
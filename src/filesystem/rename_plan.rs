@@ -0,0 +1,215 @@
+//! Planning bulk renames
+//!
+//! The edited names are checked as a whole before anything is renamed:
+//! there must be one per entry, no two entries may end up at the same path
+//! and nothing outside the renamed entries may be replaced. Renames that
+//! form chains or cycles, such as swapping two names, are ordered so each
+//! target is free when its turn comes, going through a temporary name where
+//! no order works.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+/// One filesystem change of a plan, to be made in order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenameStep {
+    /// Create a directory a new name moves into, with its parents
+    CreateDir(PathBuf),
+    Rename(PathBuf, PathBuf),
+}
+
+/// Checked renames and the steps that carry them out
+#[derive(Clone, Debug)]
+pub struct RenamePlan {
+    /// Old and new path of each entry whose name changes, in listing order
+    pub changes: Vec<(PathBuf, PathBuf)>,
+    pub steps: Vec<RenameStep>,
+}
+
+impl RenamePlan {
+    /// Plan renaming `paths` to the lines of `edited`, one per path. Blank
+    /// lines keep their entry's name; `/` moves an entry into a directory
+    /// below its own, which is created when missing.
+    pub fn new(paths: &[PathBuf], edited: &str) -> Result<Self, String> {
+        let names: Vec<&str> = edited.lines().collect();
+        if names.len() != paths.len() {
+            return Err(format!(
+                "{} names for {} entries; lines must not be added or removed",
+                names.len(),
+                paths.len()
+            ));
+        }
+        Self::from_names(paths, &names)
+    }
+
+    /// Plan renaming each of `paths` to the name at the same index, checked
+    /// as for [`RenamePlan::new`]
+    pub fn from_names(paths: &[PathBuf], names: &[impl AsRef<str>]) -> Result<Self, String> {
+        let mut changes = Vec::new();
+        let mut targets: HashSet<PathBuf> = HashSet::new();
+        for (src, name) in paths.iter().zip(names) {
+            let name = name.as_ref().trim();
+            let dest = if name.is_empty() {
+                src.clone()
+            } else {
+                target_path(src, name)?
+            };
+            if !targets.insert(dest.clone()) {
+                return Err(format!(
+                    "more than one entry would be named {}",
+                    dest.display()
+                ));
+            }
+            if dest != *src {
+                changes.push((src.clone(), dest));
+            }
+        }
+
+        let sources: HashSet<&Path> = changes.iter().map(|(src, _)| src.as_path()).collect();
+        for (_, dest) in &changes {
+            if dest.symlink_metadata().is_ok() && !sources.contains(dest.as_path()) {
+                return Err(format!("{} already exists", dest.display()));
+            }
+        }
+
+        let steps = order_steps(&changes, |p| p.symlink_metadata().is_ok());
+        Ok(Self { changes, steps })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Path `name` gives the entry at `src`, refusing to leave its directory
+fn target_path(src: &Path, name: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(name);
+    let escapes = relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if escapes {
+        return Err(format!("{name} leaves the directory"));
+    }
+    let dir = src.parent().unwrap_or(Path::new("/"));
+    Ok(dir.join(relative))
+}
+
+/// Order renames so each target is free when its turn comes, breaking
+/// cycles with a temporary name; `exists` reports paths taken on disk
+fn order_steps(changes: &[(PathBuf, PathBuf)], exists: impl Fn(&Path) -> bool) -> Vec<RenameStep> {
+    let mut steps = Vec::new();
+    let mut created: HashSet<PathBuf> = HashSet::new();
+    for (_, dest) in changes {
+        if let Some(dir) = dest.parent()
+            && !exists(dir)
+            && created.insert(dir.to_path_buf())
+        {
+            steps.push(RenameStep::CreateDir(dir.to_path_buf()));
+        }
+    }
+
+    // Pending renames by source; a rename waits while its target is the
+    // source of another pending one
+    let mut pending: Vec<(PathBuf, PathBuf)> = changes.to_vec();
+    let mut temp_count = 0;
+    while !pending.is_empty() {
+        let sources: HashMap<&Path, usize> = pending
+            .iter()
+            .enumerate()
+            .map(|(i, (src, _))| (src.as_path(), i))
+            .collect();
+        let ready = pending
+            .iter()
+            .position(|(_, dest)| !sources.contains_key(dest.as_path()));
+        match ready {
+            Some(i) => {
+                let (src, dest) = pending.remove(i);
+                steps.push(RenameStep::Rename(src, dest));
+            }
+            None => {
+                // Every pending target is another pending source: a cycle
+                let (src, dest) = pending.remove(0);
+                let temp = loop {
+                    temp_count += 1;
+                    let name = format!(".mkfm-rename-{}-{temp_count}", std::process::id());
+                    let temp = src.with_file_name(name);
+                    if !exists(&temp) {
+                        break temp;
+                    }
+                };
+                steps.push(RenameStep::Rename(src, temp.clone()));
+                pending.push((temp, dest));
+            }
+        }
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(|n| std::env::temp_dir().join(n)).collect()
+    }
+
+    fn rename(src: &str, dest: &str) -> RenameStep {
+        RenameStep::Rename(PathBuf::from(src), PathBuf::from(dest))
+    }
+
+    #[test]
+    fn test_swaps_go_through_a_temporary_name() {
+        let changes = vec![
+            (PathBuf::from("/d/a"), PathBuf::from("/d/b")),
+            (PathBuf::from("/d/b"), PathBuf::from("/d/a")),
+        ];
+        let steps = order_steps(&changes, |p| p == Path::new("/d"));
+        let temp = format!("/d/.mkfm-rename-{}-1", std::process::id());
+        assert_eq!(
+            steps,
+            vec![
+                rename("/d/a", &temp),
+                rename("/d/b", "/d/a"),
+                rename(&temp, "/d/b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chains_rename_the_free_end_first() {
+        let changes = vec![
+            (PathBuf::from("/d/a"), PathBuf::from("/d/b")),
+            (PathBuf::from("/d/b"), PathBuf::from("/d/c")),
+        ];
+        let steps = order_steps(&changes, |p| p == Path::new("/d"));
+        assert_eq!(steps, vec![rename("/d/b", "/d/c"), rename("/d/a", "/d/b")]);
+    }
+
+    #[test]
+    fn test_subdirectories_are_created_first() {
+        let changes = vec![(PathBuf::from("/d/a"), PathBuf::from("/d/new/a"))];
+        let steps = order_steps(&changes, |p| p == Path::new("/d"));
+        assert_eq!(
+            steps,
+            vec![
+                RenameStep::CreateDir(PathBuf::from("/d/new")),
+                rename("/d/a", "/d/new/a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edits_are_validated() {
+        let entries = paths(&["mkfm-no-such-a", "mkfm-no-such-b"]);
+        assert!(RenamePlan::new(&entries, "x\n").is_err());
+        assert!(RenamePlan::new(&entries, "x\nx\n").is_err());
+        assert!(RenamePlan::new(&entries, "../x\ny\n").is_err());
+
+        let plan = RenamePlan::new(&entries, "mkfm-no-such-b\nmkfm-no-such-a\n").unwrap();
+        assert_eq!(plan.changes.len(), 2);
+        assert_eq!(plan.steps.len(), 3);
+
+        let plan = RenamePlan::new(&entries, "\nmkfm-no-such-b\n").unwrap();
+        assert!(plan.is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+//! Regex substitution on entry names
+//!
+//! `s/pattern/replacement/flags` as in vim and sed, any punctuation working
+//! as the delimiter. The replacement may use `\0`-`\9` for the match and
+//! its groups, `\U` and `\L` to upper- or lowercase what follows until `\E`,
+//! and `{n}` for a counter numbering the renamed entries from 1, padded as
+//! in `{n:03}`. Flags are `g` to replace every match and `i` to ignore case.
+
+use regex::{Captures, Regex, RegexBuilder};
+
+/// Case conversion in effect while expanding a replacement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Case {
+    Keep,
+    Upper,
+    Lower,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Group(usize),
+    Case(Case),
+    Counter { width: usize, zeros: bool },
+}
+
+/// A parsed `s/pattern/replacement/flags` command
+#[derive(Clone, Debug)]
+pub struct Substitution {
+    regex: Regex,
+    replacement: Vec<Piece>,
+    global: bool,
+}
+
+impl Substitution {
+    /// Parse the command after its `s`, starting with the delimiter
+    pub fn parse(command: &str) -> Result<Self, String> {
+        let mut chars = command.chars();
+        let delimiter = chars
+            .next()
+            .filter(|c| !c.is_alphanumeric() && !c.is_whitespace() && *c != '\\')
+            .ok_or("expected s/pattern/replacement/")?;
+        let parts = split_unescaped(chars.as_str(), delimiter);
+        let (pattern, replacement, flags) = match parts.as_slice() {
+            [pattern, replacement] => (pattern, replacement.as_str(), ""),
+            [pattern, replacement, flags] => (pattern, replacement.as_str(), flags.as_str()),
+            _ => return Err("expected s/pattern/replacement/".to_string()),
+        };
+        if pattern.is_empty() {
+            return Err("empty pattern".to_string());
+        }
+
+        let (mut global, mut ignore_case) = (false, false);
+        for flag in flags.chars() {
+            match flag {
+                'g' => global = true,
+                'i' => ignore_case = true,
+                _ => return Err(format!("unknown flag {flag}")),
+            }
+        }
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let replacement = parse_replacement(replacement)?;
+        if let Some(group) = replacement.iter().find_map(|p| match p {
+            Piece::Group(group) if *group >= regex.captures_len() => Some(group),
+            _ => None,
+        }) {
+            return Err(format!("no group {group} in the pattern"));
+        }
+        Ok(Self {
+            regex,
+            replacement,
+            global,
+        })
+    }
+
+    /// The new name for `name`, or `None` when the pattern does not match.
+    /// `counter` is what `{n}` expands to.
+    pub fn apply(&self, name: &str, counter: usize) -> Option<String> {
+        if !self.regex.is_match(name) {
+            return None;
+        }
+        let limit = if self.global { 0 } else { 1 };
+        let expand = |caps: &Captures| self.expand(caps, counter);
+        Some(self.regex.replacen(name, limit, expand).into_owned())
+    }
+
+    fn expand(&self, caps: &Captures, counter: usize) -> String {
+        let mut out = String::new();
+        let mut case = Case::Keep;
+        let mut push = |text: &str, case: Case| match case {
+            Case::Keep => out.push_str(text),
+            Case::Upper => out.push_str(&text.to_uppercase()),
+            Case::Lower => out.push_str(&text.to_lowercase()),
+        };
+        for piece in &self.replacement {
+            match piece {
+                Piece::Text(text) => push(text, case),
+                Piece::Group(group) => push(caps.get(*group).map_or("", |m| m.as_str()), case),
+                Piece::Case(next) => case = *next,
+                Piece::Counter { width, zeros } => {
+                    let number = if *zeros {
+                        format!("{counter:0width$}")
+                    } else {
+                        format!("{counter:width$}")
+                    };
+                    push(&number, case);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Split on `delimiter` where it is not escaped, removing the escapes of
+/// delimiters and keeping every other escape for the regex or replacement
+fn split_unescaped(text: &str, delimiter: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        let part = parts.last_mut().unwrap();
+        if c == '\\' {
+            match chars.next() {
+                Some(next) if next == delimiter => part.push(next),
+                Some(next) => {
+                    part.push('\\');
+                    part.push(next);
+                }
+                None => part.push('\\'),
+            }
+        } else if c == delimiter {
+            parts.push(String::new());
+        } else {
+            part.push(c);
+        }
+    }
+    // A trailing delimiter ends the command rather than starting flags
+    if parts.len() == 3 && parts[2].is_empty() {
+        parts.pop();
+    }
+    parts
+}
+
+fn parse_replacement(text: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut literal = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let piece = match c {
+            '\\' => match chars.next() {
+                Some(d @ '0'..='9') => Piece::Group(d as usize - '0' as usize),
+                Some('U') => Piece::Case(Case::Upper),
+                Some('L') => Piece::Case(Case::Lower),
+                Some('E') => Piece::Case(Case::Keep),
+                Some(other) => {
+                    literal.push(other);
+                    continue;
+                }
+                None => return Err("replacement ends with \\".to_string()),
+            },
+            '{' if chars.peek() == Some(&'n') => {
+                let spec: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                parse_counter(&spec).ok_or_else(|| format!("bad counter {{{spec}}}"))?
+            }
+            _ => {
+                literal.push(c);
+                continue;
+            }
+        };
+        if !literal.is_empty() {
+            pieces.push(Piece::Text(std::mem::take(&mut literal)));
+        }
+        pieces.push(piece);
+    }
+    if !literal.is_empty() {
+        pieces.push(Piece::Text(literal));
+    }
+    Ok(pieces)
+}
+
+/// `n`, `n:3` or `n:03`
+fn parse_counter(spec: &str) -> Option<Piece> {
+    let format = match spec.strip_prefix('n')? {
+        "" => {
+            return Some(Piece::Counter {
+                width: 0,
+                zeros: false,
+            });
+        }
+        rest => rest.strip_prefix(':')?,
+    };
+    Some(Piece::Counter {
+        width: format.parse().ok()?,
+        zeros: format.starts_with('0'),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn substitute(command: &str, name: &str, counter: usize) -> Option<String> {
+        Substitution::parse(command).unwrap().apply(name, counter)
+    }
+
+    #[test]
+    fn test_groups_case_and_flags() {
+        assert_eq!(
+            substitute("/ /_/g", "my summer photo.jpg", 1).as_deref(),
+            Some("my_summer_photo.jpg")
+        );
+        assert_eq!(
+            substitute("/ /_/", "my summer photo.jpg", 1).as_deref(),
+            Some("my_summer photo.jpg")
+        );
+        assert_eq!(
+            substitute(r"/(\w+)\.(\w+)/\U\2\E-\1/", "notes.txt", 1).as_deref(),
+            Some("TXT-notes")
+        );
+        assert_eq!(
+            substitute("/JPG$/jpg/i", "photo.jpg", 1).as_deref(),
+            Some("photo.jpg")
+        );
+        assert_eq!(substitute("/x/y/", "photo.jpg", 1), None);
+        // Other delimiters, and escaped delimiters in the pattern
+        assert_eq!(substitute(r"#a/b#c#", "a/b", 1).as_deref(), Some("c"));
+        assert_eq!(substitute(r"/a\/b/c/", "a/b", 1).as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn test_counters() {
+        assert_eq!(
+            substitute(r"/.*\.(\w+)$/img_{n:03}.\1/", "DSC1234.jpg", 7).as_deref(),
+            Some("img_007.jpg")
+        );
+        assert_eq!(
+            substitute("/^/{n} - /", "song.mp3", 12).as_deref(),
+            Some("12 - song.mp3")
+        );
+    }
+
+    #[test]
+    fn test_bad_commands_are_rejected() {
+        assert!(Substitution::parse("/a").is_err());
+        assert!(Substitution::parse("//b/").is_err());
+        assert!(Substitution::parse("/(/b/").is_err());
+        assert!(Substitution::parse("/a/b/x").is_err());
+        assert!(Substitution::parse(r"/a/\1/").is_err());
+        assert!(Substitution::parse("/a/{n:x}/").is_err());
+    }
+}
@@ -0,0 +1,499 @@
+//! FreeDesktop trash
+//!
+//! Entries go to the home trash when they are on its filesystem and to the
+//! trash at the top of their own mount otherwise: `$topdir/.Trash/$uid` when
+//! an administrator made a sticky `.Trash`, else `$topdir/.Trash-$uid`. Each
+//! trashed entry gets a `.trashinfo` file recording where it came from and
+//! when, and is renamed to a free name so nothing in the trash is replaced.
+
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::{copy_file, delete, numbered_name};
+
+/// An entry in one of the trash directories
+#[derive(Clone, Debug)]
+pub struct TrashItem {
+    /// Where the entry is kept, in the trash's `files` directory
+    pub path: PathBuf,
+    /// Where it was trashed from
+    pub original: PathBuf,
+    pub deleted: Option<SystemTime>,
+}
+
+/// Move `path` to the trash for its filesystem
+pub fn trash(path: &Path) -> io::Result<()> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No filename"))?;
+    // The entry itself may be a symlink, so only its parent is resolved
+    let parent = path.parent().unwrap_or(Path::new(".")).canonicalize()?;
+    let path = parent.join(name);
+    let device = fs::symlink_metadata(&path)?.dev();
+
+    let home = home_trash()?;
+    if fs::metadata(&home)?.dev() == device {
+        return move_into(&path, &home, &path);
+    }
+    let top = mount_root(&parent)?;
+    match top_trash(&top) {
+        // Paths in a volume's trash are relative so they survive remounting
+        Ok(trash) => move_into(&path, &trash, path.strip_prefix(&top).unwrap_or(&path)),
+        // Without a usable trash on the volume the entry is copied home
+        Err(_) => move_into(&path, &home, &path),
+    }
+}
+
+/// Everything in the home trash and the trashes of mounted volumes
+pub fn list_trash() -> Vec<TrashItem> {
+    let mut items = Vec::new();
+    for (trash, top) in trash_dirs() {
+        let Ok(read_dir) = fs::read_dir(trash.join("info")) else {
+            continue;
+        };
+        for entry in read_dir.filter_map(|e| e.ok()) {
+            let info = entry.path();
+            if info.extension() != Some(OsStr::new("trashinfo")) {
+                continue;
+            }
+            let Some(stem) = info.file_stem() else {
+                continue;
+            };
+            let path = trash.join("files").join(stem);
+            let Ok(content) = fs::read_to_string(&info) else {
+                continue;
+            };
+            if path.symlink_metadata().is_err() {
+                continue;
+            }
+            if let Some((original, deleted)) = parse_info(&content) {
+                let original = match &top {
+                    Some(top) if original.is_relative() => top.join(original),
+                    _ => original,
+                };
+                items.push(TrashItem {
+                    path,
+                    original,
+                    deleted,
+                });
+            }
+        }
+    }
+    items
+}
+
+/// Put a trashed entry back where it came from, or at `dest`
+pub fn restore(path: &Path, dest: &Path) -> io::Result<()> {
+    if dest.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dest.display()),
+        ));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    move_path(path, dest)?;
+    forget_trashed(path)
+}
+
+/// Remove the `.trashinfo` of an entry that was deleted from the trash;
+/// paths outside the trash are left alone
+pub fn forget_trashed(path: &Path) -> io::Result<()> {
+    match info_path(path) {
+        Some(info) => match fs::remove_file(info) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+        None => Ok(()),
+    }
+}
+
+/// Remove the `.trashinfo` files of every trash whose entry is gone from
+/// its `files`
+pub fn forget_orphans() -> io::Result<()> {
+    trash_dirs()
+        .iter()
+        .try_for_each(|(trash, _)| forget_orphans_in(trash))
+}
+
+fn forget_orphans_in(trash: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(trash.join("info"))?.filter_map(|e| e.ok()) {
+        let info = entry.path();
+        if info.extension() != Some(OsStr::new("trashinfo")) {
+            continue;
+        }
+        let Some(stem) = info.file_stem() else {
+            continue;
+        };
+        if trash.join("files").join(stem).symlink_metadata().is_err() {
+            match fs::remove_file(&info) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The `.trashinfo` of `path` when it is directly in a trash's `files`.
+/// Every delete asks, so the cheap checks come first and the mounts are
+/// only read for a record outside the home trash.
+fn info_path(path: &Path) -> Option<PathBuf> {
+    let files = path.parent()?;
+    let trash = files.parent()?;
+    if files.file_name() != Some(OsStr::new("files")) {
+        return None;
+    }
+    let mut name = path.file_name()?.to_os_string();
+    name.push(".trashinfo");
+    let info = trash.join("info").join(name);
+    if !info.is_file() {
+        return None;
+    }
+    let home = dirs::data_dir().map(|data| data.join("Trash"));
+    if home.as_deref() != Some(trash) && !trash_dirs().iter().any(|(dir, _)| dir == trash) {
+        return None;
+    }
+    Some(info)
+}
+
+fn home_trash() -> io::Result<PathBuf> {
+    let data = dirs::data_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    let trash = data.join("Trash");
+    create_trash_dirs(&trash)?;
+    Ok(trash)
+}
+
+/// The trash at the top of a volume, creating the per-user one if needed
+fn top_trash(top: &Path) -> io::Result<PathBuf> {
+    // SAFETY: getuid takes no arguments and cannot fail
+    let uid = unsafe { libc::getuid() };
+    let shared = top.join(".Trash");
+    if let Ok(meta) = fs::symlink_metadata(&shared)
+        && meta.is_dir()
+        // Sticky, so users cannot remove each other's entries
+        && meta.mode() & 0o1000 != 0
+    {
+        let trash = shared.join(uid.to_string());
+        if create_trash_dirs(&trash).is_ok() {
+            return Ok(trash);
+        }
+    }
+    let trash = top.join(format!(".Trash-{uid}"));
+    create_trash_dirs(&trash)?;
+    if fs::symlink_metadata(&trash)?.uid() != uid {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} belongs to another user", trash.display()),
+        ));
+    }
+    Ok(trash)
+}
+
+fn create_trash_dirs(trash: &Path) -> io::Result<()> {
+    for dir in [trash.join("files"), trash.join("info")] {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&dir)?;
+        if fs::symlink_metadata(&dir)?.file_type().is_symlink() {
+            return Err(io::Error::other(format!("{} is a symlink", dir.display())));
+        }
+    }
+    Ok(())
+}
+
+/// Outermost directory of `dir`'s filesystem
+fn mount_root(dir: &Path) -> io::Result<PathBuf> {
+    let device = fs::metadata(dir)?.dev();
+    let mut root = dir;
+    for ancestor in dir.ancestors().skip(1) {
+        if fs::metadata(ancestor)?.dev() != device {
+            break;
+        }
+        root = ancestor;
+    }
+    Ok(root.to_path_buf())
+}
+
+/// Existing trash directories, with the volume top relative paths in their
+/// `.trashinfo` files start from
+fn trash_dirs() -> Vec<(PathBuf, Option<PathBuf>)> {
+    let mut dirs = Vec::new();
+    if let Some(data) = dirs::data_dir() {
+        dirs.push((data.join("Trash"), None));
+    }
+    // SAFETY: getuid takes no arguments and cannot fail
+    let uid = unsafe { libc::getuid() };
+    let mounts = fs::read("/proc/self/mounts").unwrap_or_default();
+    for line in mounts.split(|b| *b == b'\n') {
+        let Some(field) = line.split(|b| *b == b' ').nth(1) else {
+            continue;
+        };
+        let top = PathBuf::from(OsString::from_vec(unescape_mount(field)));
+        for trash in [
+            top.join(".Trash").join(uid.to_string()),
+            top.join(format!(".Trash-{uid}")),
+        ] {
+            if trash.join("info").is_dir() && !dirs.iter().any(|(d, _)| *d == trash) {
+                dirs.push((trash, Some(top.clone())));
+            }
+        }
+    }
+    dirs
+}
+
+/// Undo the octal escapes of spaces and tabs in `/proc/self/mounts`
+fn unescape_mount(field: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(field.len());
+    let mut i = 0;
+    while i < field.len() {
+        let code = field
+            .get(i + 1..i + 4)
+            .filter(|_| field[i] == b'\\')
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match code {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            }
+            None => {
+                out.push(field[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Reserve a free name in `trash`, record `recorded` as the original path
+/// and move `path` there
+fn move_into(path: &Path, trash: &Path, recorded: &Path) -> io::Result<()> {
+    let files = trash.join("files");
+    let wanted = files.join(path.file_name().unwrap_or_default());
+    let info_for = |p: &Path| {
+        let mut name = p.file_name().unwrap_or_default().to_os_string();
+        name.push(".trashinfo");
+        trash.join("info").join(name)
+    };
+    let taken = |p: &Path| p.symlink_metadata().is_ok() || info_for(p).exists();
+
+    let mut dest = wanted.clone();
+    // Creating the info file exclusively claims the name against other
+    // programs trashing at the same time
+    let (info, mut file) = loop {
+        if dest.symlink_metadata().is_err() {
+            let info = info_for(&dest);
+            match OpenOptions::new().write(true).create_new(true).open(&info) {
+                Ok(file) => break (info, file),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+        dest = numbered_name(&wanted, taken);
+    };
+    let content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(recorded),
+        format_date(SystemTime::now())
+    );
+    let result = file
+        .write_all(content.as_bytes())
+        .and_then(|()| move_path(path, &dest));
+    if result.is_err() {
+        let _ = fs::remove_file(info);
+    }
+    result
+}
+
+/// Rename, or copy and delete when `dest` is on another filesystem
+fn move_path(src: &Path, dest: &Path) -> io::Result<()> {
+    match fs::rename(src, dest) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_file(src, dest, true)?;
+            delete(src)
+        }
+        result => result,
+    }
+}
+
+/// Percent-encode a path as the spec's `Path=` key wants
+fn encode_path(path: &Path) -> String {
+    let mut out = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+fn decode_path(encoded: &str) -> PathBuf {
+    let bytes = encoded.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match byte {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(OsString::from_vec(out))
+}
+
+/// Original path and deletion date from a `.trashinfo` file
+fn parse_info(content: &str) -> Option<(PathBuf, Option<SystemTime>)> {
+    let mut lines = content.lines().map(str::trim);
+    if lines.next()? != "[Trash Info]" {
+        return None;
+    }
+    let (mut path, mut deleted) = (None, None);
+    for line in lines {
+        if let Some(value) = line.strip_prefix("Path=") {
+            path = Some(decode_path(value));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            deleted = parse_date(value);
+        }
+    }
+    Some((path?, deleted))
+}
+
+/// Local time as `YYYY-MM-DDThh:mm:ss`
+fn format_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs()) as libc::time_t;
+    // SAFETY: `tm` is plain integers (and a pointer left null), for which
+    // all zeroes is valid
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    // SAFETY: both pointers are to live locals; on failure `tm` stays zeroed
+    unsafe { libc::localtime_r(&secs, &mut tm) };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+fn parse_date(value: &str) -> Option<SystemTime> {
+    let (date, time) = value.split_once('T')?;
+    let date: Vec<i32> = date
+        .split('-')
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+    let time: Vec<i32> = time
+        .split(':')
+        .map(|s| s.parse().ok())
+        .collect::<Option<_>>()?;
+    let ([year, month, day], [hour, min, sec]) = (date.as_slice(), time.as_slice()) else {
+        return None;
+    };
+    // SAFETY: as in `format_date`, all zeroes is a valid `tm`
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_year = year - 1900;
+    tm.tm_mon = month - 1;
+    tm.tm_mday = *day;
+    tm.tm_hour = *hour;
+    tm.tm_min = *min;
+    tm.tm_sec = *sec;
+    tm.tm_isdst = -1;
+    // SAFETY: `tm` is a live, initialized local that mktime may normalize
+    let secs = unsafe { libc::mktime(&mut tm) };
+    u64::try_from(secs)
+        .ok()
+        .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_info_round_trip() {
+        let path = Path::new("/home/user/My Files/100%.txt");
+        assert_eq!(encode_path(path), "/home/user/My%20Files/100%25.txt");
+        assert_eq!(decode_path(&encode_path(path)), path);
+
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let content = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(path),
+            format_date(now)
+        );
+        assert_eq!(parse_info(&content), Some((path.to_path_buf(), Some(now))));
+        assert_eq!(parse_info("Path=/a\n"), None);
+    }
+
+    #[test]
+    fn test_unescape_mount() {
+        assert_eq!(unescape_mount(br"/media/My\040Disk"), b"/media/My Disk");
+        assert_eq!(unescape_mount(br"/a\b"), br"/a\b");
+    }
+
+    #[test]
+    fn test_trash_names_never_collide() {
+        let dir = TempDir::new("trash");
+        let trash = dir.join("Trash");
+        create_trash_dirs(&trash).unwrap();
+        for _ in 0..2 {
+            fs::write(dir.join("a.txt"), "a").unwrap();
+            move_into(&dir.join("a.txt"), &trash, Path::new("/x/a.txt")).unwrap();
+        }
+        assert!(trash.join("files/a.txt").exists());
+        assert!(trash.join("files/a (1).txt").exists());
+        let info = fs::read_to_string(trash.join("info/a (1).txt.trashinfo")).unwrap();
+        assert!(info.starts_with("[Trash Info]\nPath=/x/a.txt\n"));
+    }
+
+    #[test]
+    fn test_forget_leaves_other_directories_alone() {
+        let dir = TempDir::new("forget");
+        create_trash_dirs(&dir).unwrap();
+        fs::write(dir.join("info/a.trashinfo"), "").unwrap();
+        // Laid out like a trash, but not one
+        assert_eq!(info_path(&dir.join("files/a")), None);
+        forget_trashed(&dir.join("files/a")).unwrap();
+        assert!(dir.join("info/a.trashinfo").exists());
+        assert_eq!(info_path(&dir.join("a")), None);
+    }
+
+    #[test]
+    fn test_forget_orphans() {
+        let dir = TempDir::new("orphans");
+        create_trash_dirs(&dir).unwrap();
+        fs::write(dir.join("files/kept"), "").unwrap();
+        fs::write(dir.join("info/kept.trashinfo"), "").unwrap();
+        fs::write(dir.join("info/gone.trashinfo"), "").unwrap();
+        fs::write(dir.join("info/notes.txt"), "").unwrap();
+        forget_orphans_in(&dir).unwrap();
+        assert!(dir.join("info/kept.trashinfo").exists());
+        assert!(!dir.join("info/gone.trashinfo").exists());
+        assert!(dir.join("info/notes.txt").exists());
+    }
+}
@@ -1,7 +1,8 @@
 //! Utility functions
 
+use std::io::Write;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 /// Format file size as human-readable string
 pub fn format_size(bytes: u64) -> String {
@@ -20,45 +21,32 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
-/// Move file to trash
-pub fn trash(path: &Path) -> std::io::Result<()> {
-    // Try trash-put first (trash-cli)
-    let result = Command::new("trash-put").arg(path).status();
-
-    if let Ok(status) = result
-        && status.success()
-    {
-        return Ok(());
-    }
-
-    // Fall back to gio trash
-    let result = Command::new("gio")
-        .args(["trash", &path.to_string_lossy()])
-        .status();
-
-    if let Ok(status) = result
-        && status.success()
-    {
-        return Ok(());
+/// Put text on the system clipboard
+pub fn copy_text(text: &str) -> std::io::Result<()> {
+    // Try wl-copy first (Wayland), then xclip
+    let commands: [(&str, &[&str]); 2] =
+        [("wl-copy", &[]), ("xclip", &["-selection", "clipboard"])];
+    for (cmd, args) in commands {
+        let Ok(mut child) = Command::new(cmd)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
     }
-
-    // Fall back to moving to XDG trash
-    let trash_dir = std::env::var("XDG_DATA_HOME")
-        .map(|d| std::path::PathBuf::from(d).join("Trash/files"))
-        .or_else(|_| {
-            std::env::var("HOME")
-                .map(|d| std::path::PathBuf::from(d).join(".local/share/Trash/files"))
-        })
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, e))?;
-
-    std::fs::create_dir_all(&trash_dir)?;
-
-    let file_name = path
-        .file_name()
-        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "No filename"))?;
-
-    let dest = trash_dir.join(file_name);
-    std::fs::rename(path, dest)
+    Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "no clipboard tool found (wl-copy or xclip)",
+    ))
 }
 
 /// Change file permissions (Unix only)
@@ -1,3 +1,69 @@
+use mkframe::{Key, KeyEvent};
+
+use crate::cmdline::EditKey;
+use crate::config::Keymap;
+
+/// Names for keys that have no character, as used in key sequences
+pub const NAMED_KEYS: &[&str] = &[
+    "Up", "Down", "Left", "Right", "Home", "End", "PageUp", "PageDown", "Delete", "Insert", "Tab",
+    "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12",
+];
+
+/// Convert a key event to the string used for binding lookup
+///
+/// Character keys use mkframe's representation (`j`, `C-w`, `\n`). Keys
+/// without a character get a name from `NAMED_KEYS`, and Alt or modified
+/// named keys get `C-`/`A-`/`S-` prefixes (`A-Left`, `S-F3`).
+pub fn key_event_string(event: &KeyEvent) -> Option<String> {
+    let mods = &event.modifiers;
+    let named = match event.key {
+        Key::Up => "Up",
+        Key::Down => "Down",
+        Key::Left => "Left",
+        Key::Right => "Right",
+        Key::Home => "Home",
+        Key::End => "End",
+        Key::PageUp => "PageUp",
+        Key::PageDown => "PageDown",
+        Key::Delete => "Delete",
+        Key::Insert => "Insert",
+        Key::Tab if mods.shift || mods.ctrl || mods.alt => "Tab",
+        Key::F1 => "F1",
+        Key::F2 => "F2",
+        Key::F3 => "F3",
+        Key::F4 => "F4",
+        Key::F5 => "F5",
+        Key::F6 => "F6",
+        Key::F7 => "F7",
+        Key::F8 => "F8",
+        Key::F9 => "F9",
+        Key::F10 => "F10",
+        Key::F11 => "F11",
+        Key::F12 => "F12",
+        _ => {
+            let key = event.to_key_string()?;
+            return Some(if mods.alt && !mods.ctrl {
+                format!("A-{}", key)
+            } else {
+                key
+            });
+        }
+    };
+
+    let mut key = String::new();
+    if mods.ctrl {
+        key.push_str("C-");
+    }
+    if mods.alt {
+        key.push_str("A-");
+    }
+    if mods.shift {
+        key.push_str("S-");
+    }
+    key.push_str(named);
+    Some(key)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub enum Mode {
     #[default]
@@ -47,15 +113,27 @@ impl SortMode {
     }
 }
 
+/// Operators that act on the range of entries a motion covers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Yank,
+    Cut,
+    Delete,
+    Trash,
+}
+
 #[derive(Clone)]
 pub enum Action {
     None,
     Pending,
+    Operator(Operator),
     MoveCursor(i32),
     CursorToTop,
     CursorToBottom,
     NextDirectory,
     PrevDirectory,
+    NextGroup,
+    PrevGroup,
     EnterDirectory,
     ParentDirectory,
     OpenFile,
@@ -66,6 +144,8 @@ pub enum Action {
     CommandBackspace,
     CommandExecute,
     CommandCancel,
+    /// Cursor movement, deletion, history and completion on the active line
+    LineEdit(EditKey),
     Yank,
     Cut,
     Paste,
@@ -95,6 +175,9 @@ pub enum Action {
     // Bookmarks
     SetMark(char),
     JumpToMark(char),
+    // Jump list
+    JumpBack,
+    JumpForward,
     // Sorting
     CycleSort,
     ReverseSort,
@@ -104,6 +187,18 @@ pub enum Action {
     ExtractArchive,
     // Symlinks
     CreateSymlink,
+    /// Prefill `:rename` with the current name, cursor before the extension
+    RenameEntry,
+    /// Prefill `:rename` with the current name, cursor at the end
+    RenameEntryAppend,
+    BulkRename,
+    // Undo journal
+    Undo,
+    Redo,
+    /// Go to the directory containing the entry under the cursor
+    RevealEntry,
+    /// Pick a file below the directory by fuzzy matching its path
+    FuzzyFind,
     // Search highlighting
     ClearSearchHighlight,
     // Fold (inline expansion)
@@ -121,135 +216,74 @@ pub enum Action {
     ToggleErrorList,
     // Feature list
     ToggleFeatureList,
+    // Visual mode
+    VisualSwapAnchor,
+    ReselectVisual,
+    // Tagged selection
+    ToggleSelect,
+    SelectAll,
+    InvertSelection,
 }
 
-pub fn handle_normal_key(key: &str, pending: &str) -> Action {
-    // Handle mark setting: m + any letter
-    if pending == "m" {
-        if let Some(c) = key.chars().next()
-            && c.is_ascii_alphabetic()
-        {
-            return Action::SetMark(c);
-        }
-        return Action::None;
-    }
-
-    // Handle mark jumping: ' + any letter
-    if pending == "'" {
-        if let Some(c) = key.chars().next()
-            && c.is_ascii_alphabetic()
-        {
-            return Action::JumpToMark(c);
-        }
-        return Action::None;
-    }
-
-    match (pending, key) {
-        // Unimpaired-style toggles: yo<key>
-        ("yo", "o") => Action::ToggleOverlay,
-        ("yo", "h") => Action::ToggleHidden,
-        ("y", "o") => Action::Pending,
-
-        // Unimpaired-style enable: [o<key>
-        ("[o", "o") => Action::EnableOverlay,
-        ("[o", "h") => Action::EnableHidden,
-        ("[", "o") => Action::Pending,
-
-        // Unimpaired-style disable: ]o<key>
-        ("]o", "o") => Action::DisableOverlay,
-        ("]o", "h") => Action::DisableHidden,
-        ("]", "o") => Action::Pending,
-
-        // Unimpaired-style navigation: [d / ]d for directories
-        ("[", "d") => Action::PrevDirectory,
-        ("]", "d") => Action::NextDirectory,
-
-        // Task list (quickfix-like): ]q / [q
-        ("]", "q") => Action::NextTask,
-        ("[", "q") => Action::PrevTask,
-
-        // Error list (location-list-like): ]l / [l
-        ("]", "l") => Action::NextError,
-        ("[", "l") => Action::PrevError,
-
-        // "yy" yanks
-        ("y", "y") => Action::Yank,
-        ("", "y") => Action::Pending,
-
-        // Start bracket sequences
-        ("", "[") => Action::Pending,
-        ("", "]") => Action::Pending,
-
-        // gg goes to top
-        ("g", "g") => Action::CursorToTop,
-        ("", "g") => Action::Pending,
-
-        // Ctrl+w split commands (vim-style)
-        ("C-w", "h") => Action::FocusLeft,
-        ("C-w", "j") => Action::FocusDown,
-        ("C-w", "k") => Action::FocusUp,
-        ("C-w", "l") => Action::FocusRight,
-        ("C-w", "v") => Action::SplitVertical,
-        ("C-w", "s") => Action::SplitHorizontal,
-        ("C-w", "c") | ("C-w", "q") => Action::CloseSplit,
-        ("", "C-w") => Action::Pending,
-
-        // Bookmarks
-        ("", "m") => Action::Pending, // Set mark
-        ("", "'") => Action::Pending, // Jump to mark
-
-        // Search
-        (_, "/") => Action::EnterSearchMode,
-        (_, "n") => Action::SearchNext,
-        (_, "N") => Action::SearchPrev,
-        (_, "C-l") => Action::ClearSearchHighlight,
-
-        // Sorting
-        (_, "s") => Action::CycleSort,
-        (_, "S") => Action::ReverseSort,
-
-        // Archives
-        (_, "e") => Action::ExtractArchive,
-
-        // Fold (inline expansion) - z-prefix
-        ("z", "o") => Action::FoldOpen,
-        ("z", "c") => Action::FoldClose,
-        ("z", "a") => Action::FoldToggle,
-        ("z", "O") => Action::FoldOpenRecursive,
-        ("z", "C") => Action::FoldCloseRecursive,
-        ("", "z") => Action::Pending,
-
-        // Feature list
-        (_, "F12") => Action::ToggleFeatureList,
-
-        // Single key commands
-        (_, "j") => Action::MoveCursor(1),
-        (_, "k") => Action::MoveCursor(-1),
-        (_, "G") => Action::CursorToBottom,
-        (_, "l") | (_, "\n") => Action::EnterDirectory,
-        (_, "h") | (_, "-") => Action::ParentDirectory,
-        (_, "=") => Action::OpenFile,
-        (_, "v") => Action::EnterVisualMode,
-        (_, "d") => Action::Cut,
-        (_, "p") => Action::Paste,
-        (_, "x") => Action::Delete,
-        (_, "X") => Action::Trash,
-        (_, ".") => Action::ToggleHidden,
-        (_, ":") => Action::EnterCommandMode,
-        (_, "\u{1b}") => Action::ExitVisualMode,
-        _ => Action::None,
+impl Action {
+    /// Whether this action moves the cursor and can follow an operator
+    pub fn is_motion(&self) -> bool {
+        matches!(
+            self,
+            Action::MoveCursor(_)
+                | Action::CursorToTop
+                | Action::CursorToBottom
+                | Action::NextDirectory
+                | Action::PrevDirectory
+                | Action::NextGroup
+                | Action::PrevGroup
+                | Action::SearchNext
+                | Action::SearchPrev
+        )
+    }
+
+    /// Whether a count repeats this action rather than being ignored; only
+    /// motions and what is as harmless to repeat, never file operations
+    /// or anything opening a prompt
+    pub fn is_repeatable(&self) -> bool {
+        (self.is_motion() && !matches!(self, Action::CursorToTop | Action::CursorToBottom))
+            || matches!(
+                self,
+                Action::ParentDirectory
+                    | Action::FocusLeft
+                    | Action::FocusRight
+                    | Action::FocusUp
+                    | Action::FocusDown
+                    | Action::JumpBack
+                    | Action::JumpForward
+                    | Action::FoldOpen
+                    | Action::FoldClose
+                    | Action::FoldToggle
+                    | Action::NextTask
+                    | Action::PrevTask
+                    | Action::NextError
+                    | Action::PrevError
+                    | Action::ToggleSelect
+            )
     }
 }
 
-pub fn handle_visual_key(key: &str) -> Action {
-    match key {
-        "j" => Action::MoveCursor(1),
-        "k" => Action::MoveCursor(-1),
-        "y" => Action::Yank,
-        "d" => Action::Cut,
-        "\u{1b}" | "v" => Action::ExitVisualMode,
-        _ => Action::None,
-    }
+/// Editing keys shared by the command and search lines and the fuzzy
+/// finder's query
+pub fn line_edit_key(key: &str) -> Option<EditKey> {
+    let edit = match key {
+        "Left" | "C-b" => EditKey::Left,
+        "Right" | "C-f" => EditKey::Right,
+        "Home" | "C-a" => EditKey::Home,
+        "End" | "C-e" => EditKey::End,
+        "Delete" | "C-d" => EditKey::Delete,
+        "C-w" => EditKey::DeleteWord,
+        "C-u" => EditKey::DeleteToStart,
+        "Up" | "C-p" => EditKey::HistoryPrev,
+        "Down" | "C-n" => EditKey::HistoryNext,
+        _ => return None,
+    };
+    Some(edit)
 }
 
 fn handle_command_key(key: &str) -> Action {
@@ -257,7 +291,12 @@ fn handle_command_key(key: &str) -> Action {
         "\u{1b}" => Action::CommandCancel,
         "\n" => Action::CommandExecute,
         "\u{8}" => Action::CommandBackspace, // Backspace
+        "\t" => Action::LineEdit(EditKey::Complete),
+        "S-Tab" => Action::LineEdit(EditKey::CompletePrev),
         _ => {
+            if let Some(edit) = line_edit_key(key) {
+                return Action::LineEdit(edit);
+            }
             let mut chars = key.chars();
             if let Some(c) = chars.next()
                 && chars.next().is_none()
@@ -276,6 +315,9 @@ fn handle_search_key(key: &str) -> Action {
         "\n" => Action::SearchExecute,
         "\u{8}" => Action::SearchBackspace,
         _ => {
+            if let Some(edit) = line_edit_key(key) {
+                return Action::LineEdit(edit);
+            }
             let mut chars = key.chars();
             if let Some(c) = chars.next()
                 && chars.next().is_none()
@@ -288,53 +330,22 @@ fn handle_search_key(key: &str) -> Action {
     }
 }
 
-/// Handle keyboard input for non-vim (standard) mode
-fn handle_normal_key_standard(key: &str) -> Action {
-    match key {
-        // Arrow key navigation
-        "Up" => Action::MoveCursor(-1),
-        "Down" => Action::MoveCursor(1),
-        "Home" => Action::CursorToTop,
-        "End" => Action::CursorToBottom,
-        "PageUp" => Action::MoveCursor(-10),
-        "PageDown" => Action::MoveCursor(10),
-
-        // Enter and navigation
-        "\n" | "Right" => Action::EnterDirectory,
-        "Left" | "\u{8}" => Action::ParentDirectory, // Backspace
-
-        // File operations
-        "Delete" => Action::Trash,
-        "C-c" => Action::Yank,
-        "C-x" => Action::Cut,
-        "C-v" => Action::Paste,
-
-        // Search
-        "C-f" => Action::EnterSearchMode,
-        "F3" => Action::SearchNext,
-        "S-F3" => Action::SearchPrev,
-
-        // Misc
-        "F5" => Action::ParentDirectory, // Refresh (re-enter current dir)
-        "\u{1b}" => Action::ClearSearchHighlight, // Escape
-        "C-h" => Action::ToggleHidden,
-        "F2" => Action::OpenFile,
-        "F12" => Action::ToggleFeatureList,
-
-        _ => Action::None,
-    }
-}
-
-pub fn handle_key(mode: Mode, key: &str, pending: &str, vi_mode: bool) -> Action {
+pub fn handle_key(
+    keymap: &Keymap,
+    mode: Mode,
+    key: &str,
+    pending: &[String],
+    vi_mode: bool,
+) -> Action {
     match mode {
         Mode::Normal => {
             if vi_mode {
-                handle_normal_key(key, pending)
+                keymap.normal.lookup(pending, key)
             } else {
-                handle_normal_key_standard(key)
+                keymap.standard.lookup(pending, key)
             }
         }
-        Mode::Visual => handle_visual_key(key),
+        Mode::Visual => keymap.visual.lookup(pending, key),
         Mode::Command => handle_command_key(key),
         Mode::Search => handle_search_key(key),
     }
@@ -344,6 +355,15 @@ pub fn handle_key(mode: Mode, key: &str, pending: &str, vi_mode: bool) -> Action
 mod tests {
     use super::*;
 
+    fn normal(key: &str, pending: &[&str]) -> Action {
+        let pending: Vec<String> = pending.iter().map(|s| s.to_string()).collect();
+        Keymap::default().normal.lookup(&pending, key)
+    }
+
+    fn visual(key: &str) -> Action {
+        Keymap::default().visual.lookup(&[], key)
+    }
+
     #[test]
     fn test_mode_display() {
         assert_eq!(Mode::Normal.display(), "NORMAL");
@@ -359,123 +379,139 @@ mod tests {
 
     #[test]
     fn test_normal_mode_navigation() {
-        assert!(matches!(handle_normal_key("j", ""), Action::MoveCursor(1)));
-        assert!(matches!(handle_normal_key("k", ""), Action::MoveCursor(-1)));
-        assert!(matches!(handle_normal_key("G", ""), Action::CursorToBottom));
-        assert!(matches!(handle_normal_key("l", ""), Action::EnterDirectory));
+        assert!(matches!(normal("j", &[]), Action::MoveCursor(1)));
+        assert!(matches!(normal("k", &[]), Action::MoveCursor(-1)));
+        assert!(matches!(normal("G", &[]), Action::CursorToBottom));
+        assert!(matches!(normal("l", &[]), Action::EnterDirectory));
+        assert!(matches!(normal("h", &[]), Action::ParentDirectory));
+        assert!(matches!(normal("-", &[]), Action::ParentDirectory));
+    }
+
+    #[test]
+    fn test_normal_mode_gg_sequence() {
+        // First g should be pending
+        assert!(matches!(normal("g", &[]), Action::Pending));
+        // Second g after pending should go to top
+        assert!(matches!(normal("g", &["g"]), Action::CursorToTop));
+        assert!(matches!(normal("v", &["g"]), Action::ReselectVisual));
+    }
+
+    #[test]
+    fn test_normal_mode_operators() {
+        assert!(matches!(normal("y", &[]), Action::Operator(Operator::Yank)));
+        assert!(matches!(normal("d", &[]), Action::Operator(Operator::Cut)));
         assert!(matches!(
-            handle_normal_key("h", ""),
-            Action::ParentDirectory
+            normal("x", &[]),
+            Action::Operator(Operator::Delete)
         ));
         assert!(matches!(
-            handle_normal_key("-", ""),
-            Action::ParentDirectory
+            normal("X", &[]),
+            Action::Operator(Operator::Trash)
         ));
+        assert!(matches!(normal("a", &[]), Action::RenameEntry));
+        assert!(matches!(normal("A", &[]), Action::RenameEntryAppend));
+        // Motions after an operator key are resolved on their own
+        assert!(matches!(normal("j", &["d"]), Action::None));
+        assert!(matches!(normal("}", &[]), Action::NextGroup));
+        assert!(matches!(normal("{", &[]), Action::PrevGroup));
     }
 
     #[test]
-    fn test_normal_mode_gg_sequence() {
-        // First g should be pending
-        assert!(matches!(handle_normal_key("g", ""), Action::Pending));
-        // Second g after pending should go to top
-        assert!(matches!(handle_normal_key("g", "g"), Action::CursorToTop));
+    fn test_motion_classification() {
+        assert!(Action::MoveCursor(1).is_motion());
+        assert!(Action::CursorToTop.is_motion());
+        assert!(Action::NextGroup.is_motion());
+        assert!(!Action::Paste.is_motion());
+        assert!(!Action::EnterVisualMode.is_repeatable());
     }
 
     #[test]
-    fn test_normal_mode_yy_sequence() {
-        assert!(matches!(handle_normal_key("y", ""), Action::Pending));
-        assert!(matches!(handle_normal_key("y", "y"), Action::Yank));
+    fn test_counts_repeat_only_harmless_actions() {
+        assert!(Action::MoveCursor(1).is_repeatable());
+        assert!(Action::ToggleSelect.is_repeatable());
+        // `G` and `gg` take a count as a line instead
+        assert!(!Action::CursorToBottom.is_repeatable());
+        // File operations and prompts run once whatever the count
+        for action in [
+            Action::Paste,
+            Action::Delete,
+            Action::Trash,
+            Action::ExtractArchive,
+            Action::OpenFile,
+            Action::CreateSymlink,
+            Action::SplitVertical,
+            Action::Undo,
+            Action::RenameEntry,
+        ] {
+            assert!(!action.is_repeatable());
+        }
     }
 
     #[test]
     fn test_normal_mode_unimpaired_toggles() {
         // yo sequence for toggle
-        assert!(matches!(handle_normal_key("o", "y"), Action::Pending));
-        assert!(matches!(
-            handle_normal_key("o", "yo"),
-            Action::ToggleOverlay
-        ));
-        assert!(matches!(handle_normal_key("h", "yo"), Action::ToggleHidden));
+        assert!(matches!(normal("o", &["y"]), Action::Pending));
+        assert!(matches!(normal("o", &["y", "o"]), Action::ToggleOverlay));
+        assert!(matches!(normal("h", &["y", "o"]), Action::ToggleHidden));
     }
 
     #[test]
     fn test_normal_mode_unimpaired_enable() {
-        assert!(matches!(handle_normal_key("[", ""), Action::Pending));
-        assert!(matches!(handle_normal_key("o", "["), Action::Pending));
-        assert!(matches!(
-            handle_normal_key("o", "[o"),
-            Action::EnableOverlay
-        ));
-        assert!(matches!(handle_normal_key("h", "[o"), Action::EnableHidden));
+        assert!(matches!(normal("[", &[]), Action::Pending));
+        assert!(matches!(normal("o", &["["]), Action::Pending));
+        assert!(matches!(normal("o", &["[", "o"]), Action::EnableOverlay));
+        assert!(matches!(normal("h", &["[", "o"]), Action::EnableHidden));
     }
 
     #[test]
     fn test_normal_mode_unimpaired_disable() {
-        assert!(matches!(handle_normal_key("]", ""), Action::Pending));
-        assert!(matches!(handle_normal_key("o", "]"), Action::Pending));
-        assert!(matches!(
-            handle_normal_key("o", "]o"),
-            Action::DisableOverlay
-        ));
-        assert!(matches!(
-            handle_normal_key("h", "]o"),
-            Action::DisableHidden
-        ));
+        assert!(matches!(normal("]", &[]), Action::Pending));
+        assert!(matches!(normal("o", &["]"]), Action::Pending));
+        assert!(matches!(normal("o", &["]", "o"]), Action::DisableOverlay));
+        assert!(matches!(normal("h", &["]", "o"]), Action::DisableHidden));
     }
 
     #[test]
     fn test_normal_mode_directory_navigation() {
-        assert!(matches!(handle_normal_key("d", "["), Action::PrevDirectory));
-        assert!(matches!(handle_normal_key("d", "]"), Action::NextDirectory));
+        assert!(matches!(normal("d", &["["]), Action::PrevDirectory));
+        assert!(matches!(normal("d", &["]"]), Action::NextDirectory));
     }
 
     #[test]
     fn test_normal_mode_split_commands() {
-        assert!(matches!(handle_normal_key("C-w", ""), Action::Pending));
-        assert!(matches!(handle_normal_key("h", "C-w"), Action::FocusLeft));
-        assert!(matches!(handle_normal_key("j", "C-w"), Action::FocusDown));
-        assert!(matches!(handle_normal_key("k", "C-w"), Action::FocusUp));
-        assert!(matches!(handle_normal_key("l", "C-w"), Action::FocusRight));
-        assert!(matches!(
-            handle_normal_key("v", "C-w"),
-            Action::SplitVertical
-        ));
-        assert!(matches!(
-            handle_normal_key("s", "C-w"),
-            Action::SplitHorizontal
-        ));
-        assert!(matches!(handle_normal_key("c", "C-w"), Action::CloseSplit));
-        assert!(matches!(handle_normal_key("q", "C-w"), Action::CloseSplit));
+        assert!(matches!(normal("C-w", &[]), Action::Pending));
+        assert!(matches!(normal("h", &["C-w"]), Action::FocusLeft));
+        assert!(matches!(normal("j", &["C-w"]), Action::FocusDown));
+        assert!(matches!(normal("k", &["C-w"]), Action::FocusUp));
+        assert!(matches!(normal("l", &["C-w"]), Action::FocusRight));
+        assert!(matches!(normal("v", &["C-w"]), Action::SplitVertical));
+        assert!(matches!(normal("s", &["C-w"]), Action::SplitHorizontal));
+        assert!(matches!(normal("c", &["C-w"]), Action::CloseSplit));
+        assert!(matches!(normal("q", &["C-w"]), Action::CloseSplit));
     }
 
     #[test]
     fn test_normal_mode_actions() {
-        assert!(matches!(handle_normal_key("d", ""), Action::Cut));
-        assert!(matches!(handle_normal_key("p", ""), Action::Paste));
-        assert!(matches!(handle_normal_key("x", ""), Action::Delete));
-        assert!(matches!(handle_normal_key(".", ""), Action::ToggleHidden));
-        assert!(matches!(
-            handle_normal_key(":", ""),
-            Action::EnterCommandMode
-        ));
-        assert!(matches!(
-            handle_normal_key("v", ""),
-            Action::EnterVisualMode
-        ));
-        assert!(matches!(handle_normal_key("=", ""), Action::OpenFile));
+        assert!(matches!(normal("p", &[]), Action::Paste));
+        assert!(matches!(normal(" ", &[]), Action::ToggleSelect));
+        assert!(matches!(normal(".", &[]), Action::ToggleHidden));
+        assert!(matches!(normal(":", &[]), Action::EnterCommandMode));
+        assert!(matches!(normal("v", &[]), Action::EnterVisualMode));
+        assert!(matches!(normal("=", &[]), Action::OpenFile));
     }
 
     #[test]
     fn test_visual_mode() {
-        assert!(matches!(handle_visual_key("j"), Action::MoveCursor(1)));
-        assert!(matches!(handle_visual_key("k"), Action::MoveCursor(-1)));
-        assert!(matches!(handle_visual_key("y"), Action::Yank));
-        assert!(matches!(handle_visual_key("d"), Action::Cut));
-        assert!(matches!(
-            handle_visual_key("\u{1b}"),
-            Action::ExitVisualMode
-        ));
-        assert!(matches!(handle_visual_key("v"), Action::ExitVisualMode));
+        assert!(matches!(visual("j"), Action::MoveCursor(1)));
+        assert!(matches!(visual("k"), Action::MoveCursor(-1)));
+        assert!(matches!(visual("y"), Action::Yank));
+        assert!(matches!(visual("d"), Action::Cut));
+        assert!(matches!(visual("x"), Action::Delete));
+        assert!(matches!(visual("X"), Action::Trash));
+        assert!(matches!(visual("o"), Action::VisualSwapAnchor));
+        assert!(matches!(visual("G"), Action::CursorToBottom));
+        assert!(matches!(visual("\u{1b}"), Action::ExitVisualMode));
+        assert!(matches!(visual("v"), Action::ExitVisualMode));
     }
 
     #[test]
@@ -491,6 +527,27 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_line_editing_keys() {
+        assert!(matches!(
+            handle_command_key("Left"),
+            Action::LineEdit(EditKey::Left)
+        ));
+        assert!(matches!(
+            handle_command_key("C-w"),
+            Action::LineEdit(EditKey::DeleteWord)
+        ));
+        assert!(matches!(
+            handle_command_key("\t"),
+            Action::LineEdit(EditKey::Complete)
+        ));
+        assert!(matches!(
+            handle_search_key("Up"),
+            Action::LineEdit(EditKey::HistoryPrev)
+        ));
+        assert!(matches!(handle_search_key("\t"), Action::None));
+    }
+
     #[test]
     fn test_command_mode_append() {
         match handle_command_key("a") {
@@ -509,41 +566,51 @@ mod tests {
 
     #[test]
     fn test_handle_key_dispatches_correctly() {
+        let keymap = Keymap::default();
+
         // Vim mode
         assert!(matches!(
-            handle_key(Mode::Normal, "j", "", true),
+            handle_key(&keymap, Mode::Normal, "j", &[], true),
             Action::MoveCursor(1)
         ));
         assert!(matches!(
-            handle_key(Mode::Visual, "y", "", true),
+            handle_key(&keymap, Mode::Visual, "y", &[], true),
             Action::Yank
         ));
         assert!(matches!(
-            handle_key(Mode::Command, "\n", "", true),
+            handle_key(&keymap, Mode::Command, "\n", &[], true),
             Action::CommandExecute
         ));
         assert!(matches!(
-            handle_key(Mode::Search, "\n", "", true),
+            handle_key(&keymap, Mode::Search, "\n", &[], true),
             Action::SearchExecute
         ));
 
         // Standard mode
         assert!(matches!(
-            handle_key(Mode::Normal, "Down", "", false),
+            handle_key(&keymap, Mode::Normal, "Down", &[], false),
             Action::MoveCursor(1)
         ));
         assert!(matches!(
-            handle_key(Mode::Normal, "Up", "", false),
+            handle_key(&keymap, Mode::Normal, "Up", &[], false),
             Action::MoveCursor(-1)
         ));
         assert!(matches!(
-            handle_key(Mode::Normal, "C-c", "", false),
+            handle_key(&keymap, Mode::Normal, "C-c", &[], false),
             Action::Yank
         ));
         assert!(matches!(
-            handle_key(Mode::Normal, "C-v", "", false),
+            handle_key(&keymap, Mode::Normal, "C-v", &[], false),
             Action::Paste
         ));
+        assert!(matches!(
+            handle_key(&keymap, Mode::Normal, "A-Left", &[], false),
+            Action::JumpBack
+        ));
+        assert!(matches!(
+            handle_key(&keymap, Mode::Normal, "C-z", &[], false),
+            Action::Undo
+        ));
     }
 
     #[test]
@@ -562,36 +629,40 @@ mod tests {
 
     #[test]
     fn test_normal_mode_search() {
-        assert!(matches!(
-            handle_normal_key("/", ""),
-            Action::EnterSearchMode
-        ));
-        assert!(matches!(handle_normal_key("n", ""), Action::SearchNext));
-        assert!(matches!(handle_normal_key("N", ""), Action::SearchPrev));
-        assert!(matches!(
-            handle_normal_key("C-l", ""),
-            Action::ClearSearchHighlight
-        ));
+        assert!(matches!(normal("/", &[]), Action::EnterSearchMode));
+        assert!(matches!(normal("n", &[]), Action::SearchNext));
+        assert!(matches!(normal("N", &[]), Action::SearchPrev));
+        assert!(matches!(normal("C-l", &[]), Action::ClearSearchHighlight));
     }
 
     #[test]
     fn test_normal_mode_bookmarks() {
-        assert!(matches!(handle_normal_key("m", ""), Action::Pending));
-        assert!(matches!(handle_normal_key("'", ""), Action::Pending));
-        match handle_normal_key("a", "m") {
+        assert!(matches!(normal("m", &[]), Action::Pending));
+        assert!(matches!(normal("'", &[]), Action::Pending));
+        match normal("a", &["m"]) {
             Action::SetMark(c) => assert_eq!(c, 'a'),
             _ => panic!("expected SetMark"),
         }
-        match handle_normal_key("z", "'") {
+        match normal("z", &["'"]) {
             Action::JumpToMark(c) => assert_eq!(c, 'z'),
             _ => panic!("expected JumpToMark"),
         }
+        assert!(matches!(normal("C-o", &[]), Action::JumpBack));
+        assert!(matches!(normal("C-i", &[]), Action::JumpForward));
+        assert!(matches!(normal("f", &["g"]), Action::RevealEntry));
+        assert!(matches!(normal("C-p", &[]), Action::FuzzyFind));
+    }
+
+    #[test]
+    fn test_normal_mode_undo() {
+        assert!(matches!(normal("u", &[]), Action::Undo));
+        assert!(matches!(normal("C-r", &[]), Action::Redo));
     }
 
     #[test]
     fn test_normal_mode_sorting() {
-        assert!(matches!(handle_normal_key("s", ""), Action::CycleSort));
-        assert!(matches!(handle_normal_key("S", ""), Action::ReverseSort));
+        assert!(matches!(normal("s", &[]), Action::CycleSort));
+        assert!(matches!(normal("S", &[]), Action::ReverseSort));
     }
 
     #[test]
@@ -609,17 +680,11 @@ mod tests {
 
     #[test]
     fn test_normal_mode_fold() {
-        assert!(matches!(handle_normal_key("z", ""), Action::Pending));
-        assert!(matches!(handle_normal_key("o", "z"), Action::FoldOpen));
-        assert!(matches!(handle_normal_key("c", "z"), Action::FoldClose));
-        assert!(matches!(handle_normal_key("a", "z"), Action::FoldToggle));
-        assert!(matches!(
-            handle_normal_key("O", "z"),
-            Action::FoldOpenRecursive
-        ));
-        assert!(matches!(
-            handle_normal_key("C", "z"),
-            Action::FoldCloseRecursive
-        ));
+        assert!(matches!(normal("z", &[]), Action::Pending));
+        assert!(matches!(normal("o", &["z"]), Action::FoldOpen));
+        assert!(matches!(normal("c", &["z"]), Action::FoldClose));
+        assert!(matches!(normal("a", &["z"]), Action::FoldToggle));
+        assert!(matches!(normal("O", &["z"]), Action::FoldOpenRecursive));
+        assert!(matches!(normal("C", &["z"]), Action::FoldCloseRecursive));
     }
 }
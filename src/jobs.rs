@@ -1,23 +1,244 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 pub type JobId = u64;
 
+/// Least time between progress updates from a running copy
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Clone, Debug)]
 pub enum JobStatus {
     Pending,
     Running,
     Complete,
-    Failed(String),
+    Failed(JobError),
+    Cancelled,
+}
+
+/// Why a job failed, kept structured so the error list can offer fixes
+#[derive(Clone, Debug)]
+pub struct JobError {
+    pub kind: std::io::ErrorKind,
+    pub message: String,
+    /// The path the error most likely concerns: the source when something
+    /// was not found, otherwise the destination
+    pub path: Option<PathBuf>,
+}
+
+impl JobError {
+    pub fn new(job: &JobKind, error: &std::io::Error) -> Self {
+        let kind = error.kind();
+        let path = if kind == std::io::ErrorKind::NotFound {
+            job.source().or(job.destination())
+        } else {
+            job.destination().or(job.source())
+        };
+        Self {
+            kind,
+            message: error.to_string(),
+            path: path.map(Path::to_path_buf),
+        }
+    }
+
+    fn other(message: String) -> Self {
+        Self {
+            kind: std::io::ErrorKind::Other,
+            message,
+            path: None,
+        }
+    }
+
+    /// A fix worth offering for this kind of error
+    pub fn suggestion(&self) -> Option<&'static str> {
+        use std::io::ErrorKind;
+        match self.kind {
+            ErrorKind::AlreadyExists => Some("R retries, replacing the destination"),
+            ErrorKind::PermissionDenied | ErrorKind::ReadOnlyFilesystem => {
+                Some("D retries with a different destination")
+            }
+            ErrorKind::StorageFull | ErrorKind::QuotaExceeded => {
+                Some("D retries with a destination on another device")
+            }
+            ErrorKind::NotFound => Some("the source is gone; x dismisses"),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for JobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}", path.display(), self.message),
+            None => f.write_str(&self.message),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum JobKind {
-    Copy { src: PathBuf, dest: PathBuf },
-    Move { src: PathBuf, dest: PathBuf },
-    Trash { path: PathBuf },
-    Extract { archive: PathBuf, dest: PathBuf },
+    /// `preserve` keeps timestamps, extended attributes and hard links.
+    /// Copies and moves replace an existing `dest` once they are complete.
+    Copy {
+        src: PathBuf,
+        dest: PathBuf,
+        preserve: bool,
+    },
+    Move {
+        src: PathBuf,
+        dest: PathBuf,
+    },
+    Trash {
+        path: PathBuf,
+    },
+    /// Permanent, recursive deletion
+    Delete {
+        path: PathBuf,
+    },
+    /// Move a trashed entry at `path` back to `dest`
+    Restore {
+        path: PathBuf,
+        dest: PathBuf,
+    },
+    /// `mode` is octal or relative, as accepted by `:chmod`
+    Chmod {
+        path: PathBuf,
+        mode: String,
+    },
+    /// Link at `dest` pointing to `src`
+    Symlink {
+        src: PathBuf,
+        dest: PathBuf,
+    },
+    Extract {
+        archive: PathBuf,
+        dest: PathBuf,
+    },
+    Mkdir {
+        path: PathBuf,
+        parents: bool,
+    },
+    Touch {
+        path: PathBuf,
+    },
+    Rename {
+        src: PathBuf,
+        dest: PathBuf,
+    },
+    /// A rename, link or restore made beside its destination and then put
+    /// in place of what is there, for retrying one that found it taken
+    Replace {
+        job: Box<JobKind>,
+    },
+    /// Parent grouping the jobs of one paste; never executed itself
+    Batch {
+        label: String,
+    },
+    Shell {
+        command: OsString,
+        cwd: PathBuf,
+    },
+    /// Search below `root`, sending what it finds as it goes
+    Find {
+        root: PathBuf,
+        query: filesystem::FindQuery,
+    },
+    /// Search the files below `root` for lines matching `regex`, sending
+    /// them as it goes
+    Grep {
+        root: PathBuf,
+        regex: regex::Regex,
+    },
+}
+
+/// Flags shared with a running job, checked between chunks of work
+#[derive(Clone, Debug, Default)]
+pub struct JobControl(Arc<ControlFlags>);
+
+#[derive(Debug, Default)]
+struct ControlFlags {
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+    /// Wakes jobs waiting on `cancelled` rather than checking the flag
+    cancel_notify: tokio::sync::Notify,
+}
+
+impl JobControl {
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Relaxed);
+        self.0.cancel_notify.notify_waiters();
+    }
+
+    /// Resolve once the job is cancelled, for jobs waiting on something
+    /// other than their own work
+    async fn cancelled(&self) {
+        loop {
+            // Registered before the check, so a cancel in between still wakes
+            let notified = self.0.cancel_notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.0.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.paused.load(Ordering::Relaxed)
+    }
+
+    /// Block while paused, failing once cancelled
+    fn checkpoint(&self) -> std::io::Result<()> {
+        loop {
+            if self.is_cancelled() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "cancelled",
+                ));
+            }
+            if !self.is_paused() {
+                return Ok(());
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+/// How far a copy, move or deletion has got
+#[derive(Clone, Debug, Default)]
+pub struct Progress {
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub files_done: u64,
+    pub files_total: u64,
+    /// Name of the file being copied
+    pub current: String,
+}
+
+impl Progress {
+    /// Fraction done, 0.0-1.0
+    pub fn fraction(&self) -> f32 {
+        if self.bytes_total == 0 {
+            return if self.files_total == 0 {
+                1.0
+            } else {
+                self.files_done as f32 / self.files_total as f32
+            };
+        }
+        (self.bytes_done as f64 / self.bytes_total as f64).min(1.0) as f32
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -26,59 +247,138 @@ pub struct Job {
     pub kind: JobKind,
     pub description: String,
     pub status: JobStatus,
-    pub progress: Option<f32>, // 0.0-1.0 for measurable, None for spinner
+    pub progress: Option<Progress>, // None for spinner
+    /// Captured stdout and stderr of shell commands
+    pub output: String,
     pub created_at: Instant,
+    pub started_at: Option<Instant>,
     pub completed_at: Option<Instant>,
+    pub control: JobControl,
+    /// Batch this job belongs to
+    pub parent: Option<JobId>,
+    /// Devices (`st_dev`) the job reads or writes, limiting how many jobs
+    /// run on each at once
+    pub devices: Vec<u64>,
+    /// Whether a batch lists its children in the task list
+    pub expanded: bool,
+}
+
+impl JobKind {
+    /// Path the job reads from
+    pub fn source(&self) -> Option<&Path> {
+        match self {
+            JobKind::Copy { src, .. } | JobKind::Move { src, .. } | JobKind::Rename { src, .. } => {
+                Some(src)
+            }
+            JobKind::Extract { archive, .. } => Some(archive),
+            JobKind::Trash { path } | JobKind::Delete { path } | JobKind::Chmod { path, .. } => {
+                Some(path)
+            }
+            JobKind::Symlink { src, .. } | JobKind::Restore { path: src, .. } => Some(src),
+            JobKind::Replace { job } => job.source(),
+            _ => None,
+        }
+    }
+
+    /// Path the job writes to
+    pub fn destination(&self) -> Option<&Path> {
+        match self {
+            JobKind::Copy { dest, .. }
+            | JobKind::Move { dest, .. }
+            | JobKind::Rename { dest, .. }
+            | JobKind::Symlink { dest, .. }
+            | JobKind::Restore { dest, .. }
+            | JobKind::Extract { dest, .. } => Some(dest),
+            JobKind::Mkdir { path, .. } | JobKind::Touch { path } => Some(path),
+            JobKind::Replace { job } => job.destination(),
+            _ => None,
+        }
+    }
+
+    /// Renames, new entries, mode changes and links take no time and can run
+    /// on the calling thread, in order
+    pub fn is_quick(&self) -> bool {
+        matches!(
+            self,
+            JobKind::Mkdir { .. }
+                | JobKind::Touch { .. }
+                | JobKind::Rename { .. }
+                | JobKind::Chmod { .. }
+                | JobKind::Symlink { .. }
+        )
+    }
+
+    /// The same job writing to `dest` instead, for kinds with a destination
+    /// separate from their source
+    pub fn with_destination(&self, dest: PathBuf) -> Option<JobKind> {
+        let mut kind = self.clone();
+        match &mut kind {
+            JobKind::Copy { dest: d, .. }
+            | JobKind::Move { dest: d, .. }
+            | JobKind::Rename { dest: d, .. }
+            | JobKind::Symlink { dest: d, .. }
+            | JobKind::Restore { dest: d, .. }
+            | JobKind::Extract { dest: d, .. } => *d = dest,
+            JobKind::Replace { job } => **job = job.with_destination(dest)?,
+            _ => return None,
+        }
+        Some(kind)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn describe(kind: &JobKind) -> String {
+    match kind {
+        JobKind::Copy { src, dest, .. } => {
+            format!("Copy {} -> {}", file_name(src), file_name(dest))
+        }
+        JobKind::Move { src, dest } => {
+            format!("Move {} -> {}", file_name(src), file_name(dest))
+        }
+        JobKind::Trash { path } => format!("Trash {}", file_name(path)),
+        JobKind::Extract { archive, .. } => format!("Extract {}", file_name(archive)),
+        JobKind::Delete { path } => format!("Delete {}", file_name(path)),
+        JobKind::Restore { dest, .. } => format!("Restore {}", dest.display()),
+        JobKind::Chmod { path, mode } => format!("Chmod {} {}", mode, file_name(path)),
+        JobKind::Symlink { src, dest } => {
+            format!("Link {} -> {}", file_name(dest), src.display())
+        }
+        JobKind::Mkdir { path, .. } => format!("Create directory {}", file_name(path)),
+        JobKind::Touch { path } => format!("Touch {}", file_name(path)),
+        JobKind::Rename { src, dest } => {
+            format!("Rename {} -> {}", file_name(src), file_name(dest))
+        }
+        JobKind::Shell { command, .. } => format!("!{}", command.to_string_lossy()),
+        JobKind::Find { root, query } => format!("Find {} in {}", query.text, root.display()),
+        JobKind::Grep { root, regex } => format!("Grep {} in {}", regex, root.display()),
+        JobKind::Replace { job } => format!("{} (replacing)", describe(job)),
+        JobKind::Batch { label } => label.clone(),
+    }
 }
 
 impl Job {
     pub fn new(id: JobId, kind: JobKind) -> Self {
-        let description = match &kind {
-            JobKind::Copy { src, dest } => {
-                let src_name = src
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default();
-                let dest_name = dest
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default();
-                format!("Copy {} -> {}", src_name, dest_name)
-            }
-            JobKind::Move { src, dest } => {
-                let src_name = src
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default();
-                let dest_name = dest
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default();
-                format!("Move {} -> {}", src_name, dest_name)
-            }
-            JobKind::Trash { path } => {
-                let name = path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default();
-                format!("Trash {}", name)
-            }
-            JobKind::Extract { archive, .. } => {
-                let name = archive
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default();
-                format!("Extract {}", name)
-            }
-        };
+        let description = describe(&kind);
+        let devices = job_devices(&kind);
         Self {
             id,
             kind,
             description,
             status: JobStatus::Pending,
             progress: None,
+            output: String::new(),
             created_at: Instant::now(),
+            started_at: None,
             completed_at: None,
+            control: JobControl::default(),
+            parent: None,
+            devices,
+            expanded: false,
         }
     }
 
@@ -93,44 +393,364 @@ impl Job {
     pub fn is_complete(&self) -> bool {
         matches!(self.status, JobStatus::Complete)
     }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.status, JobStatus::Cancelled)
+    }
+
+    pub fn is_batch(&self) -> bool {
+        matches!(self.kind, JobKind::Batch { .. })
+    }
+
+    /// Any job can be cancelled before it starts; once running, copies,
+    /// moves, trashing, deletion, extraction, searches and shell commands can
+    pub fn can_cancel(&self) -> bool {
+        matches!(self.status, JobStatus::Pending)
+            || self.is_active()
+                && matches!(
+                    self.kind,
+                    JobKind::Copy { .. }
+                        | JobKind::Move { .. }
+                        | JobKind::Trash { .. }
+                        | JobKind::Delete { .. }
+                        | JobKind::Extract { .. }
+                        | JobKind::Find { .. }
+                        | JobKind::Grep { .. }
+                        | JobKind::Shell { .. }
+                        | JobKind::Batch { .. }
+                )
+    }
+
+    /// Only copies, including moves across filesystems, and deletions can
+    /// be paused
+    pub fn can_pause(&self) -> bool {
+        self.is_active()
+            && matches!(
+                self.kind,
+                JobKind::Copy { .. }
+                    | JobKind::Move { .. }
+                    | JobKind::Delete { .. }
+                    | JobKind::Batch { .. }
+            )
+    }
+
+    /// Average bytes per second since the job started
+    pub fn throughput(&self) -> Option<f64> {
+        let elapsed = self.started_at?.elapsed().as_secs_f64();
+        let done = self.progress.as_ref()?.bytes_done;
+        (elapsed > 0.0 && done > 0).then(|| done as f64 / elapsed)
+    }
+
+    /// Time left at the average throughput so far
+    pub fn eta(&self) -> Option<Duration> {
+        let progress = self.progress.as_ref()?;
+        let remaining = progress.bytes_total.saturating_sub(progress.bytes_done);
+        Some(Duration::from_secs_f64(
+            remaining as f64 / self.throughput()?,
+        ))
+    }
 }
 
 /// Messages from background tasks to UI
 #[derive(Debug)]
 pub enum JobUpdate {
     Started(JobId),
-    Progress(JobId, f32),
+    Progress(JobId, Progress),
+    Output(JobId, String),
+    /// Entries a find job came across since its last update
+    Found(JobId, Vec<PathBuf>),
+    /// Lines a grep job matched since its last update
+    Matched(JobId, Vec<filesystem::GrepHit>),
     Complete(JobId),
-    Failed(JobId, String),
+    Failed(JobId, JobError),
+    Cancelled(JobId),
+}
+
+/// Device of `path`, or of its nearest existing ancestor for paths that
+/// are about to be created
+fn device_of(path: &Path) -> Option<u64> {
+    path.ancestors()
+        .find_map(|p| p.symlink_metadata().ok())
+        .map(|m| m.dev())
+}
+
+fn job_devices(kind: &JobKind) -> Vec<u64> {
+    let paths: Vec<&Path> = match kind {
+        JobKind::Copy { src, dest, .. } | JobKind::Move { src, dest } => vec![src, dest],
+        JobKind::Extract { archive, dest } => vec![archive, dest],
+        JobKind::Restore { path, dest } => vec![path, dest],
+        JobKind::Trash { path } | JobKind::Delete { path } => vec![path],
+        JobKind::Replace { job } => return job_devices(job),
+        _ => Vec::new(),
+    };
+    let mut devices: Vec<u64> = paths.into_iter().filter_map(device_of).collect();
+    devices.dedup();
+    devices
 }
 
 /// The job queue manager
+///
+/// Jobs start in submission order: a job waits while any device it uses
+/// has `max_per_device` jobs running, and so does every later job on that
+/// device. Jobs that touch no device start at once.
 pub struct JobQueue {
     jobs: Vec<Job>,
     next_id: JobId,
+    max_per_device: usize,
     update_rx: mpsc::Receiver<JobUpdate>,
     update_tx: mpsc::Sender<JobUpdate>,
+    /// Jobs completed since `take_completed` was last called
+    completed: Vec<Job>,
+    /// Results of find jobs since `take_found` was last called
+    found: Vec<(JobId, Vec<PathBuf>)>,
+    /// Results of grep jobs since `take_matched` was last called
+    matched: Vec<(JobId, Vec<filesystem::GrepHit>)>,
 }
 
 impl JobQueue {
-    pub fn new() -> Self {
+    pub fn new(max_per_device: usize) -> Self {
         let (update_tx, update_rx) = mpsc::channel(64);
         Self {
             jobs: Vec::new(),
             next_id: 0,
+            max_per_device: max_per_device.max(1),
             update_rx,
             update_tx,
+            completed: Vec::new(),
+            found: Vec::new(),
+            matched: Vec::new(),
         }
     }
 
     pub fn submit(&mut self, kind: JobKind) -> JobId {
+        self.submit_in(kind, None)
+    }
+
+    /// Queue a job, as part of a batch when `parent` is given. Children are
+    /// kept right after their batch so the group is scheduled together.
+    pub fn submit_in(&mut self, kind: JobKind, parent: Option<JobId>) -> JobId {
         let id = self.next_id;
         self.next_id += 1;
-        let job = Job::new(id, kind);
-        self.jobs.push(job);
+        let mut job = Job::new(id, kind);
+        let position = parent.and_then(|parent| {
+            let start = self.jobs.iter().position(|j| j.id == parent)?;
+            job.parent = Some(parent);
+            Some(
+                start
+                    + 1
+                    + self.jobs[start + 1..]
+                        .iter()
+                        .take_while(|j| j.parent == Some(parent))
+                        .count(),
+            )
+        });
+        match position {
+            Some(position) => self.jobs.insert(position, job),
+            None => self.jobs.push(job),
+        }
+        self.update_batches();
         id
     }
 
+    /// Mark the jobs that may start now as running and return them, in
+    /// queue order, for the caller to spawn
+    pub fn take_ready(&mut self) -> Vec<(JobId, JobKind, JobControl)> {
+        let mut running: HashMap<u64, usize> = HashMap::new();
+        for job in self
+            .jobs
+            .iter()
+            .filter(|j| matches!(j.status, JobStatus::Running))
+        {
+            for device in &job.devices {
+                *running.entry(*device).or_default() += 1;
+            }
+        }
+
+        let mut blocked: HashSet<u64> = HashSet::new();
+        let mut ready = Vec::new();
+        for job in &mut self.jobs {
+            if !matches!(job.status, JobStatus::Pending) || job.is_batch() {
+                continue;
+            }
+            let free = job.devices.iter().all(|device| {
+                !blocked.contains(device)
+                    && running.get(device).copied().unwrap_or(0) < self.max_per_device
+            });
+            if !free {
+                // Later jobs on the same devices wait their turn
+                blocked.extend(&job.devices);
+                continue;
+            }
+            for device in &job.devices {
+                *running.entry(*device).or_default() += 1;
+            }
+            job.status = JobStatus::Running;
+            job.started_at = Some(Instant::now());
+            ready.push((job.id, job.kind.clone(), job.control.clone()));
+        }
+        if !ready.is_empty() {
+            self.update_batches();
+        }
+        ready
+    }
+
+    /// Derive each batch's status and progress from its children
+    fn update_batches(&mut self) {
+        #[derive(Default)]
+        struct Children {
+            total: u64,
+            finished: u64,
+            running: Option<String>,
+            failed: u64,
+            cancelled: u64,
+        }
+        let mut children: HashMap<JobId, Children> = HashMap::new();
+        for job in &self.jobs {
+            let Some(parent) = job.parent else {
+                continue;
+            };
+            let entry = children.entry(parent).or_default();
+            entry.total += 1;
+            match &job.status {
+                JobStatus::Running => {
+                    entry.running.get_or_insert_with(|| job.description.clone());
+                }
+                JobStatus::Pending => {}
+                JobStatus::Complete => entry.finished += 1,
+                JobStatus::Failed(_) => {
+                    entry.finished += 1;
+                    entry.failed += 1;
+                }
+                JobStatus::Cancelled => {
+                    entry.finished += 1;
+                    entry.cancelled += 1;
+                }
+            }
+        }
+
+        for job in self.jobs.iter_mut().filter(|j| j.is_batch()) {
+            let Some(summary) = children.get(&job.id) else {
+                continue;
+            };
+            let was_active = job.is_active();
+            job.status = if summary.finished < summary.total {
+                if summary.running.is_some() {
+                    JobStatus::Running
+                } else {
+                    JobStatus::Pending
+                }
+            } else if summary.failed > 0 {
+                JobStatus::Failed(JobError::other(format!(
+                    "{} of {} failed",
+                    summary.failed, summary.total
+                )))
+            } else if summary.cancelled == summary.total {
+                JobStatus::Cancelled
+            } else {
+                JobStatus::Complete
+            };
+            if matches!(job.status, JobStatus::Running) && job.started_at.is_none() {
+                job.started_at = Some(Instant::now());
+            }
+            if was_active && !job.is_active() {
+                job.completed_at = Some(Instant::now());
+            }
+            job.progress = Some(Progress {
+                files_done: summary.finished,
+                files_total: summary.total,
+                current: summary.running.clone().unwrap_or_default(),
+                ..Progress::default()
+            });
+        }
+    }
+
+    /// Move a job, together with the rest of its batch, one place earlier
+    /// (`delta` < 0) or later in the queue
+    pub fn move_job(&mut self, id: JobId, delta: i32) -> bool {
+        let Some(job) = self.get(id) else {
+            return false;
+        };
+        let top = job.parent.unwrap_or(id);
+
+        // Each unit is a top-level job followed by its children
+        let mut units: Vec<(usize, usize)> = Vec::new();
+        for (i, job) in self.jobs.iter().enumerate() {
+            match units.last_mut() {
+                Some((_, end)) if job.parent.is_some() && *end == i => *end = i + 1,
+                _ => units.push((i, i + 1)),
+            }
+        }
+        let Some(index) = units
+            .iter()
+            .position(|(start, _)| self.jobs[*start].id == top)
+        else {
+            return false;
+        };
+        let other = if delta < 0 {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1).filter(|i| *i < units.len())
+        };
+        let Some(other) = other else {
+            return false;
+        };
+        let (first, second) = (index.min(other), index.max(other));
+        let (start, mid, end) = (units[first].0, units[first].1, units[second].1);
+        self.jobs[start..end].rotate_left(mid - start);
+        true
+    }
+
+    /// Drop a finished job from the lists
+    pub fn remove(&mut self, id: JobId) -> Option<Job> {
+        let index = self
+            .jobs
+            .iter()
+            .position(|j| j.id == id && !j.is_active())?;
+        let job = self.jobs.remove(index);
+        self.update_batches();
+        Some(job)
+    }
+
+    /// Show or hide a batch's children in the task list
+    pub fn toggle_expanded(&mut self, id: JobId) -> bool {
+        match self.jobs.iter_mut().find(|j| j.id == id && j.is_batch()) {
+            Some(job) => {
+                job.expanded = !job.expanded;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Id the next submitted job will get
+    pub fn next_id(&self) -> JobId {
+        self.next_id
+    }
+
+    /// Run a quick operation on the calling thread and record its outcome,
+    /// so failures show up in the error list like any other job
+    pub fn run_now(&mut self, kind: JobKind) -> Result<(), String> {
+        self.run_now_in(kind, None).1
+    }
+
+    /// Run a quick operation now as part of the batch `parent`, for steps
+    /// that must happen in order
+    pub fn run_now_in(
+        &mut self,
+        kind: JobKind,
+        parent: Option<JobId>,
+    ) -> (JobId, Result<(), String>) {
+        let id = self.submit_in(kind.clone(), parent);
+        let result = run_quick(&kind);
+        let update = match &result {
+            Ok(()) => JobUpdate::Complete(id),
+            Err(e) => JobUpdate::Failed(id, JobError::new(&kind, e)),
+        };
+        self.apply_update(update);
+        self.update_batches();
+        (id, result.map_err(|e| e.to_string()))
+    }
+
     pub fn get(&self, id: JobId) -> Option<&Job> {
         self.jobs.iter().find(|j| j.id == id)
     }
@@ -143,8 +763,9 @@ impl JobQueue {
         self.jobs.iter().filter(|j| j.is_complete())
     }
 
+    /// Failed jobs; a failed batch is represented by its failed children
     pub fn failed_jobs(&self) -> impl Iterator<Item = &Job> {
-        self.jobs.iter().filter(|j| j.is_failed())
+        self.jobs.iter().filter(|j| j.is_failed() && !j.is_batch())
     }
 
     pub fn has_active_jobs(&self) -> bool {
@@ -152,17 +773,40 @@ impl JobQueue {
     }
 
     pub fn active_count(&self) -> usize {
-        self.jobs.iter().filter(|j| j.is_active()).count()
+        self.jobs
+            .iter()
+            .filter(|j| j.is_active() && !j.is_batch())
+            .count()
     }
 
     pub fn failed_count(&self) -> usize {
-        self.jobs.iter().filter(|j| j.is_failed()).count()
+        self.failed_jobs().count()
+    }
+
+    /// Jobs that completed since the last call, in completion order
+    pub fn take_completed(&mut self) -> Vec<Job> {
+        std::mem::take(&mut self.completed)
+    }
+
+    /// Find results that arrived since the last call, in arrival order
+    pub fn take_found(&mut self) -> Vec<(JobId, Vec<PathBuf>)> {
+        std::mem::take(&mut self.found)
+    }
+
+    /// Grep results that arrived since the last call, in arrival order
+    pub fn take_matched(&mut self) -> Vec<(JobId, Vec<filesystem::GrepHit>)> {
+        std::mem::take(&mut self.matched)
     }
 
     /// Non-blocking poll for updates
     pub fn poll_updates(&mut self) {
+        let mut updated = false;
         while let Ok(update) = self.update_rx.try_recv() {
             self.apply_update(update);
+            updated = true;
+        }
+        if updated {
+            self.update_batches();
         }
     }
 
@@ -171,6 +815,7 @@ impl JobQueue {
             JobUpdate::Started(id) => {
                 if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
                     job.status = JobStatus::Running;
+                    job.started_at.get_or_insert_with(Instant::now);
                 }
             }
             JobUpdate::Progress(id, progress) => {
@@ -178,11 +823,24 @@ impl JobQueue {
                     job.progress = Some(progress);
                 }
             }
+            JobUpdate::Output(id, output) => {
+                if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+                    job.output = output;
+                }
+            }
+            JobUpdate::Found(id, paths) => self.found.push((id, paths)),
+            JobUpdate::Matched(id, hits) => self.matched.push((id, hits)),
             JobUpdate::Complete(id) => {
                 if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
                     job.status = JobStatus::Complete;
-                    job.progress = Some(1.0);
+                    if let Some(progress) = &mut job.progress {
+                        progress.bytes_done = progress.bytes_total;
+                        progress.files_done = progress.files_total;
+                    }
                     job.completed_at = Some(Instant::now());
+                    if !job.is_batch() {
+                        self.completed.push(job.clone());
+                    }
                 }
             }
             JobUpdate::Failed(id, msg) => {
@@ -191,19 +849,61 @@ impl JobQueue {
                     job.completed_at = Some(Instant::now());
                 }
             }
+            JobUpdate::Cancelled(id) => {
+                if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+                    job.status = JobStatus::Cancelled;
+                    job.completed_at = Some(Instant::now());
+                }
+            }
+        }
+    }
+
+    /// Ask a job to stop; it reports `Cancelled` once it has cleaned up
+    /// A job that has not started is cancelled at once. A batch cancels
+    /// each of its children.
+    pub fn cancel(&mut self, id: JobId) -> bool {
+        let mut cancelled = false;
+        for job in &mut self.jobs {
+            let targeted = job.id == id || job.parent == Some(id);
+            if !targeted || job.is_batch() || !job.can_cancel() {
+                continue;
+            }
+            if matches!(job.status, JobStatus::Pending) {
+                job.status = JobStatus::Cancelled;
+                job.completed_at = Some(Instant::now());
+            }
+            job.control.cancel();
+            cancelled = true;
         }
+        self.update_batches();
+        cancelled
+    }
+
+    /// Pause or resume a job, or every copy in a batch, returning whether
+    /// it is now paused
+    pub fn toggle_pause(&mut self, id: JobId) -> Option<bool> {
+        let job = self.jobs.iter().find(|j| j.id == id && j.can_pause())?;
+        let paused = !job.control.is_paused();
+        for job in &self.jobs {
+            if job.id == id || job.parent == Some(id) && job.can_pause() {
+                job.control.set_paused(paused);
+            }
+        }
+        Some(paused)
     }
 
     pub fn sender(&self) -> mpsc::Sender<JobUpdate> {
         self.update_tx.clone()
     }
 
-    /// Clear completed jobs older than the given duration
+    /// Clear completed jobs older than the given duration, keeping those
+    /// with output that has not been read yet
     pub fn clear_completed(&mut self, max_age_secs: u64) {
         let now = Instant::now();
         self.jobs.retain(|job| {
             if let Some(completed) = job.completed_at
-                && job.is_complete()
+                && (job.is_complete() || job.is_cancelled())
+                && job.output.is_empty()
                 && now.duration_since(completed).as_secs() > max_age_secs
             {
                 return false;
@@ -222,111 +922,392 @@ impl JobQueue {
 
 use crate::filesystem;
 
-pub async fn execute_job(id: JobId, kind: JobKind, tx: mpsc::Sender<JobUpdate>) {
+pub async fn execute_job(
+    id: JobId,
+    kind: JobKind,
+    control: JobControl,
+    tx: mpsc::Sender<JobUpdate>,
+) {
     let _ = tx.send(JobUpdate::Started(id)).await;
 
+    let job = kind.clone();
     let result = match kind {
-        JobKind::Copy { src, dest } => copy_with_progress(&src, &dest, id, &tx).await,
-        JobKind::Move { src, dest } => move_file(&src, &dest).await,
-        JobKind::Trash { path } => trash_file(&path).await,
-        JobKind::Extract { archive, dest } => extract_archive(&archive, &dest).await,
+        JobKind::Copy {
+            src,
+            dest,
+            preserve,
+        } => {
+            let staged = filesystem::staging_path(&dest);
+            let target = staged.as_deref().unwrap_or(&dest);
+            let result = copy_with_progress(&src, target, preserve, id, &control, &tx).await;
+            finish_staged(result, staged, dest).await
+        }
+        JobKind::Move { src, dest } => move_file(&src, &dest, id, &control, &tx).await,
+        JobKind::Trash { path } => trash_file(&path, &control).await,
+        JobKind::Delete { path } => delete_with_progress(&path, id, &control, &tx).await,
+        JobKind::Extract { archive, dest } => extract_archive(&archive, &dest, &control).await,
+        JobKind::Shell { command, cwd } => run_shell(command, cwd, id, &control, &tx).await,
+        JobKind::Find { root, query } => find_entries(root, query, id, &control, &tx).await,
+        JobKind::Grep { root, regex } => grep_files(root, regex, id, &control, &tx).await,
+        JobKind::Replace { job } => tokio::task::spawn_blocking(move || run_replacing(&job))
+            .await
+            .map_err(std::io::Error::other)
+            .and_then(|result| result),
+        // Batches only group other jobs and are never scheduled
+        JobKind::Batch { .. } => Ok(()),
+        kind @ (JobKind::Mkdir { .. }
+        | JobKind::Touch { .. }
+        | JobKind::Rename { .. }
+        | JobKind::Chmod { .. }
+        | JobKind::Symlink { .. }
+        | JobKind::Restore { .. }) => tokio::task::spawn_blocking(move || run_quick(&kind))
+            .await
+            .map_err(std::io::Error::other)
+            .and_then(|result| result),
     };
 
     match result {
         Ok(()) => {
             let _ = tx.send(JobUpdate::Complete(id)).await;
         }
+        Err(_) if control.is_cancelled() => {
+            let _ = tx.send(JobUpdate::Cancelled(id)).await;
+        }
         Err(e) => {
-            let _ = tx.send(JobUpdate::Failed(id, e.to_string())).await;
+            let _ = tx
+                .send(JobUpdate::Failed(id, JobError::new(&job, &e)))
+                .await;
         }
     }
 }
 
-async fn copy_with_progress(
-    src: &Path,
-    dest: &Path,
+/// Operations that finish fast enough to run without progress reporting
+fn run_quick(kind: &JobKind) -> std::io::Result<()> {
+    match kind {
+        JobKind::Mkdir { path, parents } => filesystem::create_directory(path, *parents),
+        JobKind::Touch { path } => filesystem::touch(path),
+        JobKind::Rename { src, dest } => filesystem::rename(src, dest),
+        JobKind::Chmod { path, mode } => filesystem::chmod(path, mode),
+        JobKind::Symlink { src, dest } => filesystem::create_symlink(src, dest),
+        JobKind::Restore { path, dest } => filesystem::restore(path, dest),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "not a quick operation",
+        )),
+    }
+}
+
+/// Run a quick job writing beside its destination, then swap what it made
+/// in for what was there; a failed swap puts a renamed or restored entry
+/// back where it came from
+fn run_replacing(kind: &JobKind) -> std::io::Result<()> {
+    let staged = kind.destination().and_then(filesystem::staging_path);
+    let (Some(staged), Some(dest)) = (staged, kind.destination()) else {
+        return run_quick(kind);
+    };
+    let staged_kind = kind
+        .with_destination(staged.clone())
+        .expect("a job with a destination can write elsewhere");
+    run_quick(&staged_kind)?;
+    let result = filesystem::replace(&staged, dest);
+    if result.is_err() {
+        match kind {
+            JobKind::Rename { src, .. } | JobKind::Restore { path: src, .. } => {
+                let _ = std::fs::rename(&staged, src);
+            }
+            _ => {
+                let _ = filesystem::delete(&staged);
+            }
+        }
+    }
+    result
+}
+
+/// Run a command with `sh -c`, sending its combined output as it comes,
+/// at most every `PROGRESS_INTERVAL`, and all of it before the result.
+/// Cancelling kills the command.
+async fn run_shell(
+    command: OsString,
+    cwd: PathBuf,
     id: JobId,
+    control: &JobControl,
     tx: &mpsc::Sender<JobUpdate>,
 ) -> std::io::Result<()> {
-    use tokio::fs;
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
-
-    if src.is_dir() {
-        // Directory copy - use blocking operation
-        let src = src.to_path_buf();
-        let dest = dest.to_path_buf();
-        tokio::task::spawn_blocking(move || filesystem::copy_file(&src, &dest))
-            .await
-            .map_err(std::io::Error::other)??;
-        Ok(())
-    } else {
-        // File copy with byte-level progress
-        let metadata = fs::metadata(src).await?;
-        let total_bytes = metadata.len();
+    use std::process::Stdio;
+    use tokio::io::AsyncReadExt;
 
-        let mut src_file = fs::File::open(src).await?;
-        let mut dest_file = fs::File::create(dest).await?;
+    let cancelled = || std::io::Error::new(std::io::ErrorKind::Interrupted, "cancelled");
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .current_dir(&cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
 
-        let mut copied = 0u64;
-        let mut buffer = vec![0u8; 64 * 1024]; // 64KB buffer
-        let mut last_progress = 0.0f32;
+    let mut output = Vec::new();
+    let (mut out_buf, mut err_buf) = ([0; 4096], [0; 4096]);
+    let (mut out_open, mut err_open) = (true, true);
+    let mut last_sent: Option<Instant> = None;
+    while out_open || err_open {
+        tokio::select! {
+            read = stdout.read(&mut out_buf), if out_open => match read? {
+                0 => out_open = false,
+                n => output.extend_from_slice(&out_buf[..n]),
+            },
+            read = stderr.read(&mut err_buf), if err_open => match read? {
+                0 => err_open = false,
+                n => output.extend_from_slice(&err_buf[..n]),
+            },
+            () = control.cancelled() => return Err(cancelled()),
+        }
+        if !output.is_empty() && last_sent.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL) {
+            last_sent = Some(Instant::now());
+            let text = String::from_utf8_lossy(&output).into_owned();
+            let _ = tx.try_send(JobUpdate::Output(id, text));
+        }
+    }
+    let status = tokio::select! {
+        status = child.wait() => status?,
+        () = control.cancelled() => return Err(cancelled()),
+    };
 
-        loop {
-            let n = src_file.read(&mut buffer).await?;
-            if n == 0 {
-                break;
-            }
+    if !output.is_empty() {
+        let text = String::from_utf8_lossy(&output).into_owned();
+        let _ = tx.send(JobUpdate::Output(id, text)).await;
+    }
 
-            dest_file.write_all(&buffer[..n]).await?;
-            copied += n as u64;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(status.to_string()))
+    }
+}
 
-            // Only send progress updates when significant change (>1%)
-            let progress = if total_bytes > 0 {
-                copied as f32 / total_bytes as f32
-            } else {
-                1.0
+/// Copy after counting what there is to copy, reporting progress from the
+/// blocking thread at most every `PROGRESS_INTERVAL`
+async fn copy_with_progress(
+    src: &Path,
+    dest: &Path,
+    preserve: bool,
+    id: JobId,
+    control: &JobControl,
+    tx: &mpsc::Sender<JobUpdate>,
+) -> std::io::Result<()> {
+    let src = src.to_path_buf();
+    let dest = dest.to_path_buf();
+    let control = control.clone();
+    let tx = tx.clone();
+    tokio::task::spawn_blocking(move || {
+        control.checkpoint()?;
+        let (bytes_total, files_total) = filesystem::scan_tree(&src)?;
+        let mut last_sent: Option<Instant> = None;
+        filesystem::copy_file_with_progress(&src, &dest, preserve, &mut |copied| {
+            control.checkpoint()?;
+            if last_sent.is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL) {
+                return Ok(());
+            }
+            last_sent = Some(Instant::now());
+            let progress = Progress {
+                bytes_done: copied.bytes,
+                bytes_total,
+                files_done: copied.files,
+                files_total,
+                current: file_name(&copied.current),
             };
-            if progress - last_progress > 0.01 {
-                let _ = tx.send(JobUpdate::Progress(id, progress)).await;
-                last_progress = progress;
+            // Progress is dropped rather than waited on when the UI lags
+            let _ = tx.try_send(JobUpdate::Progress(id, progress));
+            Ok(())
+        })
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// Swap a copy or move written under a staging name in for what was at
+/// `dest`, or clean up after it when it failed
+async fn finish_staged(
+    result: std::io::Result<()>,
+    staged: Option<PathBuf>,
+    dest: PathBuf,
+) -> std::io::Result<()> {
+    let Some(staged) = staged else {
+        return result;
+    };
+    tokio::task::spawn_blocking(move || match result {
+        Ok(()) => filesystem::replace(&staged, &dest),
+        Err(e) => {
+            if staged.symlink_metadata().is_ok() {
+                let _ = filesystem::delete(&staged);
             }
+            Err(e)
         }
-
-        Ok(())
-    }
+    })
+    .await
+    .map_err(std::io::Error::other)?
 }
 
-async fn move_file(src: &Path, dest: &Path) -> std::io::Result<()> {
+async fn move_file(
+    src: &Path,
+    dest: &Path,
+    id: JobId,
+    control: &JobControl,
+    tx: &mpsc::Sender<JobUpdate>,
+) -> std::io::Result<()> {
+    let staged = filesystem::staging_path(dest);
+    let target = staged.as_deref().unwrap_or(dest);
     // Try rename first (fast, same filesystem)
-    match tokio::fs::rename(src, dest).await {
-        Ok(()) => Ok(()),
+    match tokio::fs::rename(src, target).await {
+        Ok(()) => finish_staged(Ok(()), staged, dest.to_path_buf()).await,
         Err(_) => {
-            // Fall back to copy + delete (cross-filesystem)
+            // Fall back to copy + delete (cross-filesystem), the source only
+            // going once the copy is in place
+            let copied = copy_with_progress(src, target, true, id, control, tx).await;
+            finish_staged(copied, staged, dest.to_path_buf()).await?;
             let src = src.to_path_buf();
-            let dest = dest.to_path_buf();
-            tokio::task::spawn_blocking(move || {
-                filesystem::copy_file(&src, &dest)?;
-                filesystem::delete(&src)
-            })
-            .await
-            .map_err(std::io::Error::other)?
+            tokio::task::spawn_blocking(move || filesystem::delete(&src))
+                .await
+                .map_err(std::io::Error::other)?
         }
     }
 }
 
-async fn trash_file(path: &Path) -> std::io::Result<()> {
+/// Delete a tree, counting removed files against a scan made first.
+/// Cancelling stops between entries, keeping what is left.
+async fn delete_with_progress(
+    path: &Path,
+    id: JobId,
+    control: &JobControl,
+    tx: &mpsc::Sender<JobUpdate>,
+) -> std::io::Result<()> {
+    let path = path.to_path_buf();
+    let control = control.clone();
+    let tx = tx.clone();
+    tokio::task::spawn_blocking(move || {
+        control.checkpoint()?;
+        let (_, files_total) = filesystem::scan_tree(&path)?;
+        let mut files_done = 0;
+        let mut last_sent: Option<Instant> = None;
+        filesystem::delete_with_progress(&path, &mut |removed, meta| {
+            if meta.is_file() {
+                files_done += 1;
+            }
+            control.checkpoint()?;
+            if last_sent.is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL) {
+                return Ok(());
+            }
+            last_sent = Some(Instant::now());
+            let progress = Progress {
+                files_done,
+                files_total,
+                current: file_name(removed),
+                ..Progress::default()
+            };
+            let _ = tx.try_send(JobUpdate::Progress(id, progress));
+            Ok(())
+        })?;
+        // Deleting from the trash view also drops the entry's record
+        filesystem::forget_trashed(&path)
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// Walk for a find job, sending results in batches at most every
+/// `PROGRESS_INTERVAL`. Unlike progress, results are never dropped.
+async fn find_entries(
+    root: PathBuf,
+    query: filesystem::FindQuery,
+    id: JobId,
+    control: &JobControl,
+    tx: &mpsc::Sender<JobUpdate>,
+) -> std::io::Result<()> {
+    let control = control.clone();
+    let tx = tx.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut batch = Vec::new();
+        let mut last_sent = Instant::now();
+        let send = |batch: &mut Vec<PathBuf>| {
+            let paths = std::mem::take(batch);
+            tx.blocking_send(JobUpdate::Found(id, paths))
+                .map_err(std::io::Error::other)
+        };
+        filesystem::find(&root, &query, &|| control.is_cancelled(), &mut |path| {
+            batch.push(path);
+            if last_sent.elapsed() >= PROGRESS_INTERVAL {
+                last_sent = Instant::now();
+                send(&mut batch)?;
+            }
+            Ok(())
+        })?;
+        if batch.is_empty() {
+            return Ok(());
+        }
+        send(&mut batch)
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// Search for a grep job, sending matches in batches as `find_entries`
+/// sends what it finds
+async fn grep_files(
+    root: PathBuf,
+    regex: regex::Regex,
+    id: JobId,
+    control: &JobControl,
+    tx: &mpsc::Sender<JobUpdate>,
+) -> std::io::Result<()> {
+    let control = control.clone();
+    let tx = tx.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut batch = Vec::new();
+        let mut last_sent = Instant::now();
+        let send = |batch: &mut Vec<filesystem::GrepHit>| {
+            let hits = std::mem::take(batch);
+            tx.blocking_send(JobUpdate::Matched(id, hits))
+                .map_err(std::io::Error::other)
+        };
+        filesystem::grep(&root, &regex, &|| control.is_cancelled(), &mut |hit| {
+            batch.push(hit);
+            if last_sent.elapsed() >= PROGRESS_INTERVAL {
+                last_sent = Instant::now();
+                send(&mut batch)?;
+            }
+            Ok(())
+        })?;
+        if batch.is_empty() {
+            return Ok(());
+        }
+        send(&mut batch)
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+async fn trash_file(path: &Path, control: &JobControl) -> std::io::Result<()> {
+    // Trashing is a single rename or command, so it can only be cancelled
+    // before it starts
+    control.checkpoint()?;
     let path = path.to_path_buf();
     tokio::task::spawn_blocking(move || filesystem::trash(&path))
         .await
         .map_err(std::io::Error::other)?
 }
 
-async fn extract_archive(archive: &Path, dest: &Path) -> std::io::Result<()> {
+async fn extract_archive(archive: &Path, dest: &Path, control: &JobControl) -> std::io::Result<()> {
     let archive = archive.to_path_buf();
     let dest = dest.to_path_buf();
-    tokio::task::spawn_blocking(move || filesystem::extract_archive(&archive, &dest))
-        .await
-        .map_err(std::io::Error::other)?
+    let control = control.clone();
+    tokio::task::spawn_blocking(move || {
+        filesystem::extract_archive(&archive, &dest, &|| control.is_cancelled())
+    })
+    .await
+    .map_err(std::io::Error::other)?
 }
 
 // ==================== Task List and Error List Panes ====================
@@ -335,6 +1316,10 @@ pub struct TaskListPane {
     pub cursor: usize,
     pub visible: bool,
     pub scroll_offset: usize,
+    /// Showing the output of the job under the cursor instead of the list
+    pub showing_detail: bool,
+    /// `d` was pressed; a second `d` cancels the job under the cursor
+    pub pending_cancel: bool,
 }
 
 impl TaskListPane {
@@ -343,11 +1328,14 @@ impl TaskListPane {
             cursor: 0,
             visible: false,
             scroll_offset: 0,
+            showing_detail: false,
+            pending_cancel: false,
         }
     }
 
     pub fn toggle(&mut self) {
         self.visible = !self.visible;
+        self.showing_detail = false;
     }
 
     pub fn show(&mut self) {
@@ -356,6 +1344,11 @@ impl TaskListPane {
 
     pub fn hide(&mut self) {
         self.visible = false;
+        self.showing_detail = false;
+    }
+
+    pub fn toggle_detail(&mut self) {
+        self.showing_detail = !self.showing_detail;
     }
 }
 
@@ -363,6 +1356,8 @@ pub struct ErrorListPane {
     pub cursor: usize,
     pub visible: bool,
     pub scroll_offset: usize,
+    /// Showing the output of the job under the cursor instead of the list
+    pub showing_detail: bool,
 }
 
 impl ErrorListPane {
@@ -371,11 +1366,13 @@ impl ErrorListPane {
             cursor: 0,
             visible: false,
             scroll_offset: 0,
+            showing_detail: false,
         }
     }
 
     pub fn toggle(&mut self) {
         self.visible = !self.visible;
+        self.showing_detail = false;
     }
 
     pub fn show(&mut self) {
@@ -384,5 +1381,225 @@ impl ErrorListPane {
 
     pub fn hide(&mut self) {
         self.visible = false;
+        self.showing_detail = false;
+    }
+
+    pub fn toggle_detail(&mut self) {
+        self.showing_detail = !self.showing_detail;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn copy(name: &str) -> JobKind {
+        let dir = std::env::temp_dir();
+        JobKind::Copy {
+            src: dir.join(name),
+            dest: dir.join(format!("{name}.copy")),
+            preserve: false,
+        }
+    }
+
+    fn ids(queue: &JobQueue) -> Vec<JobId> {
+        queue.all_jobs().iter().map(|j| j.id).collect()
+    }
+
+    #[test]
+    fn test_jobs_on_a_busy_device_wait_in_order() {
+        let mut queue = JobQueue::new(1);
+        let first = queue.submit(copy("a"));
+        let second = queue.submit(copy("b"));
+        let shell = queue.submit(JobKind::Shell {
+            command: "true".into(),
+            cwd: PathBuf::from("/"),
+        });
+
+        let started: Vec<JobId> = queue.take_ready().iter().map(|(id, ..)| *id).collect();
+        assert_eq!(started, vec![first, shell]);
+        assert!(queue.take_ready().is_empty());
+
+        queue.apply_update(JobUpdate::Complete(first));
+        let started: Vec<JobId> = queue.take_ready().iter().map(|(id, ..)| *id).collect();
+        assert_eq!(started, vec![second]);
+    }
+
+    #[test]
+    fn test_batches_move_as_a_unit() {
+        let mut queue = JobQueue::new(1);
+        let single = queue.submit(copy("a"));
+        let batch = queue.submit(JobKind::Batch {
+            label: "Paste 2 items".into(),
+        });
+        let child_b = queue.submit_in(copy("b"), Some(batch));
+        let later = queue.submit(copy("d"));
+        // Children stay with their batch even when submitted later
+        let child_c = queue.submit_in(copy("c"), Some(batch));
+        assert_eq!(ids(&queue), vec![single, batch, child_b, child_c, later]);
+
+        assert!(queue.move_job(child_c, -1));
+        assert_eq!(ids(&queue), vec![batch, child_b, child_c, single, later]);
+        assert!(!queue.move_job(batch, -1));
+
+        assert!(queue.cancel(batch));
+        assert!(matches!(
+            queue.get(batch).unwrap().status,
+            JobStatus::Cancelled
+        ));
+    }
+
+    #[test]
+    fn test_failures_point_at_the_likely_path() {
+        let kind = copy("a");
+        let missing = JobError::new(&kind, &std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(missing.path.as_deref(), kind.source());
+        let exists = JobError::new(
+            &kind,
+            &std::io::Error::from(std::io::ErrorKind::AlreadyExists),
+        );
+        assert_eq!(exists.path.as_deref(), kind.destination());
+        assert!(exists.suggestion().is_some());
+
+        let retargeted = kind.with_destination(PathBuf::from("/elsewhere")).unwrap();
+        assert_eq!(retargeted.destination(), Some(Path::new("/elsewhere")));
+        assert!(
+            JobKind::Touch {
+                path: PathBuf::from("/t")
+            }
+            .with_destination(PathBuf::from("/u"))
+            .is_none()
+        );
+    }
+
+    /// Run a job to completion, returning its last update
+    fn run(kind: JobKind) -> JobUpdate {
+        run_cancelled(kind, false).pop().unwrap()
+    }
+
+    /// Run a job to completion, cancelling it as soon as it starts when
+    /// `cancel` is set, returning every update
+    fn run_cancelled(kind: JobKind, cancel: bool) -> Vec<JobUpdate> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let (tx, mut rx) = mpsc::channel(16);
+        let control = JobControl::default();
+        runtime.block_on(async {
+            let canceller = async {
+                if cancel {
+                    tokio::task::yield_now().await;
+                    control.cancel();
+                }
+            };
+            tokio::join!(execute_job(1, kind, control.clone(), tx), canceller);
+        });
+        let mut updates = Vec::new();
+        while let Ok(update) = rx.try_recv() {
+            updates.push(update);
+        }
+        updates
+    }
+
+    #[test]
+    fn test_shell_output_and_cancel() {
+        let shell = |command: &str| JobKind::Shell {
+            command: command.into(),
+            cwd: PathBuf::from("/"),
+        };
+        let updates = run_cancelled(shell("echo out; echo err >&2"), false);
+        assert!(updates.iter().any(|update| matches!(
+            update,
+            JobUpdate::Output(_, text) if text.contains("out") && text.contains("err")
+        )));
+        assert!(matches!(updates.last(), Some(JobUpdate::Complete(_))));
+
+        let started = Instant::now();
+        let updates = run_cancelled(shell("sleep 30"), true);
+        assert!(matches!(updates.last(), Some(JobUpdate::Cancelled(_))));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_replace_retries_a_rename_over_its_destination() {
+        use std::fs;
+
+        let dir = TempDir::new("jobs-replace");
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::create_dir_all(dir.join("b/old")).unwrap();
+        let rename = JobKind::Rename {
+            src: dir.join("a"),
+            dest: dir.join("b"),
+        };
+        assert!(matches!(run(rename.clone()), JobUpdate::Failed(..)));
+        let job = Box::new(rename);
+        assert!(matches!(
+            run(JobKind::Replace { job }),
+            JobUpdate::Complete(_)
+        ));
+        assert_eq!(fs::read_to_string(dir.join("b")).unwrap(), "a");
+        assert!(!dir.join("a").exists());
+        // Nothing staged is left behind
+        assert_eq!(fs::read_dir(&*dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_overwriting_replaces_the_destination() {
+        use std::fs;
+
+        let dir = TempDir::new("jobs-overwrite");
+        let copy = |src: &str, dest: &str| JobKind::Copy {
+            src: dir.join(src),
+            dest: dir.join(dest),
+            preserve: false,
+        };
+        let moved = |src: &str, dest: &str| JobKind::Move {
+            src: dir.join(src),
+            dest: dir.join(dest),
+        };
+        fs::write(dir.join("file"), "new").unwrap();
+        fs::create_dir_all(dir.join("tree")).unwrap();
+        fs::write(dir.join("tree/new"), "").unwrap();
+
+        // A symlink is replaced, not written through
+        fs::write(dir.join("target"), "kept").unwrap();
+        std::os::unix::fs::symlink(dir.join("target"), dir.join("link")).unwrap();
+        assert!(matches!(run(copy("file", "link")), JobUpdate::Complete(_)));
+        assert!(!fs::symlink_metadata(dir.join("link")).unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(dir.join("link")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(dir.join("target")).unwrap(), "kept");
+
+        // A directory is replaced, not merged into
+        fs::create_dir_all(dir.join("old_tree")).unwrap();
+        fs::write(dir.join("old_tree/old"), "").unwrap();
+        assert!(matches!(
+            run(copy("tree", "old_tree")),
+            JobUpdate::Complete(_)
+        ));
+        assert!(dir.join("old_tree/new").exists());
+        assert!(!dir.join("old_tree/old").exists());
+
+        // Either may be a file where the other is a directory
+        assert!(matches!(
+            run(copy("file", "old_tree")),
+            JobUpdate::Complete(_)
+        ));
+        assert!(dir.join("old_tree").is_file());
+        assert!(matches!(
+            run(moved("tree", "old_tree")),
+            JobUpdate::Complete(_)
+        ));
+        assert!(dir.join("old_tree/new").exists());
+        assert!(!dir.join("tree").exists());
+
+        // Nothing is left under a staging name
+        let mut names: Vec<_> = fs::read_dir(&*dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["file", "link", "old_tree", "target"]);
     }
 }
@@ -0,0 +1,694 @@
+//! Undo and redo of file operations
+//!
+//! Completed moves, renames, trashing, links, new directories and mode
+//! changes are recorded as operations, the jobs of one batch as one step.
+//! Undoing first checks that what a step left behind is still there as it
+//! was, then hands back the jobs that reverse it. Each job is checked
+//! against the disk as the jobs before it will leave it, so swaps and
+//! chains of renames reverse in one go. Jobs queued by undo are not
+//! recorded; jobs queued by redo are, without clearing the redo stack.
+
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::filesystem;
+use crate::jobs::{JobId, JobKind, JobQueue};
+
+/// Most steps kept for undo
+const MAX_STEPS: usize = 100;
+
+/// Identifies an entry, to notice when it is replaced or modified
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stamp {
+    ino: u64,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl Stamp {
+    fn of(path: &Path) -> Option<Self> {
+        let meta = path.symlink_metadata().ok()?;
+        Some(Self {
+            ino: meta.ino(),
+            len: meta.len(),
+            modified: meta.modified().ok(),
+        })
+    }
+}
+
+/// A reversible file operation
+#[derive(Clone, Debug)]
+pub enum Operation {
+    /// A move or, with `rename`, a rename within one filesystem; `stamp`
+    /// is what arrived at `dest`
+    Move {
+        src: PathBuf,
+        dest: PathBuf,
+        stamp: Option<Stamp>,
+        rename: bool,
+    },
+    Trash {
+        path: PathBuf,
+    },
+    Symlink {
+        src: PathBuf,
+        dest: PathBuf,
+    },
+    Mkdir {
+        path: PathBuf,
+    },
+    Chmod {
+        path: PathBuf,
+        before: u32,
+        after: u32,
+    },
+}
+
+impl Operation {
+    /// The operations a completed job performed that can be undone, given
+    /// what `Journal::job_started` noted before it ran
+    fn from_job(kind: &JobKind, before: Option<Before>) -> Vec<Self> {
+        match (kind, before) {
+            (JobKind::Mkdir { .. }, Some(Before::Missing(paths))) => paths
+                .into_iter()
+                .map(|path| Operation::Mkdir { path })
+                .collect(),
+            (JobKind::Chmod { path, .. }, Some(Before::Mode(before))) => mode_of(path)
+                .map(|after| Operation::Chmod {
+                    path: path.clone(),
+                    before,
+                    after,
+                })
+                .into_iter()
+                .collect(),
+            (kind, _) => Self::from_plain_job(kind).into_iter().collect(),
+        }
+    }
+
+    fn from_plain_job(kind: &JobKind) -> Option<Self> {
+        Some(match kind {
+            JobKind::Move { src, dest } | JobKind::Rename { src, dest } => Operation::Move {
+                src: src.clone(),
+                dest: dest.clone(),
+                stamp: Stamp::of(dest),
+                rename: matches!(kind, JobKind::Rename { .. }),
+            },
+            JobKind::Trash { path } => Operation::Trash { path: path.clone() },
+            JobKind::Symlink { src, dest } => Operation::Symlink {
+                src: src.clone(),
+                dest: dest.clone(),
+            },
+            JobKind::Mkdir { path, .. } => Operation::Mkdir { path: path.clone() },
+            // What was replaced is gone for good; undo only takes back the job
+            JobKind::Replace { job } => return Self::from_plain_job(job),
+            _ => return None,
+        })
+    }
+
+    /// The job reversing this operation, after checking nothing changed;
+    /// `disk` is the disk as the jobs before will leave it, and takes in
+    /// what this one changes
+    fn undo(&self, disk: &mut Planned) -> Result<JobKind, String> {
+        match self {
+            Operation::Move {
+                src,
+                dest,
+                stamp,
+                rename,
+            } => {
+                let now = disk.on_disk(dest).and_then(|path| Stamp::of(&path));
+                if now.is_none() || now != *stamp {
+                    return Err(format!("{} changed since it was moved", dest.display()));
+                }
+                disk.ensure_free(src)?;
+                disk.push(Change::Moved {
+                    from: dest.clone(),
+                    to: src.clone(),
+                });
+                Ok(move_job(dest, src, *rename))
+            }
+            Operation::Trash { path } => {
+                disk.ensure_free(path)?;
+                let item = filesystem::list_trash()
+                    .into_iter()
+                    .filter(|item| item.original == *path)
+                    .max_by_key(|item| item.deleted)
+                    .ok_or_else(|| format!("{} is no longer in the trash", path.display()))?;
+                disk.push(Change::Created(path.clone()));
+                Ok(JobKind::Restore {
+                    path: item.path,
+                    dest: path.clone(),
+                })
+            }
+            Operation::Symlink { src, dest } => {
+                let link = disk.on_disk(dest).and_then(|path| fs::read_link(path).ok());
+                if link.as_ref() != Some(src) {
+                    return Err(format!("{} changed since it was linked", dest.display()));
+                }
+                disk.push(Change::Removed(dest.clone()));
+                Ok(JobKind::Delete { path: dest.clone() })
+            }
+            Operation::Mkdir { path } => {
+                if !disk.is_empty_dir(path) {
+                    return Err(format!(
+                        "{} is no longer an empty directory",
+                        path.display()
+                    ));
+                }
+                disk.push(Change::Removed(path.clone()));
+                Ok(JobKind::Delete { path: path.clone() })
+            }
+            Operation::Chmod {
+                path,
+                before,
+                after,
+            } => {
+                if disk.on_disk(path).and_then(|path| mode_of(&path)) != Some(*after) {
+                    return Err(format!("mode of {} changed since", path.display()));
+                }
+                Ok(chmod(path, *before))
+            }
+        }
+    }
+
+    /// The job performing this operation again, checked as `undo` checks
+    fn redo(&self, disk: &mut Planned) -> Result<JobKind, String> {
+        match self {
+            Operation::Move {
+                src, dest, rename, ..
+            } => {
+                disk.ensure_exists(src)?;
+                disk.ensure_free(dest)?;
+                disk.push(Change::Moved {
+                    from: src.clone(),
+                    to: dest.clone(),
+                });
+                Ok(move_job(src, dest, *rename))
+            }
+            Operation::Trash { path } => {
+                disk.ensure_exists(path)?;
+                disk.push(Change::Removed(path.clone()));
+                Ok(JobKind::Trash { path: path.clone() })
+            }
+            Operation::Symlink { src, dest } => {
+                disk.ensure_free(dest)?;
+                disk.push(Change::Created(dest.clone()));
+                Ok(JobKind::Symlink {
+                    src: src.clone(),
+                    dest: dest.clone(),
+                })
+            }
+            Operation::Mkdir { path } => {
+                disk.ensure_free(path)?;
+                disk.push(Change::Created(path.clone()));
+                Ok(JobKind::Mkdir {
+                    path: path.clone(),
+                    parents: false,
+                })
+            }
+            Operation::Chmod {
+                path,
+                before,
+                after,
+            } => {
+                if disk.on_disk(path).and_then(|path| mode_of(&path)) != Some(*before) {
+                    return Err(format!("mode of {} changed since", path.display()));
+                }
+                Ok(chmod(path, *after))
+            }
+        }
+    }
+}
+
+/// A change one job of a step makes, for checking the jobs after it
+#[derive(Debug)]
+enum Change {
+    /// What was at `from`, and everything below it, is now at `to`
+    Moved {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Created(PathBuf),
+    Removed(PathBuf),
+}
+
+/// Where what a path will hold is now
+#[derive(Debug, PartialEq)]
+enum Origin {
+    /// On the disk, at this path
+    Disk(PathBuf),
+    /// Made by one of the jobs
+    Created,
+}
+
+/// The disk as it will be once the jobs checked so far have run
+#[derive(Debug, Default)]
+struct Planned {
+    changes: Vec<Change>,
+}
+
+impl Planned {
+    fn push(&mut self, change: Change) {
+        self.changes.push(change);
+    }
+
+    fn origin(&self, path: &Path) -> Option<Origin> {
+        origin(&self.changes, path)
+    }
+
+    /// The entry on the disk now that will be at `path`
+    fn on_disk(&self, path: &Path) -> Option<PathBuf> {
+        match self.origin(path)? {
+            Origin::Disk(path) => Some(path),
+            Origin::Created => None,
+        }
+    }
+
+    fn ensure_free(&self, path: &Path) -> Result<(), String> {
+        match self.origin(path) {
+            Some(_) => Err(format!("{} exists", path.display())),
+            None => Ok(()),
+        }
+    }
+
+    fn ensure_exists(&self, path: &Path) -> Result<(), String> {
+        match self.origin(path) {
+            Some(_) => Ok(()),
+            None => Err(format!("{} no longer exists", path.display())),
+        }
+    }
+
+    /// Whether `path` will be a directory with everything in it moved out
+    /// and nothing moved or made in
+    fn is_empty_dir(&self, path: &Path) -> bool {
+        let Some(dir) = self.on_disk(path) else {
+            return false;
+        };
+        if dir.is_symlink() {
+            return false;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return false;
+        };
+        let emptied = entries
+            .filter_map(|entry| entry.ok())
+            .all(|entry| self.origin(&path.join(entry.file_name())).is_none());
+        emptied
+            && self.changes.iter().all(|change| match change {
+                Change::Moved { to: added, .. } | Change::Created(added) => {
+                    added == path || !added.starts_with(path) || self.origin(added).is_none()
+                }
+                Change::Removed(_) => true,
+            })
+    }
+}
+
+/// Where what is at `path` after `changes` is before them, if anything is
+fn origin(changes: &[Change], path: &Path) -> Option<Origin> {
+    let Some((last, earlier)) = changes.split_last() else {
+        return path
+            .symlink_metadata()
+            .is_ok()
+            .then(|| Origin::Disk(path.to_path_buf()));
+    };
+    match last {
+        Change::Moved { from, to } => {
+            if let Ok(rest) = path.strip_prefix(to) {
+                let before = match rest.as_os_str().is_empty() {
+                    true => from.clone(),
+                    false => from.join(rest),
+                };
+                return origin(earlier, &before);
+            }
+            if path.starts_with(from) {
+                return None;
+            }
+        }
+        Change::Created(created) if path == created => return Some(Origin::Created),
+        Change::Created(created) | Change::Removed(created) if path.starts_with(created) => {
+            return None;
+        }
+        _ => {}
+    }
+    origin(earlier, path)
+}
+
+fn move_job(src: &Path, dest: &Path, rename: bool) -> JobKind {
+    let (src, dest) = (src.to_path_buf(), dest.to_path_buf());
+    if rename {
+        JobKind::Rename { src, dest }
+    } else {
+        JobKind::Move { src, dest }
+    }
+}
+
+fn mode_of(path: &Path) -> Option<u32> {
+    Some(fs::metadata(path).ok()?.permissions().mode() & 0o7777)
+}
+
+fn chmod(path: &Path, mode: u32) -> JobKind {
+    JobKind::Chmod {
+        path: path.to_path_buf(),
+        mode: format!("{mode:o}"),
+    }
+}
+
+/// Operations undone together: one job, or the jobs of one batch
+#[derive(Debug)]
+struct Step {
+    batch: Option<JobId>,
+    operations: Vec<Operation>,
+}
+
+/// What a job found as it started that its completion cannot tell
+#[derive(Debug)]
+enum Before {
+    /// The mode of the path a chmod changes
+    Mode(u32),
+    /// The directories a mkdir is to make, outermost first
+    Missing(Vec<PathBuf>),
+}
+
+/// Why a job was queued by the journal itself
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Replay {
+    Undo,
+    Redo,
+}
+
+/// Undo and redo stacks of file operations
+#[derive(Debug, Default)]
+pub struct Journal {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    /// What running jobs found as they started
+    before: HashMap<JobId, Before>,
+    replaying: HashMap<JobId, Replay>,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note the state a job is about to change that its completion cannot
+    /// tell
+    pub fn job_started(&mut self, id: JobId, kind: &JobKind) {
+        let before = match kind {
+            JobKind::Chmod { path, .. } => mode_of(path).map(Before::Mode),
+            JobKind::Mkdir { path, parents } => {
+                let mut missing: Vec<PathBuf> = path
+                    .ancestors()
+                    .take(if *parents { usize::MAX } else { 1 })
+                    .take_while(|dir| dir.symlink_metadata().is_err())
+                    .map(Path::to_path_buf)
+                    .collect();
+                missing.reverse();
+                Some(Before::Missing(missing))
+            }
+            _ => None,
+        };
+        if let Some(before) = before {
+            self.before.insert(id, before);
+        }
+    }
+
+    /// Mark a job as queued by undo or redo
+    pub fn replaying(&mut self, id: JobId, replay: Replay) {
+        self.replaying.insert(id, replay);
+    }
+
+    /// Run a quick job now, noting what it changes first as `job_started`
+    /// does for jobs the queue starts; `replay` when undo or redo queued it
+    pub fn run_now(
+        &mut self,
+        queue: &mut JobQueue,
+        kind: JobKind,
+        parent: Option<JobId>,
+        replay: Option<Replay>,
+    ) -> (JobId, Result<(), String>) {
+        self.job_started(queue.next_id(), &kind);
+        let (id, result) = queue.run_now_in(kind, parent);
+        if let Some(replay) = replay {
+            self.replaying(id, replay);
+        }
+        (id, result)
+    }
+
+    /// Record a completed job, in the step of the batch `parent` when the
+    /// latest step belongs to it
+    pub fn job_completed(&mut self, id: JobId, parent: Option<JobId>, kind: &JobKind) {
+        let before = self.before.remove(&id);
+        let replay = self.replaying.remove(&id);
+        if replay == Some(Replay::Undo) {
+            return;
+        }
+        let operations = Operation::from_job(kind, before);
+        if operations.is_empty() {
+            return;
+        }
+        if replay.is_none() {
+            self.redo.clear();
+        }
+        match self.undo.last_mut() {
+            Some(step) if parent.is_some() && step.batch == parent => {
+                step.operations.extend(operations);
+            }
+            _ => {
+                self.undo.push(Step {
+                    batch: parent,
+                    operations,
+                });
+                if self.undo.len() > MAX_STEPS {
+                    self.undo.remove(0);
+                }
+            }
+        }
+    }
+
+    /// Jobs undoing the latest step, which moves to the redo stack; the step
+    /// stays put when anything it touched has changed
+    pub fn undo(&mut self) -> Result<Vec<JobKind>, String> {
+        let step = self.undo.pop().ok_or("nothing to undo")?;
+        let mut disk = Planned::default();
+        let kinds = step.operations.iter().rev().map(|op| op.undo(&mut disk));
+        match kinds.collect() {
+            Ok(kinds) => {
+                self.redo.push(step);
+                Ok(kinds)
+            }
+            Err(e) => {
+                self.undo.push(step);
+                Err(e)
+            }
+        }
+    }
+
+    /// Jobs redoing the latest undone step; their completions record it for
+    /// undo again
+    pub fn redo(&mut self) -> Result<Vec<JobKind>, String> {
+        let step = self.redo.pop().ok_or("nothing to redo")?;
+        let mut disk = Planned::default();
+        match step
+            .operations
+            .iter()
+            .map(|op| op.redo(&mut disk))
+            .collect()
+        {
+            Ok(kinds) => Ok(kinds),
+            Err(e) => {
+                self.redo.push(step);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn temp_dir(name: &str) -> TempDir {
+        TempDir::new(&format!("journal-{name}"))
+    }
+
+    #[test]
+    fn test_undo_refuses_a_modified_destination() {
+        let dir = temp_dir("modified");
+        let (src, dest) = (dir.join("a"), dir.join("b"));
+        fs::write(&dest, "moved").unwrap();
+
+        let kind = JobKind::Rename {
+            src: src.clone(),
+            dest: dest.clone(),
+        };
+        let mut journal = Journal::new();
+        journal.job_completed(1, None, &kind);
+        let kinds = journal.undo().unwrap();
+        assert!(
+            matches!(&kinds[..], [JobKind::Rename { src: s, dest: d }] if *s == dest && *d == src)
+        );
+        assert!(journal.undo().is_err());
+
+        let mut journal = Journal::new();
+        journal.job_completed(1, None, &kind);
+        fs::write(&dest, "modified after the move").unwrap();
+        assert!(journal.undo().unwrap_err().contains("changed"));
+        // The refused step is still the one to undo
+        assert_eq!(journal.undo.len(), 1);
+    }
+
+    #[test]
+    fn test_batches_undo_as_one_step_and_redo_keeps_history() {
+        let dir = temp_dir("batch");
+        let mut journal = Journal::new();
+        for (id, name) in [(2, "x"), (3, "y")] {
+            let path = dir.join(name);
+            fs::create_dir(&path).unwrap();
+            journal.job_completed(
+                id,
+                Some(1),
+                &JobKind::Mkdir {
+                    path,
+                    parents: false,
+                },
+            );
+        }
+        let kinds = journal.undo().unwrap();
+        assert_eq!(kinds.len(), 2);
+        assert!(journal.undo().is_err());
+
+        // Redo refuses while the directories still exist
+        assert!(journal.redo().is_err());
+        fs::remove_dir(dir.join("x")).unwrap();
+        fs::remove_dir(dir.join("y")).unwrap();
+        assert_eq!(journal.redo().unwrap().len(), 2);
+
+        // An undo job's completion is not recorded as a new step
+        journal.replaying(4, Replay::Undo);
+        journal.job_completed(
+            4,
+            None,
+            &JobKind::Mkdir {
+                path: dir.to_path_buf(),
+                parents: false,
+            },
+        );
+        assert!(journal.undo.is_empty());
+    }
+
+    /// Rename on the disk and record it as a job of the batch 1
+    fn rename(journal: &mut Journal, id: JobId, src: &Path, dest: &Path) {
+        fs::rename(src, dest).unwrap();
+        let kind = JobKind::Rename {
+            src: src.to_path_buf(),
+            dest: dest.to_path_buf(),
+        };
+        journal.job_completed(id, Some(1), &kind);
+    }
+
+    #[test]
+    fn test_undo_reverses_swaps_and_chains() {
+        let dir = temp_dir("swap");
+        let [a, b, c, tmp] = ["a", "b", "c", "tmp"].map(|name| dir.join(name));
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+        let mut journal = Journal::new();
+        rename(&mut journal, 2, &a, &tmp);
+        rename(&mut journal, 3, &b, &a);
+        rename(&mut journal, 4, &tmp, &b);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "b");
+
+        let mut queue = JobQueue::new(1);
+        for kind in journal.undo().unwrap() {
+            queue.run_now(kind).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "b");
+        assert!(!tmp.exists());
+
+        let mut journal = Journal::new();
+        rename(&mut journal, 2, &a, &c);
+        rename(&mut journal, 3, &b, &a);
+        rename(&mut journal, 4, &c, &b);
+        rename(&mut journal, 5, &b, &c);
+        for kind in journal.undo().unwrap() {
+            queue.run_now(kind).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "b");
+        assert!(!c.exists());
+    }
+
+    #[test]
+    fn test_undo_rename_into_new_directories() {
+        let dir = temp_dir("into");
+        let (file, sub) = (dir.join("a"), dir.join("x/y"));
+        fs::write(&file, "").unwrap();
+        let mut journal = Journal::new();
+        let mkdir = JobKind::Mkdir {
+            path: sub.clone(),
+            parents: true,
+        };
+        journal.job_started(2, &mkdir);
+        fs::create_dir_all(&sub).unwrap();
+        journal.job_completed(2, Some(1), &mkdir);
+        rename(&mut journal, 3, &file, &sub.join("a"));
+
+        let kinds = journal.undo().unwrap();
+        assert!(matches!(&kinds[0], JobKind::Rename { dest, .. } if *dest == file));
+        // Every directory the mkdir made goes, innermost first
+        let deleted: Vec<&Path> = kinds[1..]
+            .iter()
+            .filter_map(|kind| match kind {
+                JobKind::Delete { path } => Some(path.as_path()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deleted, [sub.as_path(), &dir.join("x")]);
+    }
+
+    #[test]
+    fn test_chmod_can_be_undone_again_after_redo() {
+        let dir = temp_dir("chmod");
+        let path = dir.join("a");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut queue = JobQueue::new(1);
+        let mut journal = Journal::new();
+        let record = |queue: &mut JobQueue, journal: &mut Journal| {
+            for job in queue.take_completed() {
+                journal.job_completed(job.id, job.parent, &job.kind);
+            }
+        };
+        let kind = JobKind::Chmod {
+            path: path.clone(),
+            mode: "600".into(),
+        };
+        journal.job_started(queue.next_id(), &kind);
+        queue.run_now(kind).unwrap();
+        record(&mut queue, &mut journal);
+
+        for (replay, mode) in [
+            (Replay::Undo, 0o644),
+            (Replay::Redo, 0o600),
+            (Replay::Undo, 0o644),
+        ] {
+            let kinds = match replay {
+                Replay::Undo => journal.undo(),
+                Replay::Redo => journal.redo(),
+            };
+            for kind in kinds.unwrap() {
+                let (_, result) = journal.run_now(&mut queue, kind, None, Some(replay));
+                result.unwrap();
+            }
+            record(&mut queue, &mut journal);
+            assert_eq!(mode_of(&path), Some(mode));
+        }
+    }
+}
@@ -3,6 +3,7 @@
 mod app;
 mod app_render;
 mod cli;
+mod cmdline;
 mod config;
 mod event_loop;
 mod features;
@@ -10,10 +11,13 @@ mod ffmpeg;
 mod filesystem;
 mod input;
 mod jobs;
+mod journal;
 mod navigation;
 mod preview;
 mod preview_state;
 mod render;
+#[cfg(test)]
+mod test_util;
 
 use app::App;
 use config::Theme;
@@ -97,6 +101,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             win_h,
         );
     }
+    app.frecency.flush();
 
     Ok(())
 }
@@ -132,7 +137,7 @@ fn handle_input(app: &mut App, mkapp: &mut MkApp, window_id: mkframe::WindowId)
         if event.state != KeyState::Pressed {
             continue;
         }
-        if let Some(key_str) = event.to_key_string()
+        if let Some(key_str) = input::key_event_string(&event)
             && app.process_key(&key_str)
         {
             needs_redraw = true;
@@ -146,6 +151,8 @@ fn handle_input(app: &mut App, mkapp: &mut MkApp, window_id: mkframe::WindowId)
             needs_redraw = true;
         }
     }
+    app.record_visits();
+    app.cancel_left_finds();
 
     needs_redraw
 }
@@ -211,9 +218,10 @@ fn render_preview_if_needed(
 
     if let Some(attached_id) = preview.attached {
         if mkapp.is_attached_surface_dirty(attached_id) || preview.needs_render {
-            let content = preview
-                .cache
-                .get_or_load(path, preview_width, preview_height);
+            let content =
+                preview
+                    .cache
+                    .get_or_load(path, preview.line, preview_width, preview_height);
             mkapp.render_attached_surface(attached_id, |canvas| {
                 render_preview(canvas, text_renderer, content);
             });
@@ -225,7 +233,7 @@ fn render_preview_if_needed(
     {
         let content = preview
             .cache
-            .get_or_load(path, preview_width, preview_height);
+            .get_or_load(path, preview.line, preview_width, preview_height);
         mkapp.render_subsurface(subsurface_id, |canvas| {
             render_preview(canvas, text_renderer, content);
         });
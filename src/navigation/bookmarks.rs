@@ -0,0 +1,287 @@
+//! Bookmarks (marks)
+//!
+//! Lowercase marks last for the session; uppercase marks are global and are
+//! saved to the state directory. Marks from the `[bookmarks]` table in
+//! config.toml are loaded first and act as defaults.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::SavedSettings;
+use crate::filesystem;
+
+/// Marks by letter
+#[derive(Clone, Debug, Default)]
+pub struct Bookmarks {
+    marks: BTreeMap<char, PathBuf>,
+    /// File uppercase marks are saved to
+    path: Option<PathBuf>,
+}
+
+impl Bookmarks {
+    /// Load config bookmarks, then global marks saved by earlier sessions
+    pub fn load() -> Self {
+        let mut bookmarks = Self::default();
+        if let Some(toml::Value::Table(table)) = SavedSettings::load_existing().get("bookmarks") {
+            for error in bookmarks.apply_config(table) {
+                eprintln!("warning: {error}");
+            }
+        }
+
+        bookmarks.path = dirs::state_dir()
+            .or_else(dirs::data_dir)
+            .map(|dir| dir.join("mkfm").join("bookmarks"));
+        if let Some(content) = bookmarks
+            .path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+        {
+            bookmarks.apply_saved(&content);
+        }
+        bookmarks
+    }
+
+    fn apply_config(&mut self, table: &toml::map::Map<String, toml::Value>) -> Vec<String> {
+        let mut errors = Vec::new();
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+        for (key, value) in table {
+            let Some(c) = mark_char(key) else {
+                errors.push(format!("[bookmarks] '{key}' is not a single letter"));
+                continue;
+            };
+            let Some(path) = value.as_str() else {
+                errors.push(format!("[bookmarks] {key} must be a path string"));
+                continue;
+            };
+            match filesystem::expand_path(path, &home) {
+                Ok(path) => {
+                    self.marks.insert(c, path);
+                }
+                Err(e) => errors.push(format!("[bookmarks] {key}: {e}")),
+            }
+        }
+        errors
+    }
+
+    /// Read `<letter> <path>` lines written by `save`
+    fn apply_saved(&mut self, content: &str) {
+        for line in content.lines() {
+            if let Some((key, path)) = line.split_once(' ')
+                && let Some(c) = mark_char(key).filter(|c| is_global(*c))
+            {
+                self.marks.insert(c, PathBuf::from(path));
+            }
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let content: String = self
+            .marks
+            .iter()
+            .filter(|(c, _)| is_global(**c))
+            .map(|(c, p)| format!("{c} {}\n", p.display()))
+            .collect();
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(path, content));
+        if let Err(e) = result {
+            eprintln!("warning: failed to save bookmarks: {e}");
+        }
+    }
+
+    pub fn get(&self, c: char) -> Option<&Path> {
+        self.marks.get(&c).map(PathBuf::as_path)
+    }
+
+    pub fn set(&mut self, c: char, path: PathBuf) {
+        self.marks.insert(c, path);
+        if is_global(c) {
+            self.save();
+        }
+    }
+
+    /// Remove a mark, returning whether it existed
+    pub fn remove(&mut self, c: char) -> bool {
+        let removed = self.marks.remove(&c).is_some();
+        if removed && is_global(c) {
+            self.save();
+        }
+        removed
+    }
+
+    /// Remove every session (lowercase) mark
+    pub fn remove_session_marks(&mut self) {
+        self.marks.retain(|c, _| is_global(*c));
+    }
+
+    /// Move a mark to another letter, replacing any mark there
+    pub fn rename(&mut self, from: char, to: char) -> bool {
+        let Some(path) = self.marks.remove(&from) else {
+            return false;
+        };
+        self.marks.insert(to, path);
+        if is_global(from) || is_global(to) {
+            self.save();
+        }
+        true
+    }
+
+    /// Marks in letter order, lowercase after uppercase
+    pub fn iter(&self) -> impl Iterator<Item = (char, &Path)> {
+        self.marks.iter().map(|(c, p)| (*c, p.as_path()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.marks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+}
+
+/// Uppercase marks are global and persistent
+pub fn is_global(c: char) -> bool {
+    c.is_ascii_uppercase()
+}
+
+fn mark_char(key: &str) -> Option<char> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphabetic() => Some(c),
+        _ => None,
+    }
+}
+
+/// Parse `:delmarks` arguments such as `a b`, `a-d` or `aB`
+pub fn parse_mark_list(arg: &str) -> Result<Vec<char>, String> {
+    let chars: Vec<char> = arg.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut marks = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if !c.is_ascii_alphabetic() {
+            return Err(format!("invalid mark '{c}'"));
+        }
+        if chars.get(i + 1) == Some(&'-') {
+            let end = chars
+                .get(i + 2)
+                .copied()
+                .filter(|e| {
+                    e.is_ascii_alphabetic()
+                        && e.is_ascii_uppercase() == c.is_ascii_uppercase()
+                        && *e >= c
+                })
+                .ok_or_else(|| format!("invalid mark range starting at '{c}'"))?;
+            marks.extend(c..=end);
+            i += 3;
+        } else {
+            marks.push(c);
+            i += 1;
+        }
+    }
+    Ok(marks)
+}
+
+/// Marks pane state
+pub struct MarksPane {
+    pub cursor: usize,
+    pub visible: bool,
+    /// Waiting for the letter to move the mark under the cursor to
+    pub renaming: bool,
+}
+
+impl MarksPane {
+    pub fn new() -> Self {
+        Self {
+            cursor: 0,
+            visible: false,
+            renaming: false,
+        }
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.renaming = false;
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.renaming = false;
+    }
+
+    pub fn move_cursor(&mut self, delta: i32, max: usize) {
+        if max == 0 {
+            return;
+        }
+        if delta > 0 {
+            self.cursor = (self.cursor + delta as usize).min(max - 1);
+        } else if delta < 0 {
+            self.cursor = self.cursor.saturating_sub((-delta) as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_marks_are_global_only() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.apply_saved("A /srv/data\nb /tmp\nbad line\nC /home/user/My Files\n");
+        assert_eq!(bookmarks.get('A'), Some(Path::new("/srv/data")));
+        assert_eq!(bookmarks.get('b'), None);
+        assert_eq!(bookmarks.get('C'), Some(Path::new("/home/user/My Files")));
+    }
+
+    #[test]
+    fn test_config_marks_expand_home() {
+        let table: toml::Table = r#"
+            d = "~/Downloads"
+            T = "/tmp"
+            long = "/nope"
+            n = 3
+        "#
+        .parse()
+        .unwrap();
+        let mut bookmarks = Bookmarks::default();
+        let errors = bookmarks.apply_config(&table);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            bookmarks.get('d'),
+            Some(dirs::home_dir().unwrap().join("Downloads").as_path())
+        );
+        assert_eq!(bookmarks.get('T'), Some(Path::new("/tmp")));
+    }
+
+    #[test]
+    fn test_rename_and_session_marks() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.set('a', PathBuf::from("/a"));
+        bookmarks.set('B', PathBuf::from("/b"));
+        assert!(bookmarks.rename('a', 'c'));
+        assert!(!bookmarks.rename('a', 'd'));
+        assert_eq!(bookmarks.get('c'), Some(Path::new("/a")));
+        bookmarks.remove_session_marks();
+        assert_eq!(
+            bookmarks.iter().map(|(c, _)| c).collect::<Vec<_>>(),
+            vec!['B']
+        );
+    }
+
+    #[test]
+    fn test_parse_mark_list() {
+        assert_eq!(
+            parse_mark_list("a c-e X"),
+            Ok(vec!['a', 'c', 'd', 'e', 'X'])
+        );
+        assert_eq!(parse_mark_list("ab"), Ok(vec!['a', 'b']));
+        assert!(parse_mark_list("a-C").is_err());
+        assert!(parse_mark_list("1").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Find results: what a `:find` job turned up below the directory, listed
+//! by their path from it as they arrive
+
+use std::path::{Path, PathBuf};
+
+use crate::filesystem::Entry;
+use crate::jobs::JobId;
+
+use super::Browser;
+
+/// A search whose results the browser is showing
+pub(super) struct FindView {
+    job: JobId,
+    query: String,
+    /// Everything found so far, in the order it was found
+    paths: Vec<PathBuf>,
+}
+
+impl Browser {
+    /// The find job whose results are shown
+    pub fn find_job(&self) -> Option<JobId> {
+        self.find.as_ref().map(|view| view.job)
+    }
+
+    pub fn find_query(&self) -> Option<&str> {
+        self.find.as_ref().map(|view| view.query.as_str())
+    }
+
+    /// Show the results of `job`, searching below the current directory for
+    /// `query`; they are added by `add_found` as they arrive
+    pub fn enter_find(&mut self, job: JobId, query: String) {
+        self.leave_virtual_view();
+        self.find = Some(FindView {
+            job,
+            query,
+            paths: Vec::new(),
+        });
+        self.refresh();
+        self.cursor = 0;
+    }
+
+    pub fn exit_find(&mut self) {
+        self.leave_find();
+        self.refresh();
+    }
+
+    /// Stop showing results, leaving their job to be cancelled
+    pub(super) fn leave_find(&mut self) {
+        if let Some(view) = self.find.take() {
+            self.left_finds.push(view.job);
+        }
+    }
+
+    /// Add results, keeping the cursor on the entry it was on
+    pub fn add_found(&mut self, paths: Vec<PathBuf>) {
+        if self.find.is_none() {
+            return;
+        }
+        let current = self.current_entry().map(|e| e.path.clone());
+        let new_entries: Vec<Entry> = paths
+            .iter()
+            .filter_map(|path| found_entry(&self.path, path, self.show_hidden))
+            .collect();
+        if let Some(view) = self.find.as_mut() {
+            view.paths.extend(paths);
+        }
+        // Merged in rather than sorted and filtered again, which would grow
+        // with every batch
+        let pattern = self.filter.as_ref().map(|p| p.to_lowercase());
+        let shown: Vec<Entry> = new_entries
+            .iter()
+            .filter(|e| {
+                pattern
+                    .as_ref()
+                    .is_none_or(|p| e.name.to_lowercase().contains(p))
+            })
+            .cloned()
+            .collect();
+        let (mode, reverse) = (self.sort_mode, self.sort_reverse);
+        Self::merge_entries_impl(&mut self.all_entries, new_entries, mode, reverse);
+        Self::merge_entries_impl(&mut self.entries, shown, mode, reverse);
+        if let Some(current) = current {
+            self.select_path(&current);
+        }
+    }
+
+    /// Drop results that no longer exist and pick up changes to the rest
+    pub(super) fn refresh_find(&mut self) {
+        let Some(view) = self.find.as_mut() else {
+            return;
+        };
+        view.paths.retain(|path| path.symlink_metadata().is_ok());
+        self.all_entries = view
+            .paths
+            .iter()
+            .filter_map(|path| found_entry(&self.path, path, self.show_hidden))
+            .collect();
+        Self::sort_entries_impl(&mut self.all_entries, self.sort_mode, self.sort_reverse);
+        self.apply_filter();
+        self.cursor = self.cursor.min(self.entries.len().saturating_sub(1));
+    }
+}
+
+/// Entry for a result, named by its path from `root`; hidden entries and
+/// entries in hidden directories are left out unless shown
+fn found_entry(root: &Path, path: &Path, show_hidden: bool) -> Option<Entry> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let hidden = relative
+        .iter()
+        .any(|part| part.to_string_lossy().starts_with('.'));
+    if hidden && !show_hidden {
+        return None;
+    }
+    let meta = path.symlink_metadata().ok()?;
+    Some(Entry {
+        name: relative.to_string_lossy().into_owned(),
+        path: path.to_path_buf(),
+        is_dir: path.is_dir(),
+        size: meta.len(),
+        modified: meta.modified().ok(),
+        depth: 0,
+    })
+}
@@ -0,0 +1,131 @@
+//! Directory history: the jump list and per-directory cursor memory
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Most directories kept in a jump list
+const MAX_JUMPS: usize = 100;
+
+/// Directories visited by one browser, traversed with `C-o`/`C-i`
+#[derive(Clone, Debug, Default)]
+pub struct JumpList {
+    jumps: Vec<PathBuf>,
+    /// Index of the current directory in `jumps`
+    position: usize,
+}
+
+impl JumpList {
+    pub fn new(start: PathBuf) -> Self {
+        Self {
+            jumps: vec![start],
+            position: 0,
+        }
+    }
+
+    /// Record a visit, dropping any directories ahead of the current one
+    pub fn push(&mut self, path: PathBuf) {
+        if self.jumps.get(self.position) == Some(&path) {
+            return;
+        }
+        self.jumps.truncate(self.position + 1);
+        self.jumps.push(path);
+        if self.jumps.len() > MAX_JUMPS {
+            self.jumps.remove(0);
+        }
+        self.position = self.jumps.len() - 1;
+    }
+
+    /// Step back, returning the directory to show
+    pub fn back(&mut self) -> Option<&Path> {
+        self.position = self.position.checked_sub(1)?;
+        Some(&self.jumps[self.position])
+    }
+
+    /// Step forward, returning the directory to show
+    pub fn forward(&mut self) -> Option<&Path> {
+        if self.position + 1 >= self.jumps.len() {
+            return None;
+        }
+        self.position += 1;
+        Some(&self.jumps[self.position])
+    }
+
+    /// Forget the directory just stepped to when it could not be shown,
+    /// moving the position back to the directory still being shown
+    pub fn discard(&mut self, went_back: bool) {
+        if self.jumps.len() > 1 {
+            self.jumps.remove(self.position);
+            if !went_back {
+                self.position -= 1;
+            }
+        }
+    }
+}
+
+/// The entry the cursor was on when each directory was left
+#[derive(Clone, Debug, Default)]
+pub struct CursorMemory {
+    entries: HashMap<PathBuf, PathBuf>,
+}
+
+impl CursorMemory {
+    pub fn remember(&mut self, dir: &Path, entry: &Path) {
+        self.entries.insert(dir.to_path_buf(), entry.to_path_buf());
+    }
+
+    pub fn recall(&self, dir: &Path) -> Option<&Path> {
+        self.entries.get(dir).map(PathBuf::as_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(paths: &[&str]) -> JumpList {
+        let mut jumps = JumpList::new(PathBuf::from(paths[0]));
+        for path in &paths[1..] {
+            jumps.push(PathBuf::from(path));
+        }
+        jumps
+    }
+
+    #[test]
+    fn test_back_and_forward() {
+        let mut jumps = list(&["/a", "/b", "/c"]);
+        assert_eq!(jumps.forward(), None);
+        assert_eq!(jumps.back(), Some(Path::new("/b")));
+        assert_eq!(jumps.back(), Some(Path::new("/a")));
+        assert_eq!(jumps.back(), None);
+        assert_eq!(jumps.forward(), Some(Path::new("/b")));
+    }
+
+    #[test]
+    fn test_push_drops_forward_history() {
+        let mut jumps = list(&["/a", "/b", "/c"]);
+        jumps.back();
+        jumps.back();
+        jumps.push(PathBuf::from("/d"));
+        jumps.push(PathBuf::from("/d"));
+        assert_eq!(jumps.forward(), None);
+        assert_eq!(jumps.back(), Some(Path::new("/a")));
+        assert_eq!(jumps.back(), None);
+    }
+
+    #[test]
+    fn test_discard_keeps_current_directory() {
+        let mut jumps = list(&["/a", "/gone", "/c"]);
+        jumps.back();
+        jumps.discard(true);
+        assert_eq!(jumps.forward(), None);
+        assert_eq!(jumps.back(), Some(Path::new("/a")));
+
+        let mut jumps = list(&["/a", "/gone", "/c"]);
+        jumps.back();
+        jumps.back();
+        jumps.forward();
+        jumps.discard(false);
+        assert_eq!(jumps.back(), None);
+        assert_eq!(jumps.forward(), Some(Path::new("/c")));
+    }
+}
@@ -6,13 +6,24 @@ mod archive;
 mod expansion;
 mod expansion_helpers;
 mod filter_search;
+mod find;
+mod history;
 mod sorting;
+mod trash;
 
 use std::collections::HashSet;
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 
-use crate::filesystem::{self, ArchiveEntry, Entry};
+use crate::filesystem::{self, ArchiveEntry, Entry, TrashItem};
 use crate::input::SortMode;
+use crate::jobs::JobId;
+
+use find::FindView;
+use history::{CursorMemory, JumpList};
+
+pub use trash::TRASH_URI;
 
 /// File browser state
 pub struct Browser {
@@ -29,8 +40,21 @@ pub struct Browser {
     pub(super) archive_path: Option<PathBuf>,
     pub(super) archive_prefix: String,
     pub(super) archive_entries: Vec<ArchiveEntry>,
+    // Trash browsing
+    pub(super) in_trash: bool,
+    pub(super) trash_items: Vec<TrashItem>,
+    // Find results
+    find: Option<FindView>,
     // Fold expansion
     pub(super) expanded_dirs: HashSet<PathBuf>,
+    // Directory history
+    previous_path: Option<PathBuf>,
+    jumps: JumpList,
+    cursor_memory: CursorMemory,
+    /// Directories changed to since last taken, for the frecency database
+    visits: Vec<PathBuf>,
+    /// Find jobs whose results were left since last taken, to be cancelled
+    left_finds: Vec<JobId>,
 }
 
 impl Browser {
@@ -45,6 +69,7 @@ impl Browser {
         };
 
         let mut browser = Self {
+            jumps: JumpList::new(path.clone()),
             path,
             entries: Vec::new(),
             all_entries: Vec::new(),
@@ -57,7 +82,14 @@ impl Browser {
             archive_path: None,
             archive_prefix: String::new(),
             archive_entries: Vec::new(),
+            in_trash: false,
+            trash_items: Vec::new(),
+            find: None,
             expanded_dirs: HashSet::new(),
+            previous_path: None,
+            cursor_memory: CursorMemory::default(),
+            visits: Vec::new(),
+            left_finds: Vec::new(),
         };
         browser.refresh();
         browser
@@ -72,6 +104,10 @@ impl Browser {
     pub fn refresh(&mut self) {
         if self.archive_path.is_some() {
             self.refresh_archive();
+        } else if self.in_trash {
+            self.refresh_trash();
+        } else if self.find.is_some() {
+            self.refresh_find();
         } else {
             self.refresh_directory();
         }
@@ -133,6 +169,14 @@ impl Browser {
             return self.enter_archive_directory(&entry);
         }
 
+        // Trashed and found directories and archives are browsed where they
+        // are kept
+        if (self.in_trash || self.find.is_some())
+            && (entry.is_dir || filesystem::is_archive(&entry.path))
+        {
+            self.leave_virtual_view();
+        }
+
         if !entry.is_dir && filesystem::is_archive(&entry.path) {
             self.enter_archive(&entry.path);
             return true;
@@ -142,9 +186,7 @@ impl Browser {
             return false;
         }
 
-        self.path = entry.path.clone();
-        self.refresh();
-        self.cursor = 0;
+        self.change_directory(entry.path, true);
         true
     }
 
@@ -153,12 +195,21 @@ impl Browser {
             return self.parent_archive_directory();
         }
 
+        if self.in_trash {
+            self.exit_trash();
+            return true;
+        }
+
+        if self.find.is_some() {
+            self.exit_find();
+            return true;
+        }
+
         let Some(parent) = self.path.parent() else {
             return false;
         };
 
-        self.path = parent.to_path_buf();
-        self.refresh();
+        self.change_directory(parent.to_path_buf(), true);
         true
     }
 
@@ -189,12 +240,143 @@ impl Browser {
         }
     }
 
-    pub fn navigate_to(&mut self, path: &Path) {
-        if path.is_dir() {
-            self.path = path.to_path_buf();
-            self.refresh();
-            self.cursor = 0;
+    /// Move to the end of the current run of directories or files, or to
+    /// the end of the next run when already there
+    pub fn next_group(&mut self) {
+        let len = self.entries.len();
+        if self.cursor + 1 >= len {
+            return;
+        }
+        let mut i = self.cursor;
+        if self.entries[i + 1].is_dir != self.entries[i].is_dir {
+            i += 1;
         }
+        while i + 1 < len && self.entries[i + 1].is_dir == self.entries[i].is_dir {
+            i += 1;
+        }
+        self.cursor = i;
+    }
+
+    /// Move to the start of the current run of directories or files, or to
+    /// the start of the previous run when already there
+    pub fn prev_group(&mut self) {
+        if self.cursor == 0 || self.cursor >= self.entries.len() {
+            return;
+        }
+        let mut i = self.cursor;
+        if self.entries[i - 1].is_dir != self.entries[i].is_dir {
+            i -= 1;
+        }
+        while i > 0 && self.entries[i - 1].is_dir == self.entries[i].is_dir {
+            i -= 1;
+        }
+        self.cursor = i;
+    }
+
+    /// Put the cursor on a 1-based line number, clamped to the listing
+    pub fn go_to_line(&mut self, line: usize) {
+        if !self.entries.is_empty() {
+            self.cursor = line.saturating_sub(1).min(self.entries.len() - 1);
+        }
+    }
+
+    /// Change to a directory, leaving any archive being browsed
+    ///
+    /// Fails without changing anything when the path is missing, is not a
+    /// directory or cannot be listed.
+    pub fn navigate_to(&mut self, path: &Path) -> io::Result<()> {
+        check_directory(path)?;
+        self.leave_virtual_view();
+        self.change_directory(path.to_path_buf(), true);
+        Ok(())
+    }
+
+    /// Go back in the jump list (`C-o`); `None` when already at the oldest
+    pub fn jump_back(&mut self) -> Option<io::Result<()>> {
+        let path = self.jumps.back()?.to_path_buf();
+        Some(self.jump_to(&path, true))
+    }
+
+    /// Go forward in the jump list (`C-i`); `None` when already at the newest
+    pub fn jump_forward(&mut self) -> Option<io::Result<()>> {
+        let path = self.jumps.forward()?.to_path_buf();
+        Some(self.jump_to(&path, false))
+    }
+
+    fn jump_to(&mut self, path: &Path, went_back: bool) -> io::Result<()> {
+        if let Err(e) = check_directory(path) {
+            self.jumps.discard(went_back);
+            return Err(e);
+        }
+        self.leave_virtual_view();
+        self.change_directory(path.to_path_buf(), false);
+        Ok(())
+    }
+
+    /// Directories changed to since the last call
+    pub fn take_visits(&mut self) -> Vec<PathBuf> {
+        std::mem::take(&mut self.visits)
+    }
+
+    /// Find jobs whose results were left since the last call
+    pub fn take_left_finds(&mut self) -> Vec<JobId> {
+        std::mem::take(&mut self.left_finds)
+    }
+
+    /// Directory shown before the last change
+    pub fn previous_path(&self) -> Option<&Path> {
+        self.previous_path.as_deref()
+    }
+
+    /// Put the cursor on the entry for `path`, or on the listed directory
+    /// containing it; returns whether an entry was found
+    pub fn select_path(&mut self, path: &Path) -> bool {
+        let found = path.ancestors().find_map(|ancestor| {
+            self.entries
+                .iter()
+                .position(|e| e.name != ".." && e.path == ancestor)
+        });
+        if let Some(index) = found {
+            self.cursor = index;
+        }
+        found.is_some()
+    }
+
+    /// Show another directory, remembering the entry the cursor was on in
+    /// the one being left and restoring it on the way back
+    fn change_directory(&mut self, path: PathBuf, record: bool) {
+        if let Some(entry) = self.current_entry().filter(|e| e.name != "..") {
+            let entry = entry.path.clone();
+            self.cursor_memory.remember(&self.path, &entry);
+        }
+        let previous = std::mem::replace(&mut self.path, path);
+        if previous != self.path {
+            self.previous_path = Some(previous.clone());
+            self.visits.push(self.path.clone());
+        }
+        if record {
+            self.jumps.push(self.path.clone());
+        }
+
+        self.refresh();
+        // Without a remembered entry, going up lands on the directory just left
+        let target = self
+            .cursor_memory
+            .recall(&self.path)
+            .map(Path::to_path_buf)
+            .unwrap_or(previous);
+        self.cursor = 0;
+        self.select_path(&target);
+    }
+
+    /// Stop browsing an archive, the trash or find results
+    fn leave_virtual_view(&mut self) {
+        self.archive_path = None;
+        self.archive_prefix.clear();
+        self.archive_entries.clear();
+        self.in_trash = false;
+        self.trash_items.clear();
+        self.leave_find();
     }
 
     // Sorting
@@ -204,3 +386,14 @@ impl Browser {
         self.refresh();
     }
 }
+
+/// Fail unless `path` is a directory that can be listed
+fn check_directory(path: &Path) -> io::Result<()> {
+    if !fs::metadata(path)?.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotADirectory,
+            format!("{} is not a directory", path.display()),
+        ));
+    }
+    fs::read_dir(path).map(|_| ())
+}
@@ -16,17 +16,52 @@ impl Browser {
         let start = skip_parent_entry(entries);
         let slice = &mut entries[start..];
 
-        match sort_mode {
-            SortMode::Name => slice.sort_by(compare_by_name),
-            SortMode::Size => slice.sort_by(compare_by_size),
-            SortMode::Date => slice.sort_by(compare_by_date),
-            SortMode::Type => slice.sort_by(compare_by_type),
-        }
+        slice.sort_by(comparator(sort_mode));
 
         if sort_reverse {
             slice.reverse();
         }
     }
+
+    /// Merge `batch` into `entries`, already sorted, leaving the whole as
+    /// `sort_entries_impl` would have sorted it
+    pub(super) fn merge_entries_impl(
+        entries: &mut Vec<Entry>,
+        mut batch: Vec<Entry>,
+        sort_mode: SortMode,
+        sort_reverse: bool,
+    ) {
+        Self::sort_entries_impl(&mut batch, sort_mode, sort_reverse);
+        let compare = comparator(sort_mode);
+        let old = std::mem::take(entries);
+        entries.reserve(old.len() + batch.len());
+        let start = skip_parent_entry(&old);
+        let mut old = old.into_iter().peekable();
+        entries.extend(old.by_ref().take(start));
+        let mut batch = batch.into_iter().peekable();
+        while let (Some(a), Some(b)) = (old.peek(), batch.peek()) {
+            let take_old = match compare(a, b) {
+                Ordering::Less => !sort_reverse,
+                Ordering::Greater => sort_reverse,
+                // Sorting keeps equal entries in the order they came, and
+                // reversing turns that around
+                Ordering::Equal => !sort_reverse,
+            };
+            let next = if take_old { old.next() } else { batch.next() };
+            entries.extend(next);
+        }
+        entries.extend(old);
+        entries.extend(batch);
+    }
+}
+
+fn comparator(sort_mode: SortMode) -> fn(&Entry, &Entry) -> Ordering {
+    match sort_mode {
+        SortMode::Name => compare_by_name,
+        SortMode::Size => compare_by_size,
+        SortMode::Date => compare_by_date,
+        SortMode::Type => compare_by_type,
+    }
 }
 
 fn skip_parent_entry(entries: &[Entry]) -> usize {
@@ -64,3 +99,42 @@ fn compare_by_type(a: &Entry, b: &Entry) -> Ordering {
         ext_a.to_lowercase().cmp(&ext_b.to_lowercase())
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(name: &str, size: u64) -> Entry {
+        Entry {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            is_dir: false,
+            size,
+            modified: None,
+            depth: 0,
+        }
+    }
+
+    #[test]
+    fn test_merge_matches_sorting() {
+        let sizes = [3, 1, 2, 1, 3, 0, 2];
+        let all: Vec<Entry> = sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &size)| entry(&format!("e{i}"), size))
+            .collect();
+        for reverse in [false, true] {
+            let mut sorted = all.clone();
+            Browser::sort_entries_impl(&mut sorted, SortMode::Size, reverse);
+            let mut merged = Vec::new();
+            for batch in all.chunks(3) {
+                Browser::merge_entries_impl(&mut merged, batch.to_vec(), SortMode::Size, reverse);
+            }
+            let names = |entries: &[Entry]| -> Vec<String> {
+                entries.iter().map(|e| e.name.clone()).collect()
+            };
+            assert_eq!(names(&merged), names(&sorted));
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Trash browsing: the `trash://` view of every trash directory
+
+use std::path::Path;
+
+use crate::filesystem::{self, Entry};
+
+use super::Browser;
+
+/// Name of the trash view in `:cd` and the pane header
+pub const TRASH_URI: &str = "trash://";
+
+impl Browser {
+    pub fn in_trash(&self) -> bool {
+        self.in_trash
+    }
+
+    /// Show the trash, listing each entry under the path it was trashed from
+    pub fn enter_trash(&mut self) {
+        self.leave_virtual_view();
+        self.in_trash = true;
+        self.refresh();
+        self.cursor = 0;
+    }
+
+    pub fn exit_trash(&mut self) {
+        self.in_trash = false;
+        self.trash_items.clear();
+        self.refresh();
+    }
+
+    /// Where a listed trash entry was trashed from
+    pub fn trashed_from(&self, path: &Path) -> Option<&Path> {
+        self.trash_items
+            .iter()
+            .find(|item| item.path == path)
+            .map(|item| item.original.as_path())
+    }
+
+    pub(super) fn refresh_trash(&mut self) {
+        self.trash_items = filesystem::list_trash();
+        self.all_entries = self
+            .trash_items
+            .iter()
+            .map(|item| {
+                let meta = item.path.symlink_metadata().ok();
+                Entry {
+                    name: item.original.to_string_lossy().into_owned(),
+                    path: item.path.clone(),
+                    is_dir: meta.as_ref().is_some_and(|m| m.is_dir()),
+                    size: meta.map_or(0, |m| m.len()),
+                    modified: item.deleted,
+                    depth: 0,
+                }
+            })
+            .collect();
+        Self::sort_entries_impl(&mut self.all_entries, self.sort_mode, self.sort_reverse);
+        self.apply_filter();
+        self.cursor = self.cursor.min(self.entries.len().saturating_sub(1));
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::path::{Path, PathBuf};
 
-use crate::filesystem;
+use crate::filesystem::{self, ConflictPolicy};
 
 /// Clipboard for yank/cut/paste operations
 pub struct Clipboard {
@@ -47,30 +47,28 @@ impl Clipboard {
         self.archive_source.is_some()
     }
 
-    pub fn paste_to(&mut self, dest_dir: &Path) -> std::io::Result<()> {
-        if let Some(ref archive_path) = self.archive_source {
-            filesystem::extract_files_from_archive(archive_path, &self.archive_files, dest_dir)?;
-            return Ok(());
+    /// Extract the yanked archive members into `dest_dir`, returning how
+    /// many were skipped because they already exist. Members can only be
+    /// skipped or overwritten, so every policy but `Overwrite` skips.
+    pub fn paste_to(&self, dest_dir: &Path, policy: ConflictPolicy) -> std::io::Result<usize> {
+        let Some(archive_path) = &self.archive_source else {
+            return Ok(0);
+        };
+        let mut skipped = 0;
+        let files: Vec<String> = self
+            .archive_files
+            .iter()
+            .filter(|file| {
+                let exists = dest_dir.join(file).symlink_metadata().is_ok();
+                let keep = !exists || policy == ConflictPolicy::Overwrite;
+                skipped += usize::from(!keep);
+                keep
+            })
+            .cloned()
+            .collect();
+        if !files.is_empty() {
+            filesystem::extract_files_from_archive(archive_path, &files, dest_dir)?;
         }
-
-        for src in &self.paths {
-            let Some(name) = src.file_name() else {
-                continue;
-            };
-            let dest = dest_dir.join(name);
-
-            if self.is_cut {
-                filesystem::move_file(src, &dest)?;
-            } else {
-                filesystem::copy_file(src, &dest)?;
-            }
-        }
-
-        if self.is_cut {
-            self.paths.clear();
-            self.is_cut = false;
-        }
-
-        Ok(())
+        Ok(skipped)
     }
 }
@@ -0,0 +1,495 @@
+//! Frecency database of visited directories, for `:z` and `:zi`
+//!
+//! Each visit adds one to a directory's rank. A directory scores its rank
+//! weighted by how recently it was last visited, the way zoxide ranks
+//! them, so places used often and lately come first. Once the ranks add
+//! up to more than `MAX_TOTAL_RANK` they are all scaled down, and
+//! directories left with little rank are forgotten.
+//!
+//! Visits are saved at most every `SAVE_INTERVAL`, and whatever is left
+//! when mkfm exits; each save replaces the file whole, so a crash leaves
+//! the previous one rather than half of it.
+
+use std::ffi::OsString;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Total rank past which every rank is aged
+const MAX_TOTAL_RANK: f64 = 10_000.0;
+/// Rank below which an aged directory is forgotten
+const MIN_RANK: f64 = 1.0;
+/// Least time between saves for visits
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// A visited directory
+#[derive(Clone, Debug, PartialEq)]
+struct Dir {
+    path: PathBuf,
+    rank: f64,
+    /// Seconds since the epoch
+    last_access: u64,
+}
+
+impl Dir {
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_access);
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * weight
+    }
+}
+
+/// Where another tool's database is imported from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrecencySource {
+    Zoxide,
+    Autojump,
+}
+
+impl FrecencySource {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "zoxide" => Some(Self::Zoxide),
+            "autojump" => Some(Self::Autojump),
+            _ => None,
+        }
+    }
+
+    /// Where the tool keeps its database by default
+    pub fn default_path(self) -> Option<PathBuf> {
+        match self {
+            Self::Zoxide => std::env::var_os("_ZO_DATA_DIR")
+                .map(PathBuf::from)
+                .or_else(|| dirs::data_dir().map(|dir| dir.join("zoxide")))
+                .map(|dir| dir.join("db.zo")),
+            Self::Autojump => dirs::data_dir().map(|dir| dir.join("autojump").join("autojump.txt")),
+        }
+    }
+}
+
+/// Visited directories, saved to the data directory
+#[derive(Clone, Debug, Default)]
+pub struct Frecency {
+    dirs: Vec<Dir>,
+    path: Option<PathBuf>,
+    /// Whether there are visits not saved yet
+    dirty: bool,
+    saved_at: Option<Instant>,
+}
+
+impl Frecency {
+    /// Load the database from the data directory (`~/.local/share/mkfm`)
+    pub fn load() -> Self {
+        Self::load_from(dirs::data_dir().map(|dir| dir.join("mkfm").join("frecency")))
+    }
+
+    fn load_from(path: Option<PathBuf>) -> Self {
+        let dirs = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|content| parse_saved(&content))
+            .unwrap_or_default();
+        Self {
+            dirs,
+            path,
+            ..Self::default()
+        }
+    }
+
+    /// Record a visit to `path`, saving unless the last save was too
+    /// recent
+    pub fn visit(&mut self, path: &Path) {
+        self.add(path, 1.0, now());
+        self.age();
+        self.dirty = true;
+        if self
+            .saved_at
+            .is_none_or(|saved| saved.elapsed() >= SAVE_INTERVAL)
+        {
+            self.save();
+        }
+    }
+
+    /// Save the visits left over from the last save
+    pub fn flush(&mut self) {
+        if self.dirty {
+            self.save();
+        }
+    }
+
+    fn add(&mut self, path: &Path, rank: f64, last_access: u64) {
+        match self.dirs.iter_mut().find(|dir| dir.path == path) {
+            Some(dir) => {
+                dir.rank += rank;
+                dir.last_access = dir.last_access.max(last_access);
+            }
+            None => self.dirs.push(Dir {
+                path: path.to_path_buf(),
+                rank,
+                last_access,
+            }),
+        }
+    }
+
+    fn age(&mut self) {
+        let total: f64 = self.dirs.iter().map(|dir| dir.rank).sum();
+        if total <= MAX_TOTAL_RANK {
+            return;
+        }
+        let factor = 0.9 * MAX_TOTAL_RANK / total;
+        for dir in &mut self.dirs {
+            dir.rank *= factor;
+        }
+        self.dirs.retain(|dir| dir.rank >= MIN_RANK);
+    }
+
+    /// Forget a directory, returning whether it was known
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let len = self.dirs.len();
+        self.dirs.retain(|dir| dir.path != path);
+        let removed = self.dirs.len() != len;
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    /// Write the database to a temporary file and rename it over the old
+    /// one
+    fn save(&mut self) {
+        self.dirty = false;
+        self.saved_at = Some(Instant::now());
+        let Some(path) = &self.path else {
+            return;
+        };
+        let content: String = self
+            .dirs
+            .iter()
+            .map(|dir| {
+                format!(
+                    "{}\t{}\t{}\n",
+                    dir.rank,
+                    dir.last_access,
+                    encode_path(&dir.path)
+                )
+            })
+            .collect();
+        let mut temp = path.clone().into_os_string();
+        temp.push(format!(".{}.tmp", std::process::id()));
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&temp, content))
+            .and_then(|()| std::fs::rename(&temp, path));
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&temp);
+            eprintln!("warning: failed to save directory history: {e}");
+        }
+    }
+
+    /// Directories matching `terms`, best first; see `path_matches`
+    pub fn ranked(&self, terms: &[&str]) -> Vec<&Path> {
+        let now = now();
+        let mut found: Vec<&Dir> = self
+            .dirs
+            .iter()
+            .filter(|dir| path_matches(terms, &dir.path))
+            .collect();
+        found.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+        found.into_iter().map(|dir| dir.path.as_path()).collect()
+    }
+
+    /// Merge the database of another tool, returning how many directories
+    /// it held
+    pub fn import(&mut self, source: FrecencySource, data: &[u8]) -> Result<usize, String> {
+        let dirs = match source {
+            FrecencySource::Zoxide => parse_zoxide(data)?,
+            FrecencySource::Autojump => parse_autojump(&String::from_utf8_lossy(data), now()),
+        };
+        for dir in &dirs {
+            self.add(&dir.path, dir.rank, dir.last_access);
+        }
+        self.age();
+        self.save();
+        Ok(dirs.len())
+    }
+}
+
+/// Whether `path` holds every term in order, ignoring case, the last one
+/// within the final component
+pub fn path_matches(terms: &[&str], path: &Path) -> bool {
+    let path = path.to_string_lossy().to_lowercase();
+    let mut rest = path.as_str();
+    for (i, term) in terms.iter().enumerate() {
+        let term = term.to_lowercase();
+        let found = if i + 1 == terms.len() {
+            rest.rfind(&term)
+        } else {
+            rest.find(&term)
+        };
+        let Some(start) = found else {
+            return false;
+        };
+        rest = &rest[start + term.len()..];
+    }
+    terms.is_empty() || !rest.contains('/')
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Read `<rank>\t<last access>\t<path>` lines written by `save`
+fn parse_saved(content: &str) -> Vec<Dir> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            Some(Dir {
+                rank: fields.next()?.parse().ok()?,
+                last_access: fields.next()?.parse().ok()?,
+                path: decode_path(fields.next()?),
+            })
+        })
+        .collect()
+}
+
+/// `path` as one line of text: `%`, control characters and bytes that
+/// are not UTF-8 become `%XX`
+fn encode_path(path: &Path) -> String {
+    let mut out = String::new();
+    for chunk in path.as_os_str().as_bytes().utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if c == '%' || c.is_control() {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    out.push_str(&format!("%{byte:02X}"));
+                }
+            } else {
+                out.push(c);
+            }
+        }
+        for byte in chunk.invalid() {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+fn decode_path(encoded: &str) -> PathBuf {
+    let bytes = encoded.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match byte {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(OsString::from_vec(out))
+}
+
+/// Read zoxide's `db.zo`: a little-endian `u32` version (3), then a `u64`
+/// count of directories, each a `u64`-length path, an `f64` rank and a
+/// `u64` last access
+fn parse_zoxide(data: &[u8]) -> Result<Vec<Dir>, String> {
+    let mut reader = ZoxideReader { data };
+    let version = u32::from_le_bytes(reader.take(4)?.try_into().expect("4 bytes"));
+    if version != ZOXIDE_VERSION {
+        return Err(format!("unsupported zoxide database version {version}"));
+    }
+    let count = reader.u64()?;
+    let mut dirs = Vec::new();
+    for _ in 0..count {
+        let len = usize::try_from(reader.u64()?).map_err(|e| e.to_string())?;
+        let path = String::from_utf8_lossy(reader.take(len)?).into_owned();
+        dirs.push(Dir {
+            path: PathBuf::from(path),
+            rank: f64::from_bits(reader.u64()?),
+            last_access: reader.u64()?,
+        });
+    }
+    Ok(dirs)
+}
+
+/// Database version written by zoxide 0.8 and later
+const ZOXIDE_VERSION: u32 = 3;
+
+struct ZoxideReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ZoxideReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.data.len() < len {
+            return Err("truncated zoxide database".to_string());
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(
+            self.take(8)?.try_into().expect("8 bytes"),
+        ))
+    }
+}
+
+/// Read autojump's `autojump.txt`: `<weight>\t<path>` lines. Each visit
+/// raises a weight `w` to `sqrt(w² + 10²)`, so `(w / 10)²` is about the
+/// number of visits. The file has no access times; every directory counts
+/// as visited at `now`.
+fn parse_autojump(content: &str, now: u64) -> Vec<Dir> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (weight, path) = line.split_once('\t')?;
+            let weight: f64 = weight.trim().parse().ok()?;
+            Some(Dir {
+                path: PathBuf::from(path),
+                rank: (weight / 10.0).powi(2),
+                last_access: now,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_matches() {
+        let path = Path::new("/home/me/src/mkfm");
+        assert!(path_matches(&[], path));
+        assert!(path_matches(&["mkfm"], path));
+        assert!(path_matches(&["SRC", "fm"], path));
+        assert!(path_matches(&["me", "mk"], path));
+        // Terms in order, the last in the final component
+        assert!(!path_matches(&["mkfm", "src"], path));
+        assert!(!path_matches(&["src"], path));
+        assert!(path_matches(&["mk"], Path::new("/mk/src/mkfm")));
+    }
+
+    #[test]
+    fn test_ranking_and_aging() {
+        let now = now();
+        let mut db = Frecency::default();
+        db.add(Path::new("/old/project"), 10.0, now - 2 * WEEK);
+        db.add(Path::new("/new/project"), 2.0, now - 60);
+        db.add(Path::new("/new/project"), 1.0, now);
+        assert_eq!(
+            db.ranked(&["project"]),
+            [Path::new("/new/project"), Path::new("/old/project")]
+        );
+        assert_eq!(db.ranked(&["old", "proj"]), [Path::new("/old/project")]);
+        assert!(db.ranked(&["old"]).is_empty());
+
+        db.add(Path::new("/busy"), 10.0 * MAX_TOTAL_RANK, now);
+        db.age();
+        let total: f64 = db.dirs.iter().map(|dir| dir.rank).sum();
+        assert!(total <= MAX_TOTAL_RANK);
+        // Aged below the minimum rank, the little used ones are forgotten
+        assert_eq!(db.ranked(&[]), [Path::new("/busy")]);
+    }
+
+    #[test]
+    fn test_saved_format() {
+        let mut db = Frecency::default();
+        db.add(Path::new("/a b/c"), 2.5, 100);
+        let content = format!("{}\t{}\t{}\nbad line\n", 2.5, 100, "/a b/c");
+        assert_eq!(parse_saved(&content), db.dirs);
+    }
+
+    #[test]
+    fn test_saves_any_path() {
+        let dir = TempDir::new("frecency");
+        let file = dir.join("frecency");
+        let mut db = Frecency::load_from(Some(file.clone()));
+        let odd = [
+            PathBuf::from("/a\nb\tc"),
+            PathBuf::from("/100%"),
+            PathBuf::from(OsString::from_vec(b"/caf\xe9".to_vec())),
+            PathBuf::from("/café"),
+        ];
+        for path in &odd {
+            db.add(path, 1.0, 100);
+        }
+        db.save();
+        assert_eq!(Frecency::load_from(Some(file)).dirs, db.dirs);
+        // Only the database is left, no temporary file
+        assert_eq!(std::fs::read_dir(&*dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_visits_are_batched() {
+        let dir = TempDir::new("frecency");
+        let file = dir.join("frecency");
+        let mut db = Frecency::load_from(Some(file.clone()));
+        db.visit(Path::new("/a"));
+        db.visit(Path::new("/b"));
+        assert_eq!(Frecency::load_from(Some(file.clone())).dirs.len(), 1);
+        db.flush();
+        assert_eq!(Frecency::load_from(Some(file)).dirs.len(), 2);
+    }
+
+    #[test]
+    fn test_import() {
+        let mut zoxide = Vec::new();
+        zoxide.extend(3u32.to_le_bytes());
+        zoxide.extend(1u64.to_le_bytes());
+        zoxide.extend(4u64.to_le_bytes());
+        zoxide.extend(b"/src");
+        zoxide.extend(5.0f64.to_bits().to_le_bytes());
+        zoxide.extend(1234u64.to_le_bytes());
+        let mut db = Frecency::default();
+        assert_eq!(db.import(FrecencySource::Zoxide, &zoxide), Ok(1));
+        assert_eq!(
+            db.dirs,
+            [Dir {
+                path: PathBuf::from("/src"),
+                rank: 5.0,
+                last_access: 1234,
+            }]
+        );
+        assert!(
+            db.import(FrecencySource::Zoxide, &zoxide[..zoxide.len() - 1])
+                .is_err()
+        );
+
+        let autojump = "20.0\t/src\n10\t/tmp\n";
+        assert_eq!(
+            db.import(FrecencySource::Autojump, autojump.as_bytes()),
+            Ok(2)
+        );
+        assert_eq!(db.dirs[0].rank, 9.0);
+        assert_eq!(db.dirs[1].rank, 1.0);
+    }
+}
@@ -0,0 +1,115 @@
+//! Fuzzy matching of paths
+//!
+//! Every character of the query must appear in the path in order. Among
+//! the ways it can, the best scoring one counts: matches at the start of a
+//! path component or a word, runs of consecutive matches and matches in
+//! the file name score higher, and skipped characters cost a little. Words
+//! of the query separated by spaces each have to match. The query is
+//! case-insensitive unless it has an uppercase letter.
+
+/// Any matched character
+const MATCH: i32 = 16;
+/// A character starting a path component
+const COMPONENT_START: i32 = 12;
+/// A character starting a word within a component: after `-`, `_`, `.`
+/// or a space, or an uppercase letter after a lowercase one
+const WORD_START: i32 = 8;
+/// A character matched right after the previous one
+const CONSECUTIVE: i32 = 6;
+/// A character in the last component
+const FILE_NAME: i32 = 4;
+/// Each character skipped between two matches
+const GAP: i32 = 1;
+
+/// Score of `path` for `query`, higher being better, or `None` when it
+/// does not match
+pub fn fuzzy_score(query: &str, path: &str) -> Option<i32> {
+    query
+        .split_whitespace()
+        .map(|word| score_word(word, path))
+        .sum()
+}
+
+fn score_word(word: &str, path: &str) -> Option<i32> {
+    let case_sensitive = word.chars().any(char::is_uppercase);
+    let fold = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    };
+    let query: Vec<char> = word.chars().map(fold).collect();
+    let chars: Vec<char> = path.chars().collect();
+    let name_start = path.rfind('/').map_or(0, |i| path[..=i].chars().count());
+
+    // Best score with the previous query character matched at each index
+    let mut prev: Vec<Option<i32>> = vec![None; chars.len()];
+    for (i, &q) in query.iter().enumerate() {
+        let mut row = vec![None; chars.len()];
+        // Best score of the previous character matched before `j`, less
+        // the gap since
+        let mut best_before = (i == 0).then_some(0);
+        for (j, &c) in chars.iter().enumerate() {
+            if fold(c) == q {
+                let consecutive = (i > 0 && j > 0)
+                    .then(|| prev[j - 1])
+                    .flatten()
+                    .map(|score| score + CONSECUTIVE);
+                row[j] = best_before
+                    .max(consecutive)
+                    .map(|score| score + MATCH + bonus(&chars, j, name_start));
+            }
+            if i > 0 {
+                best_before = best_before.map(|score| score - GAP).max(prev[j]);
+            }
+        }
+        prev = row;
+    }
+    prev.into_iter().flatten().max()
+}
+
+fn bonus(chars: &[char], j: usize, name_start: usize) -> i32 {
+    let start = match j.checked_sub(1).map(|k| chars[k]) {
+        None | Some('/') => COMPONENT_START,
+        Some('-' | '_' | '.' | ' ') => WORD_START,
+        Some(before) if before.is_lowercase() && chars[j].is_uppercase() => WORD_START,
+        Some(_) => 0,
+    };
+    let name = if j >= name_start { FILE_NAME } else { 0 };
+    start + name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_in_order() {
+        assert!(fuzzy_score("smr", "src/main.rs").is_some());
+        assert!(fuzzy_score("nm", "src/main.rs").is_none());
+        assert!(fuzzy_score("app mod", "src/app/mod.rs").is_some());
+        assert!(fuzzy_score("app lib", "src/app/mod.rs").is_none());
+        assert_eq!(fuzzy_score("", "src/main.rs"), Some(0));
+    }
+
+    #[test]
+    fn test_smart_case() {
+        assert!(fuzzy_score("readme", "README.md").is_some());
+        assert!(fuzzy_score("README", "readme.md").is_none());
+    }
+
+    #[test]
+    fn test_ranking() {
+        let better = |query: &str, a: &str, b: &str| fuzzy_score(query, a) > fuzzy_score(query, b);
+        // Component starts beat matches inside words
+        assert!(better("mr", "src/main.rs", "src/summary.txt"));
+        // Runs beat scattered matches
+        assert!(better("main", "src/main.rs", "src/mkfm/app/in.rs"));
+        // The file name beats a directory of the same name
+        assert!(better("app", "src/render/app.rs", "src/app/handlers.rs"));
+        // Word starts within a name count
+        assert!(better("fb", "foo_bar.rs", "fooxbar.rs"));
+        assert!(better("fb", "fooBar.rs", "foobar.rs"));
+    }
+}
@@ -2,10 +2,16 @@
 //!
 //! Split into modules to reduce complexity.
 
+mod bookmarks;
 mod browser;
 mod clipboard;
+mod frecency;
+mod fuzzy;
 mod selection;
 
-pub use browser::Browser;
+pub use bookmarks::{Bookmarks, MarksPane, is_global, parse_mark_list};
+pub use browser::{Browser, TRASH_URI};
 pub use clipboard::Clipboard;
+pub use frecency::{Frecency, FrecencySource, path_matches};
+pub use fuzzy::fuzzy_score;
 pub use selection::Selection;
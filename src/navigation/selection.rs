@@ -1,56 +1,288 @@
-//! Visual mode selection
+//! File selection
+//!
+//! Selected entries are tracked by path so they stay attached to the same
+//! files across refreshes, sorting, filtering and fold changes. Tagged files
+//! are the user's explicit selection; the visual range is layered on top
+//! and takes precedence while it is active.
 
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use crate::filesystem::Entry;
 
-/// Selection state for visual mode
+/// Selection state for tagged files and the visual range
+#[derive(Default)]
 pub struct Selection {
-    indices: Vec<usize>,
+    /// Explicitly tagged paths and their sizes
+    tagged: HashMap<PathBuf, u64>,
+    /// Number and total size of the tagged paths, kept up to date so the
+    /// status line needs no walk over them
+    tagged_total: (usize, u64),
+    /// Paths covered by the active visual range or operator motion, which
+    /// may be empty while still overriding the tagged files
+    range: Option<HashMap<PathBuf, u64>>,
 }
 
 impl Selection {
     pub fn new() -> Self {
-        Self {
-            indices: Vec::new(),
-        }
+        Self::default()
     }
 
+    /// Clear tagged files and the range
     pub fn clear(&mut self) {
-        self.indices.clear();
+        self.tagged.clear();
+        self.tagged_total = (0, 0);
+        self.range = None;
+    }
+
+    pub fn add(&mut self, entry: &Entry) {
+        let size = entry_size(entry);
+        let (count, total) = &mut self.tagged_total;
+        match self.tagged.insert(entry.path.clone(), size) {
+            Some(old) => *total -= old,
+            None => *count += 1,
+        }
+        *total += size;
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        if let Some(size) = self.tagged.remove(path) {
+            self.tagged_total.0 -= 1;
+            self.tagged_total.1 -= size;
+        }
+    }
+
+    pub fn toggle(&mut self, entry: &Entry) {
+        if self.tagged.contains_key(&entry.path) {
+            self.remove(&entry.path);
+        } else {
+            self.add(entry);
+        }
+    }
+
+    /// Whether a path is tagged or inside the range
+    pub fn contains(&self, path: &Path) -> bool {
+        self.range.as_ref().is_some_and(|r| r.contains_key(path)) || self.tagged.contains_key(path)
     }
 
-    pub fn add(&mut self, index: usize) {
-        if !self.indices.contains(&index) {
-            self.indices.push(index);
+    /// Replace the range with the given entries
+    pub fn set_range<'a>(&mut self, entries: impl IntoIterator<Item = &'a Entry>) {
+        self.range = Some(
+            entries
+                .into_iter()
+                .map(|e| (e.path.clone(), entry_size(e)))
+                .collect(),
+        );
+    }
+
+    pub fn clear_range(&mut self) {
+        self.range = None;
+    }
+
+    pub fn has_range(&self) -> bool {
+        self.range.is_some()
+    }
+
+    fn active(&self) -> &HashMap<PathBuf, u64> {
+        self.range.as_ref().unwrap_or(&self.tagged)
+    }
+
+    /// Tag every listed entry that is not already tagged, and untag the rest
+    pub fn invert(&mut self, entries: &[Entry]) {
+        for entry in selectable(entries) {
+            self.toggle(entry);
+        }
+    }
+
+    /// Tag every listed entry
+    pub fn select_all(&mut self, entries: &[Entry]) {
+        for entry in selectable(entries) {
+            self.add(entry);
         }
     }
 
-    pub fn contains(&self, index: usize) -> bool {
-        self.indices.contains(&index)
+    /// Tag or untag listed entries whose names match a glob, returning how
+    /// many entries matched
+    pub fn select_matching(
+        &mut self,
+        entries: &[Entry],
+        pattern: &glob::Pattern,
+        tag: bool,
+    ) -> usize {
+        let mut matched = 0;
+        for entry in selectable(entries).filter(|e| pattern.matches(&e.name)) {
+            if tag {
+                self.add(entry);
+            } else {
+                self.remove(&entry.path);
+            }
+            matched += 1;
+        }
+        matched
     }
 
-    pub fn to_paths(&self, entries: &[Entry]) -> Vec<PathBuf> {
-        self.indices
+    /// The selected paths an action should use, in listing order
+    ///
+    /// The range wins over tagged files. Tagged files outside the listing are
+    /// only included when `persistent` is set and they still exist.
+    pub fn to_paths(&self, entries: &[Entry], persistent: bool) -> Vec<PathBuf> {
+        let active = self.active();
+        let mut paths: Vec<PathBuf> = entries
             .iter()
-            .filter_map(|&i| entries.get(i))
+            .filter(|e| active.contains_key(&e.path))
             .map(|e| e.path.clone())
-            .collect()
+            .collect();
+
+        if persistent && self.range.is_none() {
+            let listed: HashSet<&Path> = entries.iter().map(|e| e.path.as_path()).collect();
+            let mut elsewhere: Vec<PathBuf> = active
+                .keys()
+                .filter(|p| !listed.contains(p.as_path()))
+                .filter(|p| p.symlink_metadata().is_ok())
+                .cloned()
+                .collect();
+            elsewhere.sort();
+            paths.extend(elsewhere);
+        }
+        paths
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.indices.is_empty()
+    /// Number of selected paths and their total size, as `to_paths` sees
+    /// them except that persistent tags are counted without checking their
+    /// files still exist; shown with every frame, so it never walks the
+    /// tags
+    pub fn summary(&self, entries: &[Entry], persistent: bool) -> (usize, u64) {
+        if persistent && self.range.is_none() {
+            return self.tagged_total;
+        }
+        let active = self.active();
+        entries
+            .iter()
+            .filter_map(|e| active.get(&e.path))
+            .fold((0, 0), |(count, total), size| (count + 1, total + size))
     }
 
-    pub fn remove(&mut self, index: usize) {
-        self.indices.retain(|&i| i != index);
+    pub fn is_empty(&self) -> bool {
+        self.tagged.is_empty() && self.range.is_none()
     }
+}
 
-    pub fn toggle(&mut self, index: usize) {
-        if self.contains(index) {
-            self.remove(index);
-        } else {
-            self.add(index);
+/// Directories count towards the selection but not its size
+fn entry_size(entry: &Entry) -> u64 {
+    if entry.is_dir { 0 } else { entry.size }
+}
+
+/// Entries that can be selected (never the parent entry)
+fn selectable(entries: &[Entry]) -> impl Iterator<Item = &Entry> {
+    entries.iter().filter(|e| e.name != "..")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, is_dir: bool, size: u64) -> Entry {
+        Entry {
+            name: name.to_string(),
+            path: PathBuf::from("/tmp/dir").join(name),
+            is_dir,
+            size,
+            modified: None,
+            depth: 0,
         }
     }
+
+    fn listing() -> Vec<Entry> {
+        vec![
+            entry("..", true, 0),
+            entry("src", true, 4096),
+            entry("a.rs", false, 10),
+            entry("b.rs", false, 20),
+            entry("notes.txt", false, 5),
+        ]
+    }
+
+    #[test]
+    fn test_selection_follows_paths_across_reorder() {
+        let mut entries = listing();
+        let mut selection = Selection::new();
+        selection.add(&entries[3]);
+        entries.reverse();
+        assert_eq!(
+            selection.to_paths(&entries, false),
+            vec![PathBuf::from("/tmp/dir/b.rs")]
+        );
+    }
+
+    #[test]
+    fn test_range_takes_precedence_over_tags() {
+        let entries = listing();
+        let mut selection = Selection::new();
+        selection.add(&entries[4]);
+        selection.set_range(&entries[2..4]);
+        assert_eq!(selection.to_paths(&entries, false).len(), 2);
+        selection.clear_range();
+        assert_eq!(
+            selection.to_paths(&entries, false),
+            vec![PathBuf::from("/tmp/dir/notes.txt")]
+        );
+    }
+
+    #[test]
+    fn test_select_matching_and_summary() {
+        let entries = listing();
+        let mut selection = Selection::new();
+        let pattern = glob::Pattern::new("*.rs").unwrap();
+        assert_eq!(selection.select_matching(&entries, &pattern, true), 2);
+        assert_eq!(selection.summary(&entries, false), (2, 30));
+        selection.select_matching(&entries, &glob::Pattern::new("a*").unwrap(), false);
+        assert_eq!(selection.summary(&entries, false), (1, 20));
+    }
+
+    #[test]
+    fn test_invert_and_select_all_skip_parent() {
+        let entries = listing();
+        let mut selection = Selection::new();
+        selection.add(&entries[1]);
+        selection.invert(&entries);
+        assert!(!selection.contains(&entries[0].path));
+        assert!(!selection.contains(&entries[1].path));
+        assert_eq!(selection.summary(&entries, false), (3, 35));
+        selection.select_all(&entries);
+        assert_eq!(selection.summary(&entries, false).0, 4);
+    }
+
+    #[test]
+    fn test_tags_outside_listing_need_persistence() {
+        let entries = listing();
+        let mut selection = Selection::new();
+        let mut outside = entry("elsewhere", false, 1);
+        outside.path = std::env::temp_dir();
+        selection.add(&outside);
+        assert!(selection.to_paths(&entries, false).is_empty());
+        assert_eq!(
+            selection.to_paths(&entries, true),
+            vec![std::env::temp_dir()]
+        );
+    }
+
+    #[test]
+    fn test_summary_follows_tag_changes() {
+        let entries = listing();
+        let mut selection = Selection::new();
+        let outside = entry("elsewhere", false, 100);
+        selection.add(&outside);
+        selection.add(&entries[2]);
+        selection.add(&entries[2]);
+        selection.toggle(&entries[3]);
+        assert_eq!(selection.summary(&entries, true), (3, 130));
+        assert_eq!(selection.summary(&entries, false), (2, 30));
+        selection.remove(&outside.path);
+        selection.remove(&outside.path);
+        assert_eq!(selection.summary(&entries, true), (2, 30));
+        selection.set_range(&entries[4..]);
+        assert_eq!(selection.summary(&entries, true), (1, 5));
+        selection.clear();
+        assert_eq!(selection.summary(&entries, true), (0, 0));
+    }
 }
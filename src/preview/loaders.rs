@@ -4,20 +4,30 @@ use image::GenericImageView;
 
 use super::{PreviewContent, is_image_file, is_media_file, is_svg_file, is_text_file};
 
-/// Load preview content based on file type
+/// Lines a text preview shows
+const TEXT_PREVIEW_LINES: usize = 50;
+
+/// Lines shown above a highlighted line
+const LINES_ABOVE_HIGHLIGHT: usize = 10;
+
+/// Load preview content based on file type; with a `line`, the file is
+/// shown as text around that line, highlighted
 pub fn load_preview_content(
     path: &std::path::Path,
+    line: Option<usize>,
     max_width: u32,
     max_height: u32,
 ) -> PreviewContent {
-    if is_svg_file(path) {
+    if let Some(line) = line {
+        load_text_preview(path, Some(line))
+    } else if is_svg_file(path) {
         load_svg_preview(path, max_width, max_height)
     } else if is_image_file(path) {
         load_image_preview(path, max_width, max_height)
     } else if is_media_file(path) {
         load_media_preview(path, max_width, max_height)
     } else if is_text_file(path) {
-        load_text_preview(path)
+        load_text_preview(path, None)
     } else {
         load_unsupported(path)
     }
@@ -109,11 +119,22 @@ fn load_media_preview(path: &std::path::Path, max_width: u32, max_height: u32) -
     }
 }
 
-fn load_text_preview(path: &std::path::Path) -> PreviewContent {
+/// The first lines of a file, or those around `line` (from 1)
+fn load_text_preview(path: &std::path::Path, line: Option<usize>) -> PreviewContent {
     match std::fs::read_to_string(path) {
         Ok(content) => {
-            let lines: Vec<String> = content.lines().take(50).map(|s| s.to_string()).collect();
-            PreviewContent::Text(lines)
+            let index = line.map(|line| line.saturating_sub(1));
+            let start = index.map_or(0, |i| i.saturating_sub(LINES_ABOVE_HIGHLIGHT));
+            let lines: Vec<String> = content
+                .lines()
+                .skip(start)
+                .take(TEXT_PREVIEW_LINES)
+                .map(|s| s.to_string())
+                .collect();
+            PreviewContent::Text {
+                lines,
+                highlight: index.map(|i| i - start),
+            }
         }
         Err(e) => PreviewContent::Error(format!("Read failed: {}", e)),
     }
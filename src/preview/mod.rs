@@ -65,7 +65,11 @@ pub enum PreviewContent {
         width: u32,
         height: u32,
     },
-    Text(Vec<String>),
+    Text {
+        lines: Vec<String>,
+        /// Index of a line to highlight, such as a grep match
+        highlight: Option<usize>,
+    },
     Media {
         media_type: MediaType,
         metadata: MediaMetadata,
@@ -81,7 +85,9 @@ impl PreviewContent {
     pub fn dimensions(&self, max_width: u32, max_height: u32) -> (u32, u32) {
         match self {
             PreviewContent::Image { width, height, .. } => (*width, *height),
-            PreviewContent::Text(lines) => calculate_text_dimensions(lines, max_width, max_height),
+            PreviewContent::Text { lines, .. } => {
+                calculate_text_dimensions(lines, max_width, max_height)
+            }
             PreviewContent::Media {
                 thumb_width,
                 thumb_height,
@@ -20,8 +20,8 @@ pub fn render_preview(
         } => {
             canvas.draw_rgba(0, 0, *img_w, *img_h, data);
         }
-        PreviewContent::Text(lines) => {
-            render_text_preview(canvas, text_renderer, lines);
+        PreviewContent::Text { lines, highlight } => {
+            render_text_preview(canvas, text_renderer, lines, *highlight);
         }
         PreviewContent::Media {
             media_type,
@@ -72,7 +72,12 @@ fn render_message(canvas: &mut Canvas, tr: &mut TextRenderer, msg: &str, is_erro
     );
 }
 
-fn render_text_preview(canvas: &mut Canvas, tr: &mut TextRenderer, lines: &[String]) {
+fn render_text_preview(
+    canvas: &mut Canvas,
+    tr: &mut TextRenderer,
+    lines: &[String],
+    highlight: Option<usize>,
+) {
     let width = canvas.width();
     let height = canvas.height();
     let font_size = 12.0;
@@ -83,6 +88,15 @@ fn render_text_preview(canvas: &mut Canvas, tr: &mut TextRenderer, lines: &[Stri
         if y + line_height > height as i32 {
             break;
         }
+        if highlight == Some(i) {
+            canvas.fill_rect(
+                0.0,
+                y as f32,
+                width as f32,
+                line_height as f32,
+                Color::from_rgba8(90, 80, 30, 255),
+            );
+        }
         let line_rect = Rect::new(0, y, width, line_height as u32);
         let display_line = if line.len() > 80 {
             &line[..80]
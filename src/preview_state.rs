@@ -13,6 +13,8 @@ use crate::preview::PreviewCache;
 /// Manages preview surface state and lifecycle
 pub struct PreviewState {
     pub path: Option<PathBuf>,
+    /// Line highlighted in a text preview
+    pub line: Option<usize>,
     pub needs_render: bool,
     pub cache: PreviewCache,
     pub attached: Option<AttachedSurfaceId>,
@@ -27,6 +29,7 @@ impl PreviewState {
         // Always use subsurfaces - attached surfaces may be size-limited by the compositor
         Self {
             path: None,
+            line: None,
             needs_render: false,
             cache: PreviewCache::new(),
             attached: None,
@@ -50,7 +53,9 @@ impl PreviewState {
         win_h: u32,
     ) {
         let current_preview_file = app.current_previewable_path();
-        let should_show = app.overlay_enabled && current_preview_file.is_some();
+        // A grep match is shown even with the overlay off
+        let line = app.preview_line();
+        let should_show = (app.overlay_enabled || line.is_some()) && current_preview_file.is_some();
 
         if should_show {
             self.show_preview(
@@ -59,6 +64,7 @@ impl PreviewState {
                 window_id,
                 overlay_config,
                 &current_preview_file,
+                line,
                 win_w,
                 win_h,
             );
@@ -75,11 +81,12 @@ impl PreviewState {
         window_id: WindowId,
         config: &OverlayConfig,
         current_file: &Option<PathBuf>,
+        line: Option<usize>,
         win_w: u32,
         win_h: u32,
     ) {
         let have_preview = self.attached.is_some() || self.subsurface.is_some();
-        let file_changed = self.path != *current_file;
+        let file_changed = self.path != *current_file || self.line != line;
         let dims_changed = self.last_win_w != win_w || self.last_win_h != win_h;
 
         if let Some(path) = current_file {
@@ -88,7 +95,9 @@ impl PreviewState {
             if needs_recreate {
                 let preview_width = config.max_width.resolve(win_w) as u32;
                 let preview_height = config.max_height.resolve(win_h) as u32;
-                let content = self.cache.get_or_load(path, preview_width, preview_height);
+                let content = self
+                    .cache
+                    .get_or_load(path, line, preview_width, preview_height);
                 let (actual_w, actual_h) = content.dimensions(preview_width, preview_height);
 
                 // Close existing surfaces if file or dimensions changed
@@ -97,6 +106,7 @@ impl PreviewState {
                 }
 
                 self.path = current_file.clone();
+                self.line = line;
                 self.last_win_w = win_w;
                 self.last_win_h = win_h;
 
@@ -114,6 +124,7 @@ impl PreviewState {
         if self.attached.is_some() || self.subsurface.is_some() {
             self.close_surfaces(mkapp);
             self.path = None;
+            self.line = None;
             self.needs_render = false;
             self.cache.invalidate();
         }
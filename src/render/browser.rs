@@ -2,9 +2,10 @@
 
 use mkframe::{Canvas, HAlign, Rect, TextRenderer};
 
+use crate::app::SubstitutePreview;
 use crate::config::Theme;
 use crate::filesystem;
-use crate::navigation::{Browser, Selection};
+use crate::navigation::{Browser, Selection, TRASH_URI};
 
 use super::primitives::{draw_border, draw_header, draw_list_row_bg, draw_text};
 use super::{RenderColors, RenderLayout};
@@ -17,6 +18,8 @@ pub struct FileListContext<'a> {
     pub search_matches: &'a [usize],
     pub theme: &'a Theme,
     pub icons_enabled: bool,
+    /// New names to show next to the entries a `:s` would rename
+    pub renames: Option<&'a SubstitutePreview>,
 }
 
 /// Get the header text for a browser pane
@@ -28,6 +31,10 @@ pub fn browser_header_text(browser: &Browser) -> String {
         } else {
             format!("[{}]/{}", archive_path.to_string_lossy(), prefix)
         }
+    } else if browser.in_trash() {
+        TRASH_URI.to_string()
+    } else if let Some(query) = browser.find_query() {
+        format!("{} [find {query}]", browser.path.to_string_lossy())
     } else {
         browser.path.to_string_lossy().to_string()
     }
@@ -51,6 +58,7 @@ pub fn render_browser_pane(
     colors: &RenderColors,
     layout: &RenderLayout,
     icons_enabled: bool,
+    renames: Option<&SubstitutePreview>,
 ) {
     let border = if is_focused {
         colors.border_focused
@@ -86,6 +94,7 @@ pub fn render_browser_pane(
         search_matches,
         theme,
         icons_enabled,
+        renames,
     };
     render_file_list(
         canvas, tr, &ctx, inner_x, list_y, inner_w, list_h, colors, layout,
@@ -118,7 +127,7 @@ fn render_file_list(
     {
         let row_y = y + ((i - scroll_offset) as i32 * layout.line_height);
         let is_cursor = i == ctx.browser.cursor;
-        let is_selected = ctx.selection.contains(i);
+        let is_selected = ctx.selection.contains(&entry.path);
         let is_match = ctx.search_highlight && ctx.search_matches.contains(&i);
 
         draw_list_row_bg(
@@ -144,6 +153,7 @@ fn render_file_list(
             colors,
             layout,
             ctx.icons_enabled,
+            ctx.renames,
         );
     }
 }
@@ -169,15 +179,24 @@ fn render_file_entry(
     colors: &RenderColors,
     layout: &RenderLayout,
     icons_enabled: bool,
+    renames: Option<&SubstitutePreview>,
 ) {
     let indent = entry.depth as i32 * 16;
+    let name = match renames.and_then(|r| r.names.get(&entry.path)) {
+        Some(new_name) => format!("{} → {new_name}", entry.name),
+        None => entry.name.clone(),
+    };
     let display = if icons_enabled {
         let icon = select_icon(entry, browser, theme);
-        format!("{} {}", icon, entry.name)
+        format!("{} {}", icon, name)
     } else {
-        entry.name.clone()
+        name
     };
-    let color = if entry.is_dir {
+    // Renames that would collide are shown, but in the error color
+    let renamed = renames.filter(|r| r.names.contains_key(&entry.path));
+    let color = if renamed.is_some_and(|r| r.error.is_some()) {
+        colors.error
+    } else if entry.is_dir {
         colors.directory
     } else {
         colors.fg
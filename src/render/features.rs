@@ -65,7 +65,7 @@ fn calculate_panel_height(count: usize, detail: bool, max: u32, layout: &RenderL
     (count as u32 * layout.line_height as u32 + base).min(max - 100)
 }
 
-fn render_dim_overlay(canvas: &mut Canvas, width: u32, height: u32) {
+pub(super) fn render_dim_overlay(canvas: &mut Canvas, width: u32, height: u32) {
     canvas.fill_rect(
         0.0,
         0.0,
@@ -75,7 +75,14 @@ fn render_dim_overlay(canvas: &mut Canvas, width: u32, height: u32) {
     );
 }
 
-fn render_panel_background(canvas: &mut Canvas, theme: &Theme, x: i32, y: i32, w: u32, h: u32) {
+pub(super) fn render_panel_background(
+    canvas: &mut Canvas,
+    theme: &Theme,
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+) {
     let bg = Color::from_rgba8(
         theme.background.r,
         theme.background.g,
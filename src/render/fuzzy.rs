@@ -0,0 +1,126 @@
+//! Fuzzy finder popup rendering
+
+use mkframe::{Canvas, HAlign, Rect, TextColor, TextRenderer};
+
+use crate::app::{FinderSource, FuzzyFinder};
+use crate::config::Theme;
+
+use super::features::{render_dim_overlay, render_panel_background};
+use super::primitives::{draw_border, draw_header, draw_row_bg, draw_text};
+use super::status::render_line;
+use super::{RenderColors, RenderLayout};
+
+/// Render the query and best matches of the fuzzy finder
+#[allow(clippy::too_many_arguments)]
+pub fn render_fuzzy_finder(
+    canvas: &mut Canvas,
+    tr: &mut TextRenderer,
+    finder: &FuzzyFinder,
+    indexing: bool,
+    width: u32,
+    height: u32,
+    theme: &Theme,
+    colors: &RenderColors,
+    layout: &RenderLayout,
+) {
+    let panel_w = (width as f32 * 0.7).min(800.0) as u32;
+    let panel_h = (height as f32 * 0.6) as u32;
+    let panel_x = (width - panel_w) as i32 / 2;
+    let panel_y = (height - panel_h) as i32 / 2;
+
+    render_dim_overlay(canvas, width, height);
+    render_panel_background(canvas, theme, panel_x, panel_y, panel_w, panel_h);
+    draw_border(
+        canvas,
+        panel_x,
+        panel_y,
+        panel_w,
+        panel_h,
+        colors.border_focused,
+    );
+
+    let header = match &finder.source {
+        FinderSource::Files { root, .. } => format!(
+            "Files in {} - {}/{}{}",
+            root.display(),
+            finder.matches.len(),
+            finder.candidates.len(),
+            if indexing { ", indexing" } else { "" }
+        ),
+        FinderSource::Directories => format!(
+            "Visited directories - {}/{}",
+            finder.matches.len(),
+            finder.candidates.len()
+        ),
+    };
+    draw_header(
+        canvas,
+        tr,
+        panel_x + 1,
+        panel_y + 1,
+        panel_w - 2,
+        &header,
+        colors,
+        layout,
+    );
+
+    let text_w = panel_w - layout.padding as u32 * 2;
+    let query_y = panel_y + 1 + layout.header_height;
+    let query_rect = Rect::new(
+        panel_x + layout.padding,
+        query_y,
+        text_w,
+        layout.line_height as u32,
+    );
+    render_line(canvas, tr, "> ", &finder.query, query_rect, colors, layout);
+
+    let content_y = query_y + layout.line_height;
+    let content_h = panel_h as i32 - layout.header_height - layout.line_height * 2 - 6;
+    let visible = (content_h / layout.line_height).max(0) as usize;
+    let scroll = finder.cursor.saturating_sub(visible.saturating_sub(1));
+
+    for (i, &index) in finder.matches.iter().enumerate().skip(scroll).take(visible) {
+        let row_y = content_y + ((i - scroll) as i32 * layout.line_height);
+        if i == finder.cursor {
+            draw_row_bg(
+                canvas,
+                panel_x + 1,
+                row_y,
+                panel_w - 2,
+                layout.line_height,
+                colors.cursor_bg,
+            );
+        }
+        let rect = Rect::new(
+            panel_x + layout.padding,
+            row_y,
+            text_w,
+            layout.line_height as u32,
+        );
+        draw_text(
+            canvas,
+            tr,
+            &finder.candidates[index],
+            rect,
+            layout.font_size,
+            colors.fg,
+            HAlign::Left,
+        );
+    }
+
+    let hint_rect = Rect::new(
+        panel_x + layout.padding,
+        panel_y + panel_h as i32 - layout.line_height - 4,
+        text_w,
+        layout.line_height as u32,
+    );
+    draw_text(
+        canvas,
+        tr,
+        "Enter open, Ctrl-v/Ctrl-s open in a split, Escape close",
+        hint_rect,
+        layout.font_size - 2.0,
+        TextColor::rgb(128, 128, 128),
+        HAlign::Center,
+    );
+}
@@ -0,0 +1,118 @@
+//! Marks panel rendering
+
+use mkframe::{Canvas, HAlign, Rect, TextColor, TextRenderer};
+
+use crate::config::Theme;
+use crate::navigation::{Bookmarks, MarksPane, is_global};
+
+use super::features::{render_dim_overlay, render_panel_background};
+use super::primitives::{draw_border, draw_header, draw_row_bg, draw_text};
+use super::{RenderColors, RenderLayout};
+
+/// Render the marks overlay panel
+#[allow(clippy::too_many_arguments)]
+pub fn render_marks_panel(
+    canvas: &mut Canvas,
+    tr: &mut TextRenderer,
+    bookmarks: &Bookmarks,
+    pane: &MarksPane,
+    width: u32,
+    height: u32,
+    theme: &Theme,
+    colors: &RenderColors,
+    layout: &RenderLayout,
+) {
+    let panel_w = (width as f32 * 0.6).min(600.0) as u32;
+    let panel_h = (bookmarks.len() as u32 * layout.line_height as u32 + 60).min(height - 100);
+    let panel_x = (width - panel_w) as i32 / 2;
+    let panel_y = (height - panel_h) as i32 / 2;
+
+    render_dim_overlay(canvas, width, height);
+    render_panel_background(canvas, theme, panel_x, panel_y, panel_w, panel_h);
+    draw_border(
+        canvas,
+        panel_x,
+        panel_y,
+        panel_w,
+        panel_h,
+        colors.border_focused,
+    );
+
+    let header = if pane.renaming {
+        "Marks - press the new letter"
+    } else {
+        "Marks - Enter jump, d delete, r rename, q close"
+    };
+    draw_header(
+        canvas,
+        tr,
+        panel_x + 1,
+        panel_y + 1,
+        panel_w - 2,
+        header,
+        colors,
+        layout,
+    );
+
+    let content_y = panel_y + 1 + layout.header_height;
+    let content_h = panel_h as i32 - layout.header_height - 2;
+    let visible = (content_h / layout.line_height).max(0) as usize;
+    let scroll = pane.cursor.saturating_sub(visible.saturating_sub(1));
+
+    for (i, (c, path)) in bookmarks.iter().enumerate().skip(scroll).take(visible) {
+        let row_y = content_y + ((i - scroll) as i32 * layout.line_height);
+        if i == pane.cursor {
+            draw_row_bg(
+                canvas,
+                panel_x + 1,
+                row_y,
+                panel_w - 2,
+                layout.line_height,
+                colors.cursor_bg,
+            );
+        }
+
+        // Global marks are persistent, so set them apart from session marks
+        let key_color = if is_global(c) {
+            colors.directory
+        } else {
+            colors.fg
+        };
+        let key_rect = Rect::new(
+            panel_x + layout.padding,
+            row_y,
+            24,
+            layout.line_height as u32,
+        );
+        draw_text(
+            canvas,
+            tr,
+            &c.to_string(),
+            key_rect,
+            layout.font_size,
+            key_color,
+            HAlign::Left,
+        );
+
+        let path_rect = Rect::new(
+            panel_x + layout.padding + 28,
+            row_y,
+            panel_w - layout.padding as u32 * 2 - 28,
+            layout.line_height as u32,
+        );
+        let path_color = if path.is_dir() {
+            colors.fg
+        } else {
+            TextColor::rgb(128, 128, 128)
+        };
+        draw_text(
+            canvas,
+            tr,
+            &path.display().to_string(),
+            path_rect,
+            layout.font_size,
+            path_color,
+            HAlign::Left,
+        );
+    }
+}
@@ -4,7 +4,11 @@
 
 mod browser;
 mod features;
+mod fuzzy;
+mod marks;
 mod primitives;
+mod quickfix;
+mod rename;
 mod status;
 mod tasks;
 
@@ -14,6 +18,10 @@ use crate::config::Theme;
 
 pub use browser::render_browser_pane;
 pub use features::render_feature_panel;
+pub use fuzzy::render_fuzzy_finder;
+pub use marks::render_marks_panel;
+pub use quickfix::render_quickfix_pane;
+pub use rename::render_rename_preview;
 pub use status::render_status_bar;
 pub use tasks::render_task_pane;
 
@@ -25,6 +33,7 @@ pub struct RenderColors {
     pub selected_bg: Color,
     pub search_highlight_bg: Color,
     pub directory: TextColor,
+    pub error: TextColor,
     pub header_bg: Color,
     pub status_bg: Color,
     pub border: Color,
@@ -40,6 +49,7 @@ impl RenderColors {
             selected_bg: theme.selection_bg.to_color(),
             search_highlight_bg: theme.search_highlight_bg.to_color(),
             directory: theme.directory.to_text_color(),
+            error: theme.error.to_text_color(),
             header_bg: theme.header_bg.to_color(),
             status_bg: theme.status_bg.to_color(),
             border: theme.border.to_color(),
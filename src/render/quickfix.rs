@@ -0,0 +1,91 @@
+//! Grep results pane rendering
+
+use mkframe::{Canvas, HAlign, Rect, TextColor, TextRenderer};
+
+use crate::app::Quickfix;
+
+use super::primitives::{draw_header, draw_row_bg, draw_text};
+use super::{RenderColors, RenderLayout};
+
+/// Render the `path:line:text` list of the last `:grep`, scrolled to keep
+/// the cursor in view
+#[allow(clippy::too_many_arguments)]
+pub fn render_quickfix_pane(
+    canvas: &mut Canvas,
+    tr: &mut TextRenderer,
+    quickfix: &Quickfix,
+    running: bool,
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+    is_focused: bool,
+    colors: &RenderColors,
+    layout: &RenderLayout,
+) {
+    let border = if is_focused {
+        colors.border_focused
+    } else {
+        colors.border
+    };
+    canvas.fill_rect(x as f32, y as f32, w as f32, 1.0, border);
+    canvas.fill_rect(
+        x as f32,
+        (y + 1) as f32,
+        w as f32,
+        (h - 1) as f32,
+        colors.bg,
+    );
+
+    let count = quickfix.hits.len();
+    let title = if quickfix.truncated() {
+        format!(
+            "Grep {} (first {} matches, search stopped)",
+            quickfix.pattern, count
+        )
+    } else {
+        format!(
+            "Grep {} ({} match{}{})",
+            quickfix.pattern,
+            count,
+            if count == 1 { "" } else { "es" },
+            if running { ", searching" } else { "" }
+        )
+    };
+    draw_header(canvas, tr, x, y + 1, w, &title, colors, layout);
+
+    let content_y = y + 1 + layout.header_height;
+    let content_h = h as i32 - 1 - layout.header_height;
+    let visible = (content_h / layout.line_height).max(0) as usize;
+    let text_w = (w as i32 - layout.padding * 2).max(0) as u32;
+
+    if quickfix.hits.is_empty() {
+        let msg = if running {
+            "Searching..."
+        } else {
+            "No matches"
+        };
+        let rect = Rect::new(layout.padding, content_y, text_w, layout.line_height as u32);
+        let dim = TextColor::rgb(128, 128, 128);
+        draw_text(canvas, tr, msg, rect, layout.font_size, dim, HAlign::Center);
+        return;
+    }
+
+    let start = (quickfix.cursor + 1).saturating_sub(visible);
+    for (i, hit) in quickfix.hits.iter().enumerate().skip(start).take(visible) {
+        let row_y = content_y + ((i - start) as i32 * layout.line_height);
+        if i == quickfix.cursor {
+            draw_row_bg(canvas, x, row_y, w, layout.line_height, colors.cursor_bg);
+        }
+        let rect = Rect::new(x + layout.padding, row_y, text_w, layout.line_height as u32);
+        draw_text(
+            canvas,
+            tr,
+            &quickfix.hit_label(hit),
+            rect,
+            layout.font_size,
+            colors.fg,
+            HAlign::Left,
+        );
+    }
+}
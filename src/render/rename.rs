@@ -0,0 +1,81 @@
+//! Bulk rename preview rendering
+
+use mkframe::{Canvas, HAlign, Rect, TextRenderer};
+
+use crate::app::RenamePreview;
+use crate::config::Theme;
+
+use super::features::{render_dim_overlay, render_panel_background};
+use super::primitives::{draw_border, draw_header, draw_text};
+use super::{RenderColors, RenderLayout};
+
+/// Render the old → new names of a bulk rename awaiting confirmation
+#[allow(clippy::too_many_arguments)]
+pub fn render_rename_preview(
+    canvas: &mut Canvas,
+    tr: &mut TextRenderer,
+    preview: &RenamePreview,
+    width: u32,
+    height: u32,
+    theme: &Theme,
+    colors: &RenderColors,
+    layout: &RenderLayout,
+) {
+    let changes = &preview.plan.changes;
+    let panel_w = (width as f32 * 0.7).min(800.0) as u32;
+    let panel_h = (changes.len() as u32 * layout.line_height as u32 + 60).min(height - 100);
+    let panel_x = (width - panel_w) as i32 / 2;
+    let panel_y = (height - panel_h) as i32 / 2;
+
+    render_dim_overlay(canvas, width, height);
+    render_panel_background(canvas, theme, panel_x, panel_y, panel_w, panel_h);
+    draw_border(
+        canvas,
+        panel_x,
+        panel_y,
+        panel_w,
+        panel_h,
+        colors.border_focused,
+    );
+
+    let header = format!("Rename {} entries - Enter apply, Esc cancel", changes.len());
+    draw_header(
+        canvas,
+        tr,
+        panel_x + 1,
+        panel_y + 1,
+        panel_w - 2,
+        &header,
+        colors,
+        layout,
+    );
+
+    let content_y = panel_y + 1 + layout.header_height;
+    let content_h = panel_h as i32 - layout.header_height - 2;
+    let visible = (content_h / layout.line_height).max(0) as usize;
+    let scroll = preview.scroll.min(changes.len().saturating_sub(visible));
+
+    for (i, (old, new)) in changes.iter().enumerate().skip(scroll).take(visible) {
+        let row_y = content_y + ((i - scroll) as i32 * layout.line_height);
+        // Names relative to the entry's directory, so moves into a new
+        // subdirectory show the path they take
+        let dir = old.parent().unwrap_or(old);
+        let old_name = old.file_name().unwrap_or_default().to_string_lossy();
+        let new_name = new.strip_prefix(dir).unwrap_or(new).display();
+        let rect = Rect::new(
+            panel_x + layout.padding,
+            row_y,
+            panel_w - layout.padding as u32 * 2,
+            layout.line_height as u32,
+        );
+        draw_text(
+            canvas,
+            tr,
+            &format!("{old_name} → {new_name}"),
+            rect,
+            layout.font_size,
+            colors.fg,
+            HAlign::Left,
+        );
+    }
+}
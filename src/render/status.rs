@@ -2,6 +2,8 @@
 
 use mkframe::{Canvas, HAlign, Rect, TextRenderer};
 
+use crate::cmdline::LineEditor;
+use crate::filesystem::format_size;
 use crate::input::Mode;
 
 use super::primitives::draw_text;
@@ -13,8 +15,8 @@ pub fn render_status_bar(
     canvas: &mut Canvas,
     tr: &mut TextRenderer,
     mode: &Mode,
-    command_buffer: &str,
-    search_buffer: &str,
+    command_line: &LineEditor,
+    search_line: &LineEditor,
     last_search: Option<&str>,
     search_highlight: bool,
     search_matches: &[usize],
@@ -22,6 +24,8 @@ pub fn render_status_bar(
     active_jobs: usize,
     failed_jobs: usize,
     cursor_info: Option<(usize, usize)>,
+    selection_info: Option<(usize, u64)>,
+    message: Option<(&str, bool)>,
     y: i32,
     w: u32,
     colors: &RenderColors,
@@ -43,8 +47,8 @@ pub fn render_status_bar(
     );
 
     match mode {
-        Mode::Command => render_command_mode(canvas, tr, command_buffer, rect, colors, layout),
-        Mode::Search => render_search_mode(canvas, tr, search_buffer, rect, colors, layout),
+        Mode::Command => render_line(canvas, tr, ":", command_line, rect, colors, layout),
+        Mode::Search => render_line(canvas, tr, "/", search_line, rect, colors, layout),
         _ => render_normal_mode(
             canvas,
             tr,
@@ -56,6 +60,8 @@ pub fn render_status_bar(
             active_jobs,
             failed_jobs,
             cursor_info,
+            selection_info,
+            message,
             rect,
             colors,
             layout,
@@ -63,15 +69,17 @@ pub fn render_status_bar(
     }
 }
 
-fn render_command_mode(
+/// Draw an editable line with its prompt and a caret at the cursor
+pub(super) fn render_line(
     canvas: &mut Canvas,
     tr: &mut TextRenderer,
-    buffer: &str,
+    prompt: &str,
+    line: &LineEditor,
     rect: Rect,
     colors: &RenderColors,
     layout: &RenderLayout,
 ) {
-    let text = format!(":{}", buffer);
+    let text = format!("{}{}", prompt, line.text());
     draw_text(
         canvas,
         tr,
@@ -81,25 +89,16 @@ fn render_command_mode(
         colors.fg,
         HAlign::Left,
     );
-}
 
-fn render_search_mode(
-    canvas: &mut Canvas,
-    tr: &mut TextRenderer,
-    buffer: &str,
-    rect: Rect,
-    colors: &RenderColors,
-    layout: &RenderLayout,
-) {
-    let text = format!("/{}", buffer);
-    draw_text(
-        canvas,
-        tr,
-        &text,
-        rect,
-        layout.font_size,
-        colors.fg,
-        HAlign::Left,
+    let before = format!("{}{}", prompt, line.before_cursor());
+    let (caret_x, _) = tr.measure_text(&before, layout.font_size);
+    let caret_h = layout.font_size * 1.2;
+    canvas.fill_rect(
+        rect.x as f32 + caret_x,
+        rect.y as f32 + (rect.height as f32 - caret_h) / 2.0,
+        1.0,
+        caret_h,
+        colors.border_focused,
     );
 }
 
@@ -115,6 +114,8 @@ fn render_normal_mode(
     active_jobs: usize,
     failed_jobs: usize,
     cursor_info: Option<(usize, usize)>,
+    selection_info: Option<(usize, u64)>,
+    message: Option<(&str, bool)>,
     rect: Rect,
     colors: &RenderColors,
     layout: &RenderLayout,
@@ -129,7 +130,19 @@ fn render_normal_mode(
         HAlign::Left,
     );
 
-    if search_highlight && let Some(pattern) = last_search {
+    // A message takes the center over the search summary until dismissed
+    if let Some((text, is_error)) = message {
+        let color = if is_error { colors.error } else { colors.fg };
+        draw_text(
+            canvas,
+            tr,
+            text,
+            rect,
+            layout.font_size,
+            color,
+            HAlign::Center,
+        );
+    } else if search_highlight && let Some(pattern) = last_search {
         let info = format_search_info(pattern, search_matches, current_match);
         draw_text(
             canvas,
@@ -142,7 +155,7 @@ fn render_normal_mode(
         );
     }
 
-    let right = format_right_status(active_jobs, failed_jobs, cursor_info);
+    let right = format_right_status(active_jobs, failed_jobs, selection_info, cursor_info);
     if !right.is_empty() {
         draw_text(
             canvas,
@@ -165,7 +178,12 @@ fn format_search_info(pattern: &str, matches: &[usize], current: Option<usize>)
     }
 }
 
-fn format_right_status(active: usize, failed: usize, cursor: Option<(usize, usize)>) -> String {
+fn format_right_status(
+    active: usize,
+    failed: usize,
+    selection: Option<(usize, u64)>,
+    cursor: Option<(usize, usize)>,
+) -> String {
     let mut s = String::new();
     if active > 0 {
         s.push_str(&format!("\u{f0f6} {} ", active));
@@ -173,6 +191,9 @@ fn format_right_status(active: usize, failed: usize, cursor: Option<(usize, usiz
     if failed > 0 {
         s.push_str(&format!("\u{f071} {} ", failed));
     }
+    if let Some((count, size)) = selection {
+        s.push_str(&format!("{} sel ({}) ", count, format_size(size)));
+    }
     if let Some((cur, total)) = cursor {
         s.push_str(&format!("{}/{}", cur + 1, total));
     }
@@ -2,11 +2,24 @@
 
 use mkframe::{Canvas, Color, HAlign, Rect, TextColor, TextRenderer};
 
+use std::time::Duration;
+
+use crate::filesystem::format_size;
 use crate::jobs::{Job, JobStatus};
 
 use super::primitives::{draw_header, draw_row_bg, draw_text};
 use super::{RenderColors, RenderLayout};
 
+/// Width of the progress bar and status column
+const STATUS_WIDTH: i32 = 100;
+
+/// Width of the current file, throughput and ETA left of a progress bar
+const DETAIL_WIDTH: i32 = 260;
+
+/// Keys for the fixes offered on a failed job
+const FAILED_JOB_KEYS: &str =
+    "r retry  R replace destination  D other destination  o/O reveal  y copy error  x dismiss";
+
 /// Render the task/error list pane
 #[allow(clippy::too_many_arguments)]
 pub fn render_task_pane(
@@ -20,6 +33,7 @@ pub fn render_task_pane(
     y: i32,
     w: u32,
     h: u32,
+    output_of: Option<&Job>,
     is_focused: bool,
     colors: &RenderColors,
     layout: &RenderLayout,
@@ -38,12 +52,19 @@ pub fn render_task_pane(
         colors.bg,
     );
 
-    draw_header(canvas, tr, x, y + 1, w, title, colors, layout);
-
     let content_y = y + 1 + layout.header_height;
     let content_h = h as i32 - 1 - layout.header_height;
     let visible = (content_h / layout.line_height).max(0) as usize;
 
+    if let Some(job) = output_of {
+        let title = format!("Output: {}", job.description);
+        draw_header(canvas, tr, x, y + 1, w, &title, colors, layout);
+        render_job_output(canvas, tr, job, content_y, w, visible, colors, layout);
+        return;
+    }
+
+    draw_header(canvas, tr, x, y + 1, w, title, colors, layout);
+
     if jobs.is_empty() {
         render_empty_message(canvas, tr, empty_msg, content_y, w, colors, layout);
         return;
@@ -58,6 +79,56 @@ pub fn render_task_pane(
     }
 }
 
+/// Show the tail of a job's captured output, most recent lines last
+#[allow(clippy::too_many_arguments)]
+fn render_job_output(
+    canvas: &mut Canvas,
+    tr: &mut TextRenderer,
+    job: &Job,
+    y: i32,
+    w: u32,
+    visible: usize,
+    colors: &RenderColors,
+    layout: &RenderLayout,
+) {
+    let mut lines: Vec<String> = Vec::new();
+    if let JobStatus::Failed(error) = &job.status
+        && !job.is_batch()
+    {
+        lines.push(format!("Error: {}", error.message));
+        if let Some(path) = &error.path {
+            lines.push(format!("Path: {}", path.display()));
+        }
+        if let Some(suggestion) = error.suggestion() {
+            lines.push(format!("Fix: {suggestion}"));
+        }
+        lines.push(FAILED_JOB_KEYS.to_string());
+    }
+    lines.extend(job.output.lines().map(str::to_string));
+    if lines.is_empty() {
+        render_empty_message(canvas, tr, "No output", y, w, colors, layout);
+        return;
+    }
+    let start = lines.len().saturating_sub(visible);
+    for (i, line) in lines[start..].iter().enumerate() {
+        let rect = Rect::new(
+            layout.padding,
+            y + i as i32 * layout.line_height,
+            w - layout.padding as u32 * 2,
+            layout.line_height as u32,
+        );
+        draw_text(
+            canvas,
+            tr,
+            line,
+            rect,
+            layout.font_size,
+            colors.fg,
+            HAlign::Left,
+        );
+    }
+}
+
 fn render_empty_message(
     canvas: &mut Canvas,
     tr: &mut TextRenderer,
@@ -89,10 +160,31 @@ fn render_job_row(
     layout: &RenderLayout,
 ) {
     let icon = job_status_icon(&job.status);
-    let text = format!("{} {}", icon, job.description);
+    let text = if job.is_batch() {
+        let fold = if job.expanded { "\u{25be}" } else { "\u{25b8}" };
+        format!("{} {} {}", icon, fold, job.description)
+    } else {
+        format!("{} {}", icon, job.description)
+    };
+    // Children sit under their batch
+    let indent = if job.parent.is_some() {
+        layout.padding * 2
+    } else {
+        0
+    };
 
-    let text_w = (w as i32 - layout.padding * 3 - 100) as u32;
-    let rect = Rect::new(x + layout.padding, y, text_w, layout.line_height as u32);
+    let detail_w = if job.progress.is_some() && matches!(job.status, JobStatus::Running) {
+        DETAIL_WIDTH
+    } else {
+        0
+    };
+    let text_w = (w as i32 - layout.padding * 3 - STATUS_WIDTH - detail_w - indent).max(0) as u32;
+    let rect = Rect::new(
+        x + layout.padding + indent,
+        y,
+        text_w,
+        layout.line_height as u32,
+    );
     draw_text(
         canvas,
         tr,
@@ -103,19 +195,9 @@ fn render_job_row(
         HAlign::Left,
     );
 
-    let prog_x = x + w as i32 - layout.padding - 100;
-    let prog_rect = Rect::new(prog_x, y, 100, layout.line_height as u32);
-    render_job_status(
-        canvas,
-        tr,
-        &job.status,
-        job.progress,
-        prog_x,
-        y,
-        colors,
-        layout,
-        prog_rect,
-    );
+    let prog_x = x + w as i32 - layout.padding - STATUS_WIDTH;
+    let prog_rect = Rect::new(prog_x, y, STATUS_WIDTH as u32, layout.line_height as u32);
+    render_job_status(canvas, tr, job, prog_x, y, colors, layout, prog_rect);
 }
 
 fn job_status_icon(status: &JobStatus) -> &'static str {
@@ -124,6 +206,7 @@ fn job_status_icon(status: &JobStatus) -> &'static str {
         JobStatus::Running => "\u{f110}",
         JobStatus::Complete => "\u{f00c}",
         JobStatus::Failed(_) => "\u{f00d}",
+        JobStatus::Cancelled => "\u{f05e}",
     }
 }
 
@@ -131,8 +214,7 @@ fn job_status_icon(status: &JobStatus) -> &'static str {
 fn render_job_status(
     canvas: &mut Canvas,
     tr: &mut TextRenderer,
-    status: &JobStatus,
-    progress: Option<f32>,
+    job: &Job,
     x: i32,
     y: i32,
     colors: &RenderColors,
@@ -140,13 +222,18 @@ fn render_job_status(
     rect: Rect,
 ) {
     let small_font = layout.font_size - 2.0;
-    match status {
+    match &job.status {
         JobStatus::Running => {
-            render_running_status(canvas, tr, progress, x, y, colors, rect, small_font)
+            render_running_status(canvas, tr, job, x, y, colors, rect, small_font)
         }
         JobStatus::Complete => render_complete_status(canvas, tr, rect, small_font),
-        JobStatus::Failed(msg) => render_failed_status(canvas, tr, msg, rect, small_font),
-        JobStatus::Pending => render_pending_status(canvas, tr, colors, rect, small_font),
+        JobStatus::Failed(error) => {
+            render_failed_status(canvas, tr, &error.message, rect, small_font)
+        }
+        JobStatus::Pending => render_plain_status(canvas, tr, "Pending", colors, rect, small_font),
+        JobStatus::Cancelled => {
+            render_plain_status(canvas, tr, "Cancelled", colors, rect, small_font)
+        }
     }
 }
 
@@ -154,17 +241,48 @@ fn render_job_status(
 fn render_running_status(
     canvas: &mut Canvas,
     tr: &mut TextRenderer,
-    progress: Option<f32>,
+    job: &Job,
     x: i32,
     y: i32,
     colors: &RenderColors,
     rect: Rect,
     font_size: f32,
 ) {
-    if let Some(p) = progress {
+    if let Some(progress) = &job.progress {
+        let mut detail = vec![progress.current.clone()];
+        if progress.files_total > 1 {
+            detail.push(format!("{}/{}", progress.files_done, progress.files_total));
+        }
+        if job.control.is_paused() {
+            detail.push("Paused".to_string());
+        } else {
+            if let Some(rate) = job.throughput() {
+                detail.push(format!("{}/s", format_size(rate as u64)));
+            }
+            if let Some(eta) = job.eta() {
+                detail.push(format_eta(eta));
+            }
+        }
+        let detail_rect = Rect::new(x - DETAIL_WIDTH, y, DETAIL_WIDTH as u32, rect.height);
+        draw_text(
+            canvas,
+            tr,
+            &detail.join("  "),
+            detail_rect,
+            font_size,
+            colors.fg,
+            HAlign::Right,
+        );
+
         let bar_y = y + 8;
-        canvas.fill_rect(x as f32, bar_y as f32, 100.0, 8.0, colors.border);
-        let fill = (100.0 * p) as u32;
+        canvas.fill_rect(
+            x as f32,
+            bar_y as f32,
+            STATUS_WIDTH as f32,
+            8.0,
+            colors.border,
+        );
+        let fill = (STATUS_WIDTH as f32 * progress.fraction()) as u32;
         canvas.fill_rect(
             x as f32,
             bar_y as f32,
@@ -176,7 +294,11 @@ fn render_running_status(
         draw_text(
             canvas,
             tr,
-            "Running...",
+            if job.control.is_paused() {
+                "Paused"
+            } else {
+                "Running..."
+            },
             rect,
             font_size,
             colors.fg,
@@ -185,6 +307,16 @@ fn render_running_status(
     }
 }
 
+/// Remaining time as `m:ss`, or `h:mm:ss` past an hour
+fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
 fn render_complete_status(canvas: &mut Canvas, tr: &mut TextRenderer, rect: Rect, font_size: f32) {
     draw_text(
         canvas,
@@ -216,20 +348,13 @@ fn render_failed_status(
     );
 }
 
-fn render_pending_status(
+fn render_plain_status(
     canvas: &mut Canvas,
     tr: &mut TextRenderer,
+    text: &str,
     colors: &RenderColors,
     rect: Rect,
     font_size: f32,
 ) {
-    draw_text(
-        canvas,
-        tr,
-        "Pending",
-        rect,
-        font_size,
-        colors.fg,
-        HAlign::Right,
-    );
+    draw_text(canvas, tr, text, rect, font_size, colors.fg, HAlign::Right);
 }